@@ -0,0 +1,18 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use client::pool_utils::stable::{compute_a, compute_d};
+
+fuzz_target!(|input: (u64, u64, u64)| {
+    let (amp, amount_a, amount_b) = input;
+    let leverage = match compute_a(amp) {
+        Some(leverage) => leverage,
+        None => return,
+    };
+    let (amount_a, amount_b) = (amount_a as u128, amount_b as u128);
+
+    // the invariant of a pool is at most the sum of its reserves
+    if let Some(d) = compute_d(leverage, amount_a, amount_b) {
+        assert!(d <= amount_a + amount_b, "d {} > {} + {}", d, amount_a, amount_b);
+    }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use client::pool_utils::stable::{compute_a, compute_d, compute_new_destination_amount};
+
+fuzz_target!(|input: (u64, u64, u64, u64, u128)| {
+    let (amp, amount_a, amount_b, amount_in, d_val) = input;
+    let leverage = match compute_a(amp) {
+        Some(leverage) => leverage,
+        None => return,
+    };
+
+    // any input -- no panics
+    compute_new_destination_amount(leverage, amount_a as u128, d_val);
+
+    // a swap into the pool never leaves more of the destination than before
+    let (amount_a, amount_b) = (amount_a as u128, amount_b as u128);
+    let d = match compute_d(leverage, amount_a, amount_b) {
+        Some(d) if d > 0 => d,
+        _ => return,
+    };
+    if let Some(y) = compute_new_destination_amount(leverage, amount_a + amount_in as u128, d) {
+        // + 1 for the ceil div
+        assert!(y <= amount_b + 1, "y {} > {} + 1", y, amount_b);
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use client::pool_utils::serum::{ask_quote, bid_quote, bid_quote_exact_out, BookOrder, FeeTier};
+
+fuzz_target!(|input: (Vec<BookOrder>, u64, u64, u64, bool)| {
+    let (orders, coin_lot_size, pc_lot_size, amount_in, stable) = input;
+    let fee_tier = if stable { FeeTier::Stable } else { FeeTier::Base };
+
+    // buying base is bounded by the depth of the book
+    let depth = orders.iter().fold(0u64, |depth, order| {
+        depth.saturating_add(order.quantity.saturating_mul(coin_lot_size))
+    });
+    let amount_out = bid_quote(orders.clone(), coin_lot_size, pc_lot_size, fee_tier, amount_in);
+    assert!(amount_out <= depth, "out {} > depth {}", amount_out, depth);
+
+    // the same lots off the same orders -- never more than was paid
+    if let Some(cost) = bid_quote_exact_out(orders.clone(), coin_lot_size, pc_lot_size, fee_tier, amount_out) {
+        assert!(cost <= amount_in, "buying {} back costs {} > {}", amount_out, cost, amount_in);
+    }
+
+    ask_quote(orders, coin_lot_size, pc_lot_size, fee_tier, amount_in);
+});
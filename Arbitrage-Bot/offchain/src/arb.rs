@@ -1,5 +1,4 @@
 use anchor_client::solana_client::rpc_client::RpcClient;
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
 
 use anchor_client::solana_sdk::pubkey::Pubkey;
 
@@ -8,31 +7,121 @@ use anchor_client::{Cluster, Program};
 use std::collections::{HashMap, HashSet};
 
 use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signature;
 use solana_sdk::transaction::Transaction;
 
-use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Instant;
 
 use std::vec;
 
-use log::info;
+use log::{debug, info, warn};
 
 use tmp::accounts as tmp_accounts;
 use tmp::instruction as tmp_ix;
+use tmp::ix_data::{HopSplit, RouteHop};
 
+use crate::constants::*;
+use crate::error::QuoteError;
+use crate::events::{parse_route_events, RouteReport};
+use crate::health::{PoolHealth, PoolId};
+use crate::pipeline::{TxSender, TxStatus};
 use crate::pool::PoolOperations;
+use crate::registry::TokenRegistry;
+
+use crate::router::Route;
+use crate::schedule::{pick_batch, ExecutedSwaps, RouteCandidate};
+use crate::split::split_hop;
+use crate::utils::{
+    apply_slippage, derive_mint_stats_address, derive_swap_state_address, derive_token_address,
+    slot_spread, PoolGraph, PoolIndex, PoolQuote,
+};
+
+/// Sweeper mode: sell leftover intermediate tokens back to the base mints
+pub struct DustSweep {
+    // (mint idx, min amount out worth a sweep -- scaled) -- ties between equally
+    // near base mints go to the first one
+    pub base_mints: Vec<(usize, u128)>,
+    pub slippage_bps: u64, // per-hop bound under the quote
+    pub max_hops: usize,
+}
+
+/// An operator wallet -- each has its own swap state, mint stats + ATAs
+pub struct Operator {
+    pub keypair: Rc<Keypair>,
+    // treasury of its start mint stats -- Some = sweep profit above the working balance after each arb
+    pub sweep_treasury: Option<Pubkey>,
+}
+
+// pools of a hop (the split ones too) with their inputs + its quoted output
+struct HopQuote {
+    mints: [Pubkey; 2],
+    pools: Vec<PoolQuote>,
+    amounts_in: Vec<u128>,
+    amount_out: u128,
+}
 
-use crate::utils::{derive_token_address, PoolGraph, PoolIndex, PoolQuote};
+// a hop as the breaker sees it -- no pool pointers, they'd outlive the round
+struct SentHop {
+    pools: Vec<(PoolId, String)>,
+    amount_out: u128,
+}
+
+/// A sent route until its tx lands -- its pools and the quoted hops its
+/// logs are checked against
+pub struct SentRoute {
+    pools: Vec<(PoolId, String)>,
+    hops: Vec<SentHop>,
+}
+
+// best sweep route found so far
+struct SweepRoute {
+    mint_idxs: Vec<usize>,
+    pools: Vec<PoolQuote>,
+    amount_out: u128,
+    base_rank: usize, // position in DustSweep.base_mints
+}
+
+impl SweepRoute {
+    // nearest base mint first, then base mint order, then the largest output
+    fn is_better_than(&self, other: &SweepRoute) -> bool {
+        (self.pools.len(), self.base_rank) < (other.pools.len(), other.base_rank)
+            || ((self.pools.len(), self.base_rank) == (other.pools.len(), other.base_rank)
+                && self.amount_out > other.amount_out)
+    }
+}
 
 pub struct Arbitrager {
     pub token_mints: Vec<Pubkey>,
     pub graph_edges: Vec<HashSet<usize>>, // used for quick searching over the graph
     pub graph: PoolGraph,
     pub cluster: Cluster,
+    // per-hop slippage tolerance (bps) for min_amount_out -- None = no bounds
+    pub hop_slippage_bps: Option<u64>,
+    // send the whole arb as one route ix instead of start/swap/profit ixs
+    pub compact_route: bool,
+    // split each hop across the parallel pools of its pair in this many chunks
+    // when it beats the single pool -- compact routes only
+    pub split_steps: Option<usize>,
+    // print each arb and its ixs instead of sending them (replays)
+    pub dry_run: bool,
+    // skip cycles whose pool states are more than this many slots apart
+    pub max_slot_spread: Option<u64>,
+    // symbols, decimals + USD prices for the logs
+    pub tokens: TokenRegistry,
+    // per-pool circuit breaker -- fed by simulations and landed txs
+    pub health: RefCell<PoolHealth>,
+    // routes of the sent txs until they land
+    pub pending_txs: RefCell<HashMap<Signature, SentRoute>>,
     // vv -- need to clone these explicitly -- vv
     pub owner: Rc<Keypair>,
+    // wallets routes are sent from, operators[0] = owner
+    pub operators: Vec<Operator>,
     pub program: Program,
-    pub connection: RpcClient,
+    pub connection: RpcClient, // localnet simulations
+    // background sends + blockhash -- None for replays
+    pub sender: Option<TxSender>,
 }
 
 impl Arbitrager {
@@ -43,7 +132,7 @@ impl Arbitrager {
         curr_balance: u128,
         path: Vec<usize>,
         pool_path: Vec<PoolQuote>,
-        sent_arbs: &mut HashSet<String>,
+        candidates: &mut Vec<RouteCandidate>,
     ) {
         let src_curr = path[path.len() - 1]; // last mint
         let src_mint = self.token_mints[src_curr];
@@ -74,9 +163,14 @@ impl Arbitrager {
             let dst_mint = self.token_mints[dst_mint_idx];
 
             for pool in pools {
-                let new_balance =
-                    pool.0
-                        .get_quote_with_amounts_scaled(curr_balance, &src_mint, &dst_mint);
+                // no quote = no edge, the search goes on without it
+                let new_balance = match pool.0.get_quote_with_amounts_scaled(curr_balance, &src_mint, &dst_mint) {
+                    Ok(new_balance) => new_balance,
+                    Err(err) => {
+                        debug!("skipping {}: {}", self.pool_label(pool), err);
+                        continue;
+                    }
+                };
 
                 let mut new_path = path.clone();
                 new_path.push(dst_mint_idx);
@@ -84,34 +178,27 @@ impl Arbitrager {
                 let mut new_pool_path = pool_path.clone();
                 new_pool_path.push(pool.clone()); // clone the pointer
 
+                // the spread only grows deeper in -- stop here
+                if let Some(max_slot_spread) = self.max_slot_spread {
+                    if slot_spread(&new_pool_path) > max_slot_spread {
+                        continue;
+                    }
+                }
+
                 if dst_mint_idx == start_mint_idx {
                     // info!("{:?} -> {:?} (-{:?})", init_balance, new_balance, init_balance - new_balance);
 
                     // if new_balance > init_balance - 1086310399 {
                     if new_balance > init_balance {
-                        // ... profitable arb!
-                        info!("found arbitrage: {:?} -> {:?}", init_balance, new_balance);
-
-                        // check if arb was sent with a larger size
-                        // key = {mint_path}{pool_names}
-                        let mint_keys: Vec<String> =
-                            new_path.clone().iter_mut().map(|i| i.to_string()).collect();
-                        let pool_keys: Vec<String> =
-                            new_pool_path.iter().map(|p| p.0.get_name()).collect();
-                        let arb_key = format!("{}{}", mint_keys.join(""), pool_keys.join(""));
-                        if sent_arbs.contains(&arb_key) {
-                            info!("arb already sent...");
-                            continue; // dont re-send an already sent arb -- bad for network
-                        } else {
-                            sent_arbs.insert(arb_key);
-                        }
-
-                        let ixs = self.get_arbitrage_instructions(
-                            init_balance,
-                            &new_path,
-                            &new_pool_path,
-                        );
-                        self.send_ixs(ixs);
+                        // ... profitable arb! -- sent once the search is done
+                        let route = RouteCandidate {
+                            mint_idxs: new_path,
+                            pools: new_pool_path,
+                            amount_in: init_balance,
+                            amount_out: new_balance,
+                        };
+                        info!("found arbitrage: {}", self.route_summary(&route));
+                        candidates.push(route);
                     }
                 } else if !path.contains(&dst_mint_idx) {
                     // ... search deeper
@@ -121,94 +208,574 @@ impl Arbitrager {
                         new_balance,   // !
                         new_path,      // !
                         new_pool_path, // !
-                        sent_arbs,
+                        candidates,
                     );
                 }
             }
         }
     }
 
+    /// e.g. `100 USDC -> 100.2 USDC (+$0.20) via USDC -> SOL -> USDC ([..])`
+    pub fn route_summary(&self, route: &RouteCandidate) -> String {
+        let start_mint = self.token_mints[route.mint_idxs[0]];
+        let mints: Vec<Pubkey> = route.mint_idxs.iter().map(|i| self.token_mints[*i]).collect();
+        let pool_names: Vec<String> = route.pools.iter().map(|p| p.0.get_name()).collect();
+        format!(
+            "{} -> {} (+{}) via {} ({:?})",
+            self.tokens.fmt_amount(&start_mint, route.amount_in),
+            self.tokens.fmt_amount(&start_mint, route.amount_out),
+            self.tokens.fmt_usd(&start_mint, route.profit()),
+            self.tokens.fmt_path(&mints),
+            pool_names,
+        )
+    }
+
+    /// Send the routes found by a search, in batches without shared write
+    /// accounts (one route per operator) -- what's left after a batch is
+    /// re-quoted as if it had executed and dropped once it isn't profitable
+    ///
+    /// `balances` = start mint balance of each operator, the ones without any
+    /// are left out
+    pub fn send_routes(&self, mut routes: Vec<RouteCandidate>, balances: &[u128]) {
+        let funded: Vec<usize> = (0..balances.len()).filter(|idx| balances[*idx] > 0).collect();
+        let funded_balances: Vec<u128> = funded.iter().map(|idx| balances[*idx]).collect();
+
+        let mut executed = ExecutedSwaps::default();
+        let mut batch_count = 0;
+        while !routes.is_empty() {
+            let write_accounts: Vec<HashSet<Pubkey>> =
+                routes.iter().map(|route| self.pool_write_accounts(route, &funded)).collect();
+            let batch = pick_batch(&routes, &write_accounts, &funded_balances);
+            if batch.is_empty() {
+                break; // none fits an operator's balance
+            }
+            batch_count += 1;
+
+            for (route_idx, wallet_idx) in batch.iter() {
+                let route = &routes[*route_idx];
+                let operator = &self.operators[funded[*wallet_idx]];
+                info!(
+                    "batch {}: {} from {}",
+                    batch_count, self.route_summary(route), operator.keypair.pubkey()
+                );
+                if self.dry_run {
+                    println!("arbitrage: {}", self.route_summary(route));
+                }
+                let (ixs, hops) = match self.get_arbitrage_instructions(
+                    operator,
+                    route.amount_in,
+                    &route.mint_idxs,
+                    &route.pools,
+                    &executed,
+                ) {
+                    Ok(ixs_hops) => ixs_hops,
+                    Err(err) => {
+                        warn!("dropping route {}: {}", self.route_summary(route), err);
+                        continue;
+                    }
+                };
+                self.send_ixs(ixs, &hops, &operator.keypair);
+
+                for hop in hops.iter() {
+                    for (pool, amount_in) in hop.pools.iter().zip(hop.amounts_in.iter()) {
+                        executed.record(pool, *amount_in, &hop.mints[0], &hop.mints[1]);
+                    }
+                }
+            }
+
+            // the rest, on top of the batch
+            let sent: HashSet<usize> = batch.iter().map(|(route_idx, _)| *route_idx).collect();
+            routes = routes
+                .into_iter()
+                .enumerate()
+                .filter(|(route_idx, _)| !sent.contains(route_idx))
+                .filter_map(|(_, mut route)| {
+                    let mints: Vec<Pubkey> = route.mint_idxs.iter().map(|i| self.token_mints[*i]).collect();
+                    route.amount_out = executed.quote_route(&mints, &route.pools, route.amount_in).ok()?;
+                    (route.amount_out > route.amount_in).then(|| route)
+                })
+                .collect();
+        }
+    }
+
+    // pool accounts a route writes from any of `operators` -- with splits, those
+    // of every parallel pool of its hops -- each operator's own ATAs aren't
+    // included (one route per wallet), nor the pools which can't swap the hop
+    // (they won't be in its ix)
+    fn pool_write_accounts(&self, route: &RouteCandidate, operators: &[usize]) -> HashSet<Pubkey> {
+        let mut accounts = HashSet::new();
+        for (i, pool) in route.pools.iter().enumerate() {
+            let [mint_idx0, mint_idx1] = [route.mint_idxs[i], route.mint_idxs[i + 1]];
+            let [mint0, mint1] = [self.token_mints[mint_idx0], self.token_mints[mint_idx1]];
+
+            let mut hop_pools = vec![pool.clone()];
+            if self.compact_route && self.split_steps.is_some() {
+                if let Some(pools) = self.graph.0.get(&PoolIndex(mint_idx0)).and_then(|edge| edge.0.get(&PoolIndex(mint_idx1))) {
+                    hop_pools = pools.clone();
+                }
+            }
+            for operator_idx in operators {
+                let operator = self.operators[*operator_idx].keypair.pubkey();
+                let operator_accounts =
+                    [operator, derive_token_address(&operator, &mint0), derive_token_address(&operator, &mint1)];
+                for hop_pool in hop_pools.iter() {
+                    let metas = match hop_pool.0.route_hop(&operator, &mint0, &mint1) {
+                        Ok((_, metas)) => metas,
+                        Err(_) => continue,
+                    };
+                    accounts.extend(
+                        metas
+                            .iter()
+                            .filter(|meta| meta.is_writable && !operator_accounts.contains(&meta.pubkey))
+                            .map(|meta| meta.pubkey),
+                    );
+                }
+            }
+        }
+        accounts
+    }
+
     fn get_arbitrage_instructions(
         &self,
+        operator: &Operator,
         swap_start_amount: u128,
         mint_idxs: &Vec<usize>,
         pools: &Vec<PoolQuote>,
-    ) -> Vec<Instruction> {
+        executed: &ExecutedSwaps,
+    ) -> Result<(Vec<Instruction>, Vec<HopQuote>), QuoteError> {
         // gather swap ixs
         let mut ixs = vec![];
-        let (swap_state_pda, _) =
-            Pubkey::find_program_address(&[b"swap_state"], &self.program.id());
+        let mut hops = vec![];
+        let owner = operator.keypair.pubkey();
+        let swap_state_pda = derive_swap_state_address(&self.program.id(), &owner);
 
         let src_mint = self.token_mints[mint_idxs[0]];
-        let src_ata = derive_token_address(&self.owner.pubkey(), &src_mint);
-
-        // initialize swap ix
-        let ix = self
-            .program
-            .request()
-            .accounts(tmp_accounts::TokenAndSwapState {
-                src: src_ata,
-                swap_state: swap_state_pda,
-            })
-            .args(tmp_ix::StartSwap {
-                swap_input: swap_start_amount as u64,
-            })
-            .instructions()
-            .unwrap();
-        ixs.push(ix);
-
+        let src_ata = derive_token_address(&owner, &src_mint);
+        let mint_stats_pda =
+            derive_mint_stats_address(&self.program.id(), &owner, &src_mint);
+
+        let mut hop_amount = swap_start_amount;
+        let mut swap_ixs = vec![];
+        let mut route_hops = vec![];
+        let mut route_accounts = vec![];
         for i in 0..mint_idxs.len() - 1 {
             let [mint_idx0, mint_idx1] = [mint_idxs[i], mint_idxs[i + 1]];
             let [mint0, mint1] = [self.token_mints[mint_idx0], self.token_mints[mint_idx1]];
             let pool = &pools[i];
 
-            let swap_ix = pool
+            // re-quote the hop so the swap fails at the pool which moved
+            let hop_amount_in = hop_amount;
+            hop_amount = executed.quote(pool, hop_amount_in, &mint0, &mint1)?;
+
+            if self.compact_route {
+                let split = self.split_steps.and_then(|steps| {
+                    let pools = self.graph.0.get(&PoolIndex(mint_idx0))?.0.get(&PoolIndex(mint_idx1))?;
+                    // splits are quoted on the state before the routes already sent
+                    if pools.len() < 2 || pools.iter().any(|pool| executed.touches(pool)) {
+                        return None;
+                    }
+                    let split = split_hop(pools, hop_amount_in, &mint0, &mint1, steps)
+                        .and_then(|split| split.requote(hop_amount_in, &mint0, &mint1))
+                        .ok()?;
+                    // a split only pays off when the price impact saved beats the rounding
+                    if split.pools.len() > 1 && split.amount_out > hop_amount {
+                        Some(split)
+                    } else {
+                        None
+                    }
+                });
+
+                let (hop_pools, hop_amounts_in) = match split.as_ref() {
+                    Some(split) => (split.pools.clone(), split.amounts_in.clone()),
+                    None => (vec![pool.clone()], vec![hop_amount_in]),
+                };
+                let (hop_pool, splits) = match split.as_ref() {
+                    Some(split) => {
+                        let amounts: Vec<String> =
+                            split.amounts_in.iter().map(|amount| self.tokens.fmt_amount(&mint0, *amount)).collect();
+                        info!("splitting hop across {} pools: {:?}", split.pools.len(), amounts);
+                        hop_amount = split.amount_out;
+                        (&split.pools[0], &split.pools[1..])
+                    }
+                    None => (pool, &[][..]),
+                };
+                let min_amount_out = self.min_amount_out(hop_amount);
+
+                let (dex, hop_accounts) = hop_pool.0.route_hop(&owner, &mint0, &mint1)?;
+                let accounts_start = route_accounts.len() as u8;
+                route_accounts.extend(hop_accounts);
+                let accounts_end = route_accounts.len() as u8;
+
+                let mut hop_splits = vec![];
+                let share_bps = split.as_ref().map(|split| split.share_bps()).unwrap_or_default();
+                for (split_pool, share_bps) in splits.iter().zip(share_bps) {
+                    let (dex, split_accounts) = split_pool.0.route_hop(&owner, &mint0, &mint1)?;
+                    hop_splits.push(HopSplit {
+                        dex,
+                        accounts_start: route_accounts.len() as u8,
+                        accounts_end: (route_accounts.len() + split_accounts.len()) as u8,
+                        share_bps,
+                    });
+                    route_accounts.extend(split_accounts);
+                }
+
+                route_hops.push(RouteHop {
+                    dex,
+                    accounts_start,
+                    accounts_end,
+                    min_amount_out,
+                    splits: hop_splits,
+                });
+                hops.push(HopQuote {
+                    mints: [mint0, mint1],
+                    pools: hop_pools,
+                    amounts_in: hop_amounts_in,
+                    amount_out: hop_amount,
+                });
+            } else {
+                let swap_ix = pool.0.swap_ix(
+                    &self.program,
+                    &owner,
+                    &mint0,
+                    &mint1,
+                    self.min_amount_out(hop_amount),
+                )?;
+                swap_ixs.push(swap_ix);
+                hops.push(HopQuote {
+                    mints: [mint0, mint1],
+                    pools: vec![pool.clone()],
+                    amounts_in: vec![hop_amount_in],
+                    amount_out: hop_amount,
+                });
+            }
+        }
+
+        let swap_state_accounts = || tmp_accounts::TokenAndSwapState {
+            src: src_ata,
+            swap_state: swap_state_pda,
+            mint_stats: mint_stats_pda,
+            authority: owner,
+        };
+
+        if self.compact_route {
+            // ROUTE instruction -- every hop + the profit check
+            let mut ix = self
+                .program
+                .request()
+                .accounts(swap_state_accounts())
+                .args(tmp_ix::Route {
+                    swap_input: swap_start_amount as u64,
+                    hops: route_hops,
+                })
+                .instructions()
+                .unwrap();
+            ix[0].accounts.extend(route_accounts); // hop accounts = remaining accounts
+            ixs.push(ix);
+        } else {
+            // initialize swap ix
+            let ix = self
+                .program
+                .request()
+                .accounts(swap_state_accounts())
+                .args(tmp_ix::StartSwap {
+                    swap_input: swap_start_amount as u64,
+                    flash_loan: None, // routes are funded from the operator wallet
+                })
+                .instructions()
+                .unwrap();
+            ixs.push(ix);
+
+            ixs.extend(swap_ixs);
+
+            // PROFIT OR REVERT instruction
+            let ix = self
+                .program
+                .request()
+                .accounts(swap_state_accounts())
+                .args(tmp_ix::ProfitOrRevert { flash_repay: None })
+                .instructions()
+                .unwrap();
+            ixs.push(ix);
+        }
+
+        // keep the operator wallet at its working balance
+        if let Some(treasury) = operator.sweep_treasury {
+            let ix = self
+                .program
+                .request()
+                .accounts(tmp_accounts::SweepProfit {
+                    src: src_ata,
+                    treasury,
+                    swap_state: swap_state_pda,
+                    mint_stats: mint_stats_pda,
+                    authority: owner,
+                    token_program: *TOKEN_PROGRAM_ID,
+                })
+                .args(tmp_ix::SweepProfit {})
+                .instructions()
+                .unwrap();
+            ixs.push(ix);
+        }
+
+        // flatten to Vec<Instructions>
+        Ok((ixs.concat(), hops))
+    }
+
+    fn min_amount_out(&self, quote_amount: u128) -> Option<u64> {
+        self.hop_slippage_bps.map(|bps| apply_slippage(quote_amount, bps))
+    }
+
+    /// Sell every non-base balance worth a sweep back to its nearest base mint
+    ///
+    /// `balances` = (mint idx, scaled balance of the owner's ATA)
+    pub fn sweep_dust(&self, sweep: &DustSweep, balances: &[(usize, u128)]) {
+        for (mint_idx, amount) in balances.iter() {
+            let (mint_idx, amount) = (*mint_idx, *amount);
+            if amount == 0 || sweep.base_mints.iter().any(|(base, _)| *base == mint_idx) {
+                continue;
+            }
+
+            let mut best = None;
+            self.search_sweep_routes(sweep, amount, vec![mint_idx], vec![], &mut best);
+            let route = match best {
+                Some(route) => route,
+                None => {
+                    info!("no sweep route for {}", self.tokens.symbol(&self.token_mints[mint_idx]));
+                    continue;
+                }
+            };
+
+            let (base_idx, min_amount_out) = sweep.base_mints[route.base_rank];
+            if route.amount_out < min_amount_out {
+                continue; // dust -- not worth a tx
+            }
+            info!(
+                "sweeping {} -> {} ({}, {} hops)",
+                self.tokens.fmt_amount(&self.token_mints[mint_idx], amount),
+                self.tokens.fmt_amount(&self.token_mints[base_idx], route.amount_out),
+                self.tokens.fmt_usd(&self.token_mints[base_idx], route.amount_out),
+                route.pools.len(),
+            );
+
+            let (ixs, hops) = match self.get_sweep_instructions(amount, &route, sweep.slippage_bps) {
+                Ok(ixs_hops) => ixs_hops,
+                Err(err) => {
+                    warn!("no sweep of {}: {}", self.tokens.symbol(&self.token_mints[mint_idx]), err);
+                    continue;
+                }
+            };
+            self.send_ixs(ixs, &hops, &self.owner);
+        }
+    }
+
+    // non-cyclic routes from the last mint of `path` to a base mint
+    fn search_sweep_routes(
+        &self,
+        sweep: &DustSweep,
+        curr_balance: u128,
+        path: Vec<usize>,
+        pool_path: Vec<PoolQuote>,
+        best: &mut Option<SweepRoute>,
+    ) {
+        if pool_path.len() == sweep.max_hops {
+            return;
+        }
+        // a longer route can't beat one which is already nearer
+        if let Some(best) = best.as_ref() {
+            if best.pools.len() <= pool_path.len() {
+                return;
+            }
+        }
+
+        let src_curr = path[path.len() - 1]; // last mint
+        let src_mint = self.token_mints[src_curr];
+
+        for dst_mint_idx in &self.graph_edges[src_curr] {
+            let dst_mint_idx = *dst_mint_idx;
+            if path.contains(&dst_mint_idx) {
+                continue;
+            }
+            let dst_mint = self.token_mints[dst_mint_idx];
+            let pools = self
+                .graph
                 .0
-                .swap_ix(&self.program, &self.owner.pubkey(), &mint0, &mint1);
-            ixs.push(swap_ix);
+                .get(&PoolIndex(src_curr))
+                .unwrap()
+                .0
+                .get(&PoolIndex(dst_mint_idx))
+                .unwrap();
+
+            for pool in pools {
+                let new_balance = match pool.0.get_quote_with_amounts_scaled(curr_balance, &src_mint, &dst_mint) {
+                    Ok(new_balance) if new_balance > 0 => new_balance,
+                    _ => continue,
+                };
+
+                let mut new_path = path.clone();
+                new_path.push(dst_mint_idx);
+                let mut new_pool_path = pool_path.clone();
+                new_pool_path.push(pool.clone()); // clone the pointer
+
+                let base_rank = sweep.base_mints.iter().position(|(base, _)| *base == dst_mint_idx);
+                match base_rank {
+                    // stop at the first base mint reached
+                    Some(base_rank) => {
+                        let route = SweepRoute {
+                            mint_idxs: new_path,
+                            pools: new_pool_path,
+                            amount_out: new_balance,
+                            base_rank,
+                        };
+                        let is_better = match best.as_ref() {
+                            Some(best) => route.is_better_than(best),
+                            None => true,
+                        };
+                        if is_better {
+                            *best = Some(route);
+                        }
+                    }
+                    None => {
+                        self.search_sweep_routes(sweep, new_balance, new_path, new_pool_path, best);
+                    }
+                }
+            }
         }
+    }
 
-        // PROFIT OR REVERT instruction
-        let ix = self
-            .program
-            .request()
-            .accounts(tmp_accounts::TokenAndSwapState {
-                src: src_ata,
-                swap_state: swap_state_pda,
+    fn get_sweep_instructions(
+        &self,
+        swap_start_amount: u128,
+        route: &SweepRoute,
+        slippage_bps: u64,
+    ) -> Result<(Vec<Instruction>, Vec<HopQuote>), QuoteError> {
+        // re-quote every hop for its bound
+        let mints: Vec<Pubkey> = route.mint_idxs.iter().map(|idx| self.token_mints[*idx]).collect();
+        let mut amounts = vec![swap_start_amount];
+        for (i, pool) in route.pools.iter().enumerate() {
+            let amount = pool.0.get_quote_with_amounts_scaled(amounts[i], &mints[i], &mints[i + 1])?;
+            amounts.push(amount);
+        }
+        let hops = route
+            .pools
+            .iter()
+            .enumerate()
+            .map(|(i, pool)| HopQuote {
+                mints: [mints[i], mints[i + 1]],
+                pools: vec![pool.clone()],
+                amounts_in: vec![amounts[i]],
+                amount_out: amounts[i + 1],
             })
-            .args(tmp_ix::ProfitOrRevert {})
-            .instructions()
-            .unwrap();
-        ixs.push(ix);
+            .collect();
+        let route = Route { mints, pools: route.pools.clone(), amounts };
 
-        // flatten to Vec<Instructions>
-        ixs.concat()
+        Ok((route.swap_instructions(&self.program, &self.owner.pubkey(), slippage_bps)?, hops))
     }
 
-    fn send_ixs(&self, ixs: Vec<Instruction>) {
-        let owner: &Keypair = self.owner.borrow();
+    /// Name of a pool + the symbols of its mints
+    pub fn pool_label(&self, pool: &PoolQuote) -> String {
+        let symbols: Vec<String> = pool.0.get_mints().iter().map(|mint| self.tokens.symbol(mint)).collect();
+        format!("{}({})", pool.0.get_name(), symbols.join("/"))
+    }
+
+    fn health_ids(&self, pools: &[PoolQuote]) -> Vec<(PoolId, String)> {
+        pools.iter().map(|pool| (PoolId::of(&**pool.0), self.pool_label(pool))).collect()
+    }
+
+    fn sent_hops(&self, hops: &[HopQuote]) -> Vec<SentHop> {
+        hops.iter()
+            .map(|hop| SentHop { pools: self.health_ids(&hop.pools), amount_out: hop.amount_out })
+            .collect()
+    }
+
+    /// Feed the outcomes of the sent txs since the last call to the breaker
+    pub fn update_health(&self) {
+        let sender = match self.sender.as_ref() {
+            Some(sender) => sender,
+            None => return,
+        };
+        let now = Instant::now();
+        let mut health = self.health.borrow_mut();
+        for outcome in sender.outcomes() {
+            let route = match self.pending_txs.borrow_mut().remove(&outcome.signature) {
+                Some(route) => route,
+                None => continue,
+            };
+            match outcome.status {
+                TxStatus::Landed => {
+                    if !self.check_hops(&mut health, &route.hops, &outcome.logs, now) {
+                        health.record_success(&route.pools);
+                    }
+                }
+                TxStatus::Failed(err) => {
+                    warn!("{} reverted: {}", outcome.signature, err);
+                    health.record_failure(&route.pools, now);
+                }
+                TxStatus::Dropped => {} // says nothing about the pools
+            }
+        }
+    }
+
+    // the executed hops of the route in `logs` vs their quotes, true = one
+    // was off its quote (its pools are disabled)
+    fn check_hops(&self, health: &mut PoolHealth, hops: &[SentHop], logs: &[String], now: Instant) -> bool {
+        let events = parse_route_events(&self.program.id(), logs);
+        let report = match RouteReport::from_events(&events).into_iter().next() {
+            Some(report) => report,
+            None => return false,
+        };
+        let hop_pools: Vec<usize> = hops.iter().map(|hop| hop.pools.len()).collect();
+        let mut off_quote = false;
+        for (hop, amount_out) in hops.iter().zip(report.hop_outputs(&hop_pools)) {
+            off_quote |= health.record_hop(&hop.pools, hop.amount_out, amount_out as u128, now);
+        }
+        off_quote
+    }
+
+    fn send_ixs(&self, mut ixs: Vec<Instruction>, hops: &[HopQuote], signer: &Keypair) {
+        if self.dry_run {
+            println!("{:#?}", ixs);
+            return;
+        }
+
+        let sender = self.sender.as_ref().unwrap(); // only replays have no sender
+        // bundles pay for their slot
+        if let Some(jito) = sender.jito.as_ref() {
+            ixs.push(jito.tip_ix(&signer.pubkey()));
+        }
         let tx = Transaction::new_signed_with_payer(
             &ixs,
-            Some(&owner.pubkey()),
-            &[owner],
-            self.connection.get_latest_blockhash().unwrap(),
+            Some(&signer.pubkey()),
+            &[signer],
+            sender.blockhash.get(),
         );
 
+        let hops = self.sent_hops(hops);
+        let route_pools: Vec<(PoolId, String)> = hops.iter().flat_map(|hop| hop.pools.clone()).collect();
+
         if self.cluster == Cluster::Localnet {
             let res = self.connection.simulate_transaction(&tx).unwrap();
             println!("{:#?}", res);
+
+            // dry-run report of what the route would do
+            let logs = res.value.logs.clone().unwrap_or_default();
+            let events = parse_route_events(&self.program.id(), &logs);
+            for report in RouteReport::from_events(&events).iter() {
+                println!("{}", report);
+            }
+
+            let now = Instant::now();
+            let mut health = self.health.borrow_mut();
+            let off_quote = self.check_hops(&mut health, &hops, &logs, now);
+            match res.value.err {
+                Some(_) => health.record_failure(&route_pools, now),
+                None if !off_quote => health.record_success(&route_pools),
+                None => {}
+            }
         } else if self.cluster == Cluster::Mainnet {
-            let signature = self
-                .connection
-                .send_transaction_with_config(
-                    &tx,
-                    RpcSendTransactionConfig {
-                        skip_preflight: true,
-                        ..RpcSendTransactionConfig::default()
-                    },
-                )
-                .unwrap();
-            println!("signature: {:?}", signature);
+            // checked against its logs once it lands (`update_health`)
+            let route = SentRoute { pools: route_pools, hops };
+            self.pending_txs.borrow_mut().insert(tx.signatures[0], route);
+            sender.send(tx);
         }
     }
 }
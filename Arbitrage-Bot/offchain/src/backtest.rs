@@ -0,0 +1,284 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::Cluster;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use crate::pool::PoolOperations;
+use crate::snapshot::Snapshot;
+
+/// An arbitrage found on a snapshot, pools are indexes into
+/// `Backtester.pools` so it can be re-quoted on later snapshots
+#[derive(Debug, Clone)]
+pub struct Opportunity {
+    pub slot: u64,
+    pub mints: Vec<usize>, // start mint .. start mint
+    pub pools: Vec<usize>,
+    pub amount_in: u128,
+    pub amount_out: u128,
+}
+
+impl Opportunity {
+    pub fn profit(&self) -> u128 {
+        self.amount_out.saturating_sub(self.amount_in)
+    }
+}
+
+/// The engine's search over the pools of a pool set, one snapshot at a time
+pub struct Backtester {
+    pub pools: Vec<Box<dyn PoolOperations>>,
+    pub live: Vec<bool>, // false = some account of the pool isn't in the snapshot (or isn't valid)
+    pub token_mints: Vec<Pubkey>,
+    pub mint2idx: HashMap<Pubkey, usize>,
+    pub graph_edges: Vec<HashSet<usize>>,
+    pub edge_pools: HashMap<(usize, usize), Vec<usize>>,
+    pub max_hops: usize,
+}
+
+impl Backtester {
+    pub fn new(pools: Vec<Box<dyn PoolOperations>>, max_hops: usize) -> Self {
+        let mut backtester = Backtester {
+            live: vec![true; pools.len()],
+            pools: vec![],
+            token_mints: vec![],
+            mint2idx: HashMap::new(),
+            graph_edges: vec![],
+            edge_pools: HashMap::new(),
+            max_hops,
+        };
+
+        for (pool_idx, pool) in pools.iter().enumerate() {
+            let mints = pool.get_mints();
+            let idx0 = backtester.mint_idx(mints[0]);
+            let idx1 = backtester.mint_idx(mints[1]);
+            backtester.graph_edges[idx0].insert(idx1);
+            backtester.graph_edges[idx1].insert(idx0);
+            backtester.edge_pools.entry((idx0, idx1)).or_default().push(pool_idx);
+            backtester.edge_pools.entry((idx1, idx0)).or_default().push(pool_idx);
+        }
+        backtester.pools = pools;
+        backtester
+    }
+
+    fn mint_idx(&mut self, mint: Pubkey) -> usize {
+        if let Some(idx) = self.mint2idx.get(&mint) {
+            return *idx;
+        }
+        let idx = self.token_mints.len();
+        self.mint2idx.insert(mint, idx);
+        self.token_mints.push(mint);
+        self.graph_edges.push(HashSet::new());
+        idx
+    }
+
+    /// Set every pool to its accounts in `snapshot`
+    pub fn set_snapshot(&mut self, snapshot: &Snapshot, cluster: &Cluster) {
+        for (pool, live) in self.pools.iter_mut().zip(self.live.iter_mut()) {
+            let accounts = snapshot.get_multiple_accounts(&pool.get_update_accounts());
+            *live = pool.set_update_accounts(accounts, cluster.clone()).is_ok();
+        }
+    }
+
+    /// Output of `amount_in` along a route on the current snapshot, 0 if one
+    /// of its pools isn't live or can't quote its hop
+    pub fn quote_route(&self, mints: &[usize], pools: &[usize], amount_in: u128) -> u128 {
+        let mut amount = amount_in;
+        for (i, pool_idx) in pools.iter().enumerate() {
+            if !self.live[*pool_idx] {
+                return 0;
+            }
+            let mint_in = &self.token_mints[mints[i]];
+            let mint_out = &self.token_mints[mints[i + 1]];
+            amount = match self.pools[*pool_idx].get_quote_with_amounts_scaled(amount, mint_in, mint_out) {
+                Ok(amount) => amount,
+                Err(_) => return 0,
+            };
+        }
+        amount
+    }
+
+    /// Every profitable cycle of at most `max_hops` from the start mint
+    pub fn find_arbitrages(&self, slot: u64, start_mint_idx: usize, amount_in: u128) -> Vec<Opportunity> {
+        let mut opportunities = vec![];
+        self.search(slot, start_mint_idx, amount_in, amount_in, vec![start_mint_idx], vec![], &mut opportunities);
+        opportunities
+    }
+
+    fn search(
+        &self,
+        slot: u64,
+        start_mint_idx: usize,
+        init_balance: u128,
+        curr_balance: u128,
+        path: Vec<usize>,
+        pool_path: Vec<usize>,
+        opportunities: &mut Vec<Opportunity>,
+    ) {
+        if pool_path.len() == self.max_hops {
+            return;
+        }
+        let src_curr = path[path.len() - 1]; // last mint
+        let src_mint = self.token_mints[src_curr];
+
+        for dst_mint_idx in &self.graph_edges[src_curr] {
+            let dst_mint_idx = *dst_mint_idx;
+            if path.contains(&dst_mint_idx) && dst_mint_idx != start_mint_idx {
+                continue;
+            }
+            let dst_mint = self.token_mints[dst_mint_idx];
+
+            for pool_idx in self.edge_pools.get(&(src_curr, dst_mint_idx)).unwrap() {
+                if !self.live[*pool_idx] {
+                    continue;
+                }
+                let new_balance = match self.pools[*pool_idx].get_quote_with_amounts_scaled(curr_balance, &src_mint, &dst_mint) {
+                    Ok(new_balance) if new_balance > 0 => new_balance,
+                    _ => continue,
+                };
+
+                let mut new_path = path.clone();
+                new_path.push(dst_mint_idx);
+                let mut new_pool_path = pool_path.clone();
+                new_pool_path.push(*pool_idx);
+
+                if dst_mint_idx == start_mint_idx {
+                    if new_balance > init_balance {
+                        opportunities.push(Opportunity {
+                            slot,
+                            mints: new_path,
+                            pools: new_pool_path,
+                            amount_in: init_balance,
+                            amount_out: new_balance,
+                        });
+                    }
+                } else {
+                    self.search(slot, start_mint_idx, init_balance, new_balance, new_path, new_pool_path, opportunities);
+                }
+            }
+        }
+    }
+
+    /// Name of a pool + the first chars of its mints
+    pub fn pool_label(&self, pool_idx: usize) -> String {
+        let pool = &self.pools[pool_idx];
+        let mints: Vec<String> = pool
+            .get_mints()
+            .iter()
+            .map(|mint| mint.to_string()[..4].to_string())
+            .collect();
+        format!("{}({})", pool.get_name(), mints.join("/"))
+    }
+
+    pub fn route_label(&self, pools: &[usize]) -> String {
+        let labels: Vec<String> = pools.iter().map(|pool_idx| self.pool_label(*pool_idx)).collect();
+        labels.join(" -> ")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BacktestStats {
+    pub found: usize,
+    pub captured: usize,
+    pub profit: u128, // captured
+}
+
+impl BacktestStats {
+    fn record(&mut self, captured_profit: Option<u128>) {
+        self.found += 1;
+        if let Some(profit) = captured_profit {
+            self.captured += 1;
+            self.profit += profit;
+        }
+    }
+}
+
+/// Opportunities of a snapshot sequence and what executing them `latency`
+/// snapshots later would have captured
+#[derive(Debug, Clone, Default)]
+pub struct BacktestReport {
+    pub latency: usize,
+    pub snapshots: usize,
+    pub opportunities: usize,
+    pub captured: usize,
+    pub expired: usize,    // no longer profitable when executed
+    pub unresolved: usize, // executed past the last snapshot
+    pub quoted_profit: u128,
+    pub captured_profit: u128,
+    pub pools: HashMap<String, BacktestStats>,
+    pub dexes: HashMap<String, BacktestStats>,
+    pub routes: HashMap<String, BacktestStats>,
+    pub hops: BTreeMap<usize, BacktestStats>,
+    pub top: usize, // rows of the pool/route tables
+}
+
+impl BacktestReport {
+    /// Record `opportunity`, `amount_out` = its output re-quoted when it
+    /// executes (None = past the last snapshot)
+    pub fn record(&mut self, backtester: &Backtester, opportunity: &Opportunity, amount_out: Option<u128>) {
+        let amount_out = match amount_out {
+            Some(amount_out) => amount_out,
+            None => {
+                self.unresolved += 1;
+                return;
+            }
+        };
+        self.opportunities += 1;
+        self.quoted_profit += opportunity.profit();
+
+        // the profit check reverts a tx which lost its arb
+        let captured_profit = if amount_out > opportunity.amount_in {
+            self.captured += 1;
+            self.captured_profit += amount_out - opportunity.amount_in;
+            Some(amount_out - opportunity.amount_in)
+        } else {
+            self.expired += 1;
+            None
+        };
+
+        let mut dexes = HashSet::new();
+        for pool_idx in &opportunity.pools {
+            self.pools.entry(backtester.pool_label(*pool_idx)).or_default().record(captured_profit);
+            dexes.insert(backtester.pools[*pool_idx].get_name());
+        }
+        for dex in dexes {
+            self.dexes.entry(dex).or_default().record(captured_profit);
+        }
+        self.routes.entry(backtester.route_label(&opportunity.pools)).or_default().record(captured_profit);
+        self.hops.entry(opportunity.pools.len()).or_default().record(captured_profit);
+    }
+}
+
+// most profitable first, then most captured
+fn top_rows(stats: &HashMap<String, BacktestStats>, n: usize) -> Vec<(&String, &BacktestStats)> {
+    let mut rows: Vec<(&String, &BacktestStats)> = stats.iter().collect();
+    rows.sort_by(|(a_label, a), (b_label, b)| {
+        (b.profit, b.captured, a_label).cmp(&(a.profit, a.captured, b_label))
+    });
+    rows.truncate(n);
+    rows
+}
+
+impl fmt::Display for BacktestReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "snapshots: {} (latency {})", self.snapshots, self.latency)?;
+        writeln!(
+            f,
+            "opportunities: {} -- captured {}, expired {}, unresolved {}",
+            self.opportunities, self.captured, self.expired, self.unresolved
+        )?;
+        writeln!(f, "profit: quoted {}, captured {}", self.quoted_profit, self.captured_profit)?;
+
+        writeln!(f, "by hops:")?;
+        for (hops, stats) in self.hops.iter() {
+            writeln!(f, "  {}: found {} captured {} profit {}", hops, stats.found, stats.captured, stats.profit)?;
+        }
+        let tables = [("by dex", &self.dexes), ("top pools", &self.pools), ("top routes", &self.routes)];
+        for (title, stats) in tables {
+            writeln!(f, "{}:", title)?;
+            for (label, stats) in top_rows(stats, self.top) {
+                writeln!(f, "  {}: found {} captured {} profit {}", label, stats.found, stats.captured, stats.profit)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 //! Error types
 
 use num_derive::FromPrimitive;
-use solana_program::{decode_error::DecodeError, program_error::ProgramError};
+use solana_program::{decode_error::DecodeError, program_error::ProgramError, pubkey::Pubkey};
 use thiserror::Error;
 
 /// Errors that may be returned by the TokenSwap program.
@@ -114,5 +114,33 @@ impl<T> DecodeError<T> for SwapError {
     }
 }
 
-
-
+/// Why a pool can't quote or swap a pair -- the search skips the edge
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum QuoteError {
+    /// The reserves / book can't pay out the swap
+    #[error("Insufficient liquidity")]
+    InsufficientLiquidity,
+    /// The pool doesn't trade `mint_in` -> `mint_out`
+    #[error("Unsupported direction {mint_in} -> {mint_out}")]
+    UnsupportedDirection { mint_in: Pubkey, mint_out: Pubkey },
+    /// The mint isn't one of the pool's
+    #[error("Unknown mint {0}")]
+    UnknownMint(Pubkey),
+    /// The pool's accounts are missing or weren't loaded
+    #[error("Stale pool state: {0}")]
+    StaleState(String),
+    /// The curve math overflowed (or divided by zero)
+    #[error("Math overflow")]
+    MathOverflow,
+    /// The pool isn't deployed on the cluster
+    #[error("Unsupported cluster {0}")]
+    UnsupportedCluster(String),
+    /// The swap ix couldn't be built
+    #[error("Invalid swap ix: {0}")]
+    InvalidInstruction(String),
+}
+impl From<anchor_client::ClientError> for QuoteError {
+    fn from(e: anchor_client::ClientError) -> Self {
+        QuoteError::InvalidInstruction(e.to_string())
+    }
+}
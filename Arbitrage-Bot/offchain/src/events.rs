@@ -0,0 +1,151 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use std::fmt;
+use std::str::FromStr;
+
+use tmp::events::{HopExecuted, RouteCompleted, RouteStarted};
+
+/// Event emitted by the arb program during a route
+#[derive(Debug, Clone)]
+pub enum RouteEvent {
+    Started(RouteStarted),
+    Hop(HopExecuted),
+    Completed(RouteCompleted),
+}
+
+impl RouteEvent {
+    /// Decode the data of a `Program data:` log, None if it isn't a route event
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        let (disc, mut data) = data.split_at(8);
+        if disc == RouteStarted::discriminator() {
+            RouteStarted::deserialize(&mut data).ok().map(RouteEvent::Started)
+        } else if disc == HopExecuted::discriminator() {
+            HopExecuted::deserialize(&mut data).ok().map(RouteEvent::Hop)
+        } else if disc == RouteCompleted::discriminator() {
+            RouteCompleted::deserialize(&mut data).ok().map(RouteEvent::Completed)
+        } else {
+            None
+        }
+    }
+}
+
+/// Route events of `program_id` in transaction logs, in emit order
+///
+/// Data logged by other programs (the DEXs it CPIs into) is skipped
+pub fn parse_route_events(program_id: &Pubkey, logs: &[String]) -> Vec<RouteEvent> {
+    let mut events = vec![];
+    let mut invoke_stack: Vec<Pubkey> = vec![];
+
+    for log in logs {
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if invoke_stack.last() != Some(program_id) {
+                continue;
+            }
+            if let Some(event) = base64::decode(data).ok().and_then(|data| RouteEvent::decode(&data)) {
+                events.push(event);
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            // "Program <id> invoke [n]" / "Program <id> success" / "Program <id> failed: .."
+            let mut words = rest.split_whitespace();
+            let program = words.next().and_then(|id| Pubkey::from_str(id).ok());
+            match (program, words.next()) {
+                (Some(program), Some("invoke")) => invoke_stack.push(program),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    invoke_stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+/// Outcome of one route, built from its events
+#[derive(Debug, Clone, Default)]
+pub struct RouteReport {
+    pub mint: Option<Pubkey>,
+    pub swap_input: u64,
+    pub flash_borrowed: u64,
+    pub hops: Vec<HopExecuted>,
+    pub profit: Option<u64>, // None = the route didn't complete
+}
+
+impl RouteReport {
+    /// Reports of every route in the events (a tx can hold more than one)
+    pub fn from_events(events: &[RouteEvent]) -> Vec<Self> {
+        let mut reports: Vec<RouteReport> = vec![];
+        for event in events {
+            match event {
+                RouteEvent::Started(started) => reports.push(RouteReport {
+                    mint: Some(started.mint),
+                    swap_input: started.swap_input,
+                    flash_borrowed: started.flash_borrowed,
+                    ..RouteReport::default()
+                }),
+                RouteEvent::Hop(hop) => {
+                    if let Some(report) = reports.last_mut() {
+                        report.hops.push(hop.clone());
+                    }
+                }
+                RouteEvent::Completed(completed) => {
+                    if let Some(report) = reports.last_mut() {
+                        report.profit = Some(completed.profit);
+                    }
+                }
+            }
+        }
+        reports
+    }
+
+    /// Output of each hop, `hop_pools` = pools of each hop (main + splits)
+    ///
+    /// Every pool swap is its own event and a split whose share rounds to 0
+    /// isn't run, so a hop is its main swap + the splits which add up to its
+    /// input (the output of the hop before). Hops cut short by a revert are
+    /// left out
+    pub fn hop_outputs(&self, hop_pools: &[usize]) -> Vec<u64> {
+        let mut outputs = vec![];
+        let mut swaps = self.hops.iter();
+        let mut hop_input = self.swap_input;
+        for n_pools in hop_pools {
+            let (mut amount_in, mut amount_out) = (0u64, 0u64);
+            for i in 0..*n_pools {
+                if i > 0 && amount_in >= hop_input {
+                    break;
+                }
+                match swaps.next() {
+                    Some(swap) => {
+                        amount_in = amount_in.saturating_add(swap.amount_in);
+                        amount_out = amount_out.saturating_add(swap.amount_out);
+                    }
+                    None => return outputs,
+                }
+            }
+            outputs.push(amount_out);
+            hop_input = amount_out;
+        }
+        outputs
+    }
+}
+
+impl fmt::Display for RouteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mint {
+            Some(mint) => writeln!(f, "route of {} {}", self.swap_input, mint)?,
+            None => writeln!(f, "route of {}", self.swap_input)?,
+        }
+        if self.flash_borrowed > 0 {
+            writeln!(f, "  flash borrowed: {}", self.flash_borrowed)?;
+        }
+        for (i, hop) in self.hops.iter().enumerate() {
+            writeln!(f, "  hop {} {:?}: {} -> {}", i, hop.dex, hop.amount_in, hop.amount_out)?;
+        }
+        match self.profit {
+            Some(profit) => write!(f, "  profit: {}", profit),
+            None => write!(f, "  not completed"),
+        }
+    }
+}
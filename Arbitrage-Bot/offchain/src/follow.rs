@@ -0,0 +1,95 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signature;
+
+use std::collections::{HashMap, HashSet};
+
+use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
+
+use crate::constants::*;
+use crate::schedule::RouteCandidate;
+use crate::source::AccountUpdate;
+
+/// A tx through the programs of our pools, as streamed -- already
+/// processed, so an arb on the state it leaves can only land after it
+#[derive(Debug, Clone)]
+pub struct TxTrigger {
+    pub signature: Signature,
+    pub slot: u64,
+    pub token_balances: Vec<(Pubkey, u64)>, // token account -> amount after the tx
+}
+
+impl TxTrigger {
+    /// None if the update has no meta (= no balances to project from)
+    pub fn from_update(update: SubscribeUpdateTransaction) -> Option<Self> {
+        let info = update.transaction?;
+        let meta = info.meta?;
+        let message = info.transaction?.message?;
+
+        // balances index the static keys, then the ones loaded from lookup tables
+        let keys: Vec<Vec<u8>> = message
+            .account_keys
+            .into_iter()
+            .chain(meta.loaded_writable_addresses)
+            .chain(meta.loaded_readonly_addresses)
+            .collect();
+        let token_balances = meta
+            .post_token_balances
+            .iter()
+            .filter_map(|balance| {
+                let key = keys.get(balance.account_index as usize)?;
+                let amount = balance.ui_token_amount.as_ref()?.amount.parse().ok()?;
+                Some((Pubkey::new(key), amount))
+            })
+            .collect();
+
+        Some(TxTrigger {
+            signature: Signature::new(&info.signature),
+            slot: update.slot,
+            token_balances,
+        })
+    }
+
+    pub fn accounts(&self) -> HashSet<Pubkey> {
+        self.token_balances.iter().map(|(pubkey, _)| *pubkey).collect()
+    }
+}
+
+/// Programs whose txs move the reserves of our pools
+pub fn followed_programs() -> Vec<Pubkey> {
+    vec![
+        *ORCA_PROGRAM_ID,
+        *SABER_PROGRAM_ID,
+        *MERCURIAL_PROGRAM_ID,
+        *ALDRIN_V1_PROGRAM_ID,
+        *ALDRIN_V2_PROGRAM_ID,
+        *SERUM_PROGRAM_ID,
+    ]
+}
+
+/// The state the vaults in `trigger` are left in: their latest account with
+/// the amount after the tx
+///
+/// Only pools quoted from their vaults are projected -- serum markets trade
+/// through their book, which token balances don't show
+pub fn project_token_balances(latest: &HashMap<Pubkey, Account>, trigger: &TxTrigger) -> Vec<AccountUpdate> {
+    trigger
+        .token_balances
+        .iter()
+        .filter_map(|(pubkey, amount)| {
+            let mut account = latest.get(pubkey)?.clone();
+            // spl token account: mint (32) | owner (32) | amount (8) | ..
+            account.data.get_mut(64..72)?.copy_from_slice(&amount.to_le_bytes());
+            Some(AccountUpdate { pubkey: *pubkey, account: Some(account), slot: trigger.slot })
+        })
+        .collect()
+}
+
+/// Whether a route goes through a pool with one of `accounts`
+pub fn route_touches(route: &RouteCandidate, accounts: &HashSet<Pubkey>) -> bool {
+    route
+        .pools
+        .iter()
+        .any(|pool| pool.0.get_update_accounts().iter().any(|pubkey| accounts.contains(pubkey)))
+}
@@ -0,0 +1,203 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use log::warn;
+use serde::Serialize;
+
+use crate::pool::PoolOperations;
+
+/// A pool by its update accounts -- no two pools share them all
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolId(pub Vec<Pubkey>);
+
+impl PoolId {
+    pub fn of(pool: &dyn PoolOperations) -> Self {
+        PoolId(pool.get_update_accounts())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BreakerConfig {
+    pub max_failures: usize,        // consecutive failed routes through a pool before it's disabled
+    pub max_hop_deviation_bps: u64, // executed hop output under its quote before it's disabled
+    pub backoff: Duration,          // first disable, doubles with each one after
+    pub max_backoff: Duration,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PoolState {
+    label: String,
+    failures: usize, // consecutive
+    trips: u32,      // disables since the last success
+    disabled_until: Option<Instant>,
+    // lifetime counters
+    total_failures: u64,
+    short_hops: u64,
+    disables: u64,
+}
+
+/// Breaker counters of one pool, for metrics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    pub label: String,
+    pub failures: usize,            // consecutive
+    pub total_failures: u64,        // failed routes through it
+    pub short_hops: u64,            // hops paying less than their quote
+    pub disables: u64,
+    pub disabled_secs: Option<u64>, // left, None = enabled
+}
+
+/// Per-pool circuit breaker -- pools in too many consecutive failed routes
+/// (or whose executed output is under their quote) are disabled with
+/// exponential backoff
+///
+/// Once re-enabled a pool is on probation: one more failure disables it
+/// again for twice as long, a success clears it
+#[derive(Debug, Clone)]
+pub struct PoolHealth {
+    pub config: BreakerConfig,
+    pools: HashMap<PoolId, PoolState>,
+}
+
+impl PoolHealth {
+    pub fn new(config: BreakerConfig) -> Self {
+        PoolHealth { config, pools: HashMap::new() }
+    }
+
+    pub fn is_enabled(&self, pool: &PoolId, now: Instant) -> bool {
+        match self.pools.get(pool).and_then(|state| state.disabled_until) {
+            Some(until) => now >= until,
+            None => true,
+        }
+    }
+
+    /// The pools of a route which went through
+    pub fn record_success(&mut self, pools: &[(PoolId, String)]) {
+        for (pool, _) in pools {
+            if let Some(state) = self.pools.get_mut(pool) {
+                state.failures = 0;
+                state.trips = 0;
+                state.disabled_until = None;
+            }
+        }
+    }
+
+    /// The pools of a route which reverted or failed to simulate
+    pub fn record_failure(&mut self, pools: &[(PoolId, String)], now: Instant) {
+        for (pool, label) in pools {
+            let max_failures = self.config.max_failures;
+            let state = self.state(pool, label);
+            state.failures += 1;
+            state.total_failures += 1;
+            let on_probation = state.trips > 0;
+            if state.failures >= max_failures || on_probation {
+                self.disable(pool, now, "failed routes");
+            }
+        }
+    }
+
+    /// A hop whose executed output is `amount_out` for a quote of `quote`,
+    /// true = its pools were disabled
+    ///
+    /// Only shortfalls count -- a pool paying more than quoted is kept
+    pub fn record_hop(&mut self, pools: &[(PoolId, String)], quote: u128, amount_out: u128, now: Instant) -> bool {
+        if quote == 0 {
+            return false;
+        }
+        let shortfall_bps = quote.saturating_sub(amount_out) * 10_000 / quote;
+        if shortfall_bps <= self.config.max_hop_deviation_bps as u128 {
+            return false;
+        }
+        for (pool, label) in pools {
+            self.state(pool, label).short_hops += 1;
+            self.disable(pool, now, &format!("hop under its quote by {} bps", shortfall_bps));
+        }
+        true
+    }
+
+    fn state(&mut self, pool: &PoolId, label: &str) -> &mut PoolState {
+        self.pools.entry(pool.clone()).or_insert_with(|| PoolState {
+            label: label.to_string(),
+            ..PoolState::default()
+        })
+    }
+
+    fn disable(&mut self, pool: &PoolId, now: Instant, reason: &str) {
+        let (backoff, max_backoff) = (self.config.backoff, self.config.max_backoff);
+        let state = self.pools.get_mut(pool).unwrap();
+        if state.disabled_until.map_or(false, |until| now < until) {
+            return; // already out
+        }
+        let backoff = backoff.saturating_mul(2_u32.saturating_pow(state.trips)).min(max_backoff);
+        state.trips += 1;
+        state.disables += 1;
+        state.failures = 0;
+        state.disabled_until = Some(now + backoff);
+        warn!("disabling pool {} for {:?}: {}", state.label, backoff, reason);
+    }
+
+    /// (label, time left) of the disabled pools
+    pub fn disabled(&self, now: Instant) -> Vec<(String, Duration)> {
+        let mut disabled: Vec<(String, Duration)> = self
+            .pools
+            .values()
+            .filter_map(|state| {
+                let until = state.disabled_until?;
+                (now < until).then(|| (state.label.clone(), until - now))
+            })
+            .collect();
+        disabled.sort_by(|a, b| b.1.cmp(&a.1));
+        disabled
+    }
+
+    /// Counters of every tracked pool, by label
+    pub fn stats(&self, now: Instant) -> Vec<PoolStats> {
+        let mut stats: Vec<PoolStats> = self
+            .pools
+            .values()
+            .map(|state| PoolStats {
+                label: state.label.clone(),
+                failures: state.failures,
+                total_failures: state.total_failures,
+                short_hops: state.short_hops,
+                disables: state.disables,
+                disabled_secs: state.disabled_until.filter(|until| now < *until).map(|until| (until - now).as_secs()),
+            })
+            .collect();
+        stats.sort_by(|a, b| a.label.cmp(&b.label));
+        stats
+    }
+
+    pub fn summary(&self, now: Instant) -> HealthSummary {
+        HealthSummary {
+            tracked: self.pools.len(),
+            failing: self.pools.values().filter(|state| state.failures > 0).count(),
+            disabled: self.disabled(now),
+        }
+    }
+}
+
+/// Breaker state at one point in time, for the logs
+#[derive(Debug, Clone)]
+pub struct HealthSummary {
+    pub tracked: usize, // pools of any sent route
+    pub failing: usize, // with consecutive failures, not disabled yet
+    pub disabled: Vec<(String, Duration)>,
+}
+
+impl fmt::Display for HealthSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pool health: {} tracked, {} failing, {} disabled",
+            self.tracked, self.failing, self.disabled.len()
+        )?;
+        for (label, left) in self.disabled.iter() {
+            write!(f, "\n  {} ({}s left)", label, left.as_secs())?;
+        }
+        Ok(())
+    }
+}
@@ -7,6 +7,17 @@ pub mod pool;
 pub mod constants; 
 pub mod tests;
 pub mod pools; 
+pub mod events;
+pub mod router;
+pub mod split;
+pub mod snapshot;
+pub mod backtest;
+pub mod pipeline;
+pub mod source;
+pub mod registry;
+pub mod health;
+pub mod schedule;
+pub mod follow;
 
 #[macro_use]
 extern crate lazy_static;
@@ -6,10 +6,13 @@ use anchor_client::solana_sdk::signature::{Keypair, Signer};
 
 use anchor_client::{Client, Cluster};
 
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use std::borrow::Borrow;
 use std::vec;
@@ -17,14 +20,20 @@ use std::vec;
 use clap::Parser;
 
 use log::{debug, info, warn};
-use solana_sdk::account::Account;
 
 use client::arb::*;
+use client::follow::{followed_programs, route_touches};
 use client::constants::*;
+use client::error::QuoteError;
+use client::health::{BreakerConfig, PoolHealth, PoolId};
+use client::pipeline::{BlockhashCache, Jito, TxSender};
 use client::pool::{pool_factory, PoolDir, PoolOperations, PoolType};
+use client::registry::TokenRegistry;
 use client::serialize::token::unpack_token_account;
+use client::snapshot::Snapshot;
+use client::source::{AccountSource, GrpcSource, PoolUpdater, RpcPolling, SnapshotSource};
 use client::utils::{
-    derive_token_address, read_json_dir, PoolEdge, PoolGraph, PoolIndex, PoolQuote,
+    derive_mint_stats_address, derive_token_address, read_json_dir, PoolEdge, PoolGraph, PoolIndex, PoolQuote,
 };
 
 #[derive(Parser, Debug)]
@@ -32,8 +41,74 @@ use client::utils::{
 pub struct Args {
     #[clap(short, long)]
     pub cluster: String,
+    /// per-hop slippage tolerance in bps (each swap reverts below quote * (1 - bps))
+    #[clap(long)]
+    pub hop_slippage_bps: Option<u64>,
+    /// sweep profit above the working balance to the treasury of the start mint stats
+    #[clap(long)]
+    pub sweep: bool,
+    /// send each arb as a single route ix (hops in remaining accounts)
+    #[clap(long)]
+    pub compact_route: bool,
+    /// split each hop across the parallel pools of its pair (in this many chunks) when it beats one pool
+    #[clap(long)]
+    pub split_steps: Option<usize>,
+    /// before searching, sell leftover tokens worth at least this much (scaled, in the start mint) back to it
+    #[clap(long)]
+    pub sweep_dust: Option<u128>,
+    /// per-hop slippage tolerance in bps of the dust sweeps
+    #[clap(long, default_value_t = 100)]
+    pub sweep_slippage_bps: u64,
+    /// load the pool accounts from a snapshot (see the snapshot bin) and print
+    /// the arbs + their ixs instead of sending them
+    #[clap(long)]
+    pub replay: Option<String>,
+    /// search rounds, each on the account updates since the last one (0 = forever)
+    #[clap(long, default_value_t = 1)]
+    pub rounds: usize,
+    /// stream the pool accounts from this Yellowstone gRPC endpoint instead of polling RPC
+    #[clap(long)]
+    pub grpc: Option<String>,
+    #[clap(long)]
+    pub grpc_token: Option<String>,
+    /// skip cycles whose pool states are more than this many slots apart
+    #[clap(long)]
+    pub max_slot_spread: Option<u64>,
+    /// disable a pool after this many consecutive failed routes through it
+    #[clap(long, default_value_t = 3)]
+    pub breaker_failures: usize,
+    /// disable a pool when its executed hop output (simulated on localnet, from the
+    /// logs of the landed tx on mainnet) is this far off its quote (bps)
+    #[clap(long, default_value_t = 100)]
+    pub breaker_deviation_bps: u64,
+    /// first disable of a pool in secs, doubled with each one after
+    #[clap(long, default_value_t = 30)]
+    pub breaker_backoff_secs: u64,
+    /// write the breaker counters of every pool (JSON) to this file each round
+    #[clap(long)]
+    pub health_file: Option<String>,
+    /// another operator wallet (keypair file) to send routes from -- routes without
+    /// shared pool accounts go out in parallel, one per wallet
+    #[clap(long = "operator", multiple_occurrences(true))]
+    pub operators: Vec<String>,
+    /// stream the txs through our pools' programs (with --grpc) and search right
+    /// after each one on the reserves it leaves, sending the arbs as Jito bundles --
+    /// not a backrun: the stream is at `Processed` commitment, so the tx already
+    /// executed and our arb lands after it, in the same slot at best
+    #[clap(long)]
+    pub follow_txs: bool,
+    #[clap(long, default_value = "https://mainnet.block-engine.jito.wtf/api/v1/bundles")]
+    pub jito_url: String,
+    /// tip of each bundle in lamports
+    #[clap(long, default_value_t = 10_000)]
+    pub jito_tip: u64,
 }
 
+// sends use a blockhash at most this old
+const BLOCKHASH_REFRESH: Duration = Duration::from_millis(500);
+// longest a pool stays disabled
+const MAX_BREAKER_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
 fn add_pool_to_graph<'a>(
     graph: &mut PoolGraph,
     idx0: PoolIndex,
@@ -57,6 +132,19 @@ fn main() {
         _ => panic!("invalid cluster type"),
     };
 
+    // splits only exist in the route ix
+    if args.split_steps.is_some() && !args.compact_route {
+        panic!("--split-steps requires --compact-route");
+    }
+    // replays only have the accounts of the snapshot
+    if args.replay.is_some() && (args.sweep || args.sweep_dust.is_some()) {
+        panic!("--replay can't be used with --sweep or --sweep-dust");
+    }
+    // the txs come with the account stream
+    if args.follow_txs && args.grpc.is_none() {
+        panic!("--follow-txs requires --grpc");
+    }
+
     env_logger::init();
 
     let owner_kp_path = match cluster {
@@ -111,12 +199,13 @@ fn main() {
     };
     pool_dirs.push(saber_dir);
 
+    let mut tokens = TokenRegistry::from_pool_dirs(&pool_dirs);
+
     // ** json pool -> pool object
     let mut token_mints = vec![];
     let mut pools = vec![];
 
     let mut update_pks = vec![];
-    let mut update_pks_lengths = vec![];
     let mut all_mint_idxs = vec![];
 
     let mut mint2idx = HashMap::new();
@@ -157,7 +246,6 @@ fn main() {
 
             // get accounts which need account info to be updated (e.g. pool src/dst amounts for xy=k)
             let update_accounts = pool.get_update_accounts();
-            update_pks_lengths.push(update_accounts.len());
             update_pks.push(update_accounts);
 
             let mint0_idx = mint_idxs[0];
@@ -177,98 +265,286 @@ fn main() {
             pools.push(pool);
         }
     }
-    let mut update_pks = update_pks.concat();
+    let pool_update_pks = update_pks;
+    let mut update_pks = pool_update_pks.concat();
 
     info!("added {:?} mints", token_mints.len());
     info!("added {:?} pools", pools.len());
 
+    // replays are offline -- the decimals of the JSONs have to do
+    if args.replay.is_none() {
+        tokens.update_decimals(&connection, &token_mints);
+    }
+
     // !
     let usdc_mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
     let start_mint = usdc_mint;
     let start_mint_idx = *mint2idx.get(&start_mint).unwrap();
 
     let owner: &Keypair = rc_owner.borrow();
-    let owner_start_addr = derive_token_address(&owner.pubkey(), &start_mint);
-
-    // slide it in there
-    update_pks.push(owner_start_addr);
 
-    info!("getting pool amounts...");
-    let mut update_accounts = vec![];
-    for token_addr_chunk in update_pks.chunks(99) {
-        let accounts = connection.get_multiple_accounts(token_addr_chunk).unwrap();
-        update_accounts.push(accounts);
+    // each operator needs its own swap state, mint stats + ATAs (see the setup bins)
+    let mut operators = vec![];
+    let mut operator_keypairs = vec![rc_owner.clone()];
+    for path in args.operators.iter() {
+        operator_keypairs.push(Rc::new(read_keypair_file(path).unwrap()));
     }
-    let mut update_accounts = update_accounts
-        .concat()
-        .into_iter()
-        .filter(|s| s.is_some())
-        .collect::<Vec<Option<Account>>>();
-
-    info!("update accounts is {:?}", update_accounts.len());
-    // slide it out here
-    println!("accounts: {:#?}", update_accounts.clone());
-    let init_token_acc = update_accounts.pop().unwrap().unwrap();
-    let init_token_balance = unpack_token_account(&init_token_acc.data).amount as u128;
-    info!(
-        "init token acc: {:?}, balance: {:#}",
-        init_token_acc, init_token_balance
-    );
-    info!("starting balance = {}", init_token_balance);
-
-    info!("setting up exchange graph...");
-    let mut graph = PoolGraph::new();
-    let mut pool_count = 0;
-    let mut account_ptr = 0;
-
-    for pool in pools.into_iter() {
-        // update pool
-        let length = update_pks_lengths[pool_count];
-        let _account_slice = &update_accounts[account_ptr..account_ptr + length].to_vec();
-        account_ptr += length;
-
-        // pool.set_update_accounts(*account_slice);
-
-        // add pool to graph
-        let idxs = &all_mint_idxs[pool_count * 2..(pool_count + 1) * 2].to_vec();
-        let idx0 = PoolIndex(idxs[0]);
-        let idx1 = PoolIndex(idxs[1]);
-
-        let mut pool_ptr = PoolQuote::new(Rc::new(pool));
-        add_pool_to_graph(&mut graph, idx0, idx1, &mut pool_ptr.clone());
-        add_pool_to_graph(&mut graph, idx1, idx0, &mut pool_ptr);
-
-        pool_count += 1;
+    for keypair in operator_keypairs {
+        // treasury is configured on-chain with setup_mint_stats
+        let sweep_treasury = if args.sweep {
+            let mint_stats_addr =
+                derive_mint_stats_address(&program.id(), &keypair.pubkey(), &start_mint);
+            let mint_stats: tmp::state::MintStats = program.account(mint_stats_addr).unwrap();
+            info!(
+                "sweeping above {} to treasury {} ({})",
+                tokens.fmt_amount(&start_mint, mint_stats.working_balance as u128),
+                mint_stats.treasury,
+                keypair.pubkey(),
+            );
+            Some(mint_stats.treasury)
+        } else {
+            None
+        };
+        operators.push(Operator { keypair, sweep_treasury });
     }
+    let operator_start_addrs: Vec<Pubkey> = operators
+        .iter()
+        .map(|operator| derive_token_address(&operator.keypair.pubkey(), &start_mint))
+        .collect();
+    let owner_start_addr = operator_start_addrs[0];
+
+    // slide them in there
+    update_pks.extend(operator_start_addrs.iter().copied());
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let connection = Arc::new(connection);
+
+    // replays don't send
+    let sender = if args.replay.is_none() {
+        let send_connection = Arc::new(RpcClient::new_with_commitment(
+            cluster.url(),
+            CommitmentConfig::confirmed(),
+        ));
+        let blockhash = BlockhashCache::spawn(runtime.handle(), send_connection.clone(), BLOCKHASH_REFRESH);
+        let jito = args.follow_txs.then(|| Jito { url: args.jito_url.clone(), tip_lamports: args.jito_tip });
+        Some(TxSender::spawn(runtime.handle(), send_connection, blockhash, jito))
+    } else {
+        None
+    };
 
-    let arbitrager = Arbitrager {
+    let mut source: Box<dyn AccountSource> = if let Some(snapshot_path) = &args.replay {
+        let snapshot = Snapshot::load(snapshot_path);
+        if snapshot.owner != owner.pubkey().to_string() || snapshot.start_mint != start_mint.to_string() {
+            panic!("snapshot of owner {} / start mint {}", snapshot.owner, snapshot.start_mint);
+        }
+        info!("replaying slot {}", snapshot.slot);
+        Box::new(SnapshotSource { snapshot: Some(snapshot), pks: update_pks.clone() })
+    } else if let Some(endpoint) = &args.grpc {
+        Box::new(GrpcSource::spawn(
+            runtime.handle(),
+            endpoint.clone(),
+            args.grpc_token.clone(),
+            connection.clone(),
+            update_pks.clone(),
+            if args.follow_txs { followed_programs() } else { vec![] },
+        ))
+    } else {
+        Box::new(RpcPolling::new(runtime.handle(), connection.clone(), update_pks.clone()))
+    };
+
+    let mut arbitrager = Arbitrager {
         token_mints,
         graph_edges,
-        graph,
-        cluster,
-        owner: rc_owner,
+        graph: PoolGraph::new(),
+        cluster: cluster.clone(),
+        hop_slippage_bps: args.hop_slippage_bps,
+        compact_route: args.compact_route,
+        split_steps: args.split_steps,
+        dry_run: args.replay.is_some(),
+        max_slot_spread: args.max_slot_spread,
+        tokens,
+        health: RefCell::new(PoolHealth::new(BreakerConfig {
+            max_failures: args.breaker_failures,
+            max_hop_deviation_bps: args.breaker_deviation_bps,
+            backoff: Duration::from_secs(args.breaker_backoff_secs),
+            max_backoff: MAX_BREAKER_BACKOFF,
+        })),
+        pending_txs: RefCell::new(HashMap::new()),
+        owner: rc_owner.clone(),
+        operators,
         program,
         connection: send_tx_connection,
+        sender,
     };
 
-    info!("searching for arbitrages...");
-    let min_swap_amount = 10_u128.pow(6_u32); // scaled! -- 1 USDC
-    let mut swap_start_amount = init_token_balance; // scaled!
-    let mut sent_arbs = HashSet::new(); // track what arbs we did with a larger size
-
-    for _ in 0..4 {
-        arbitrager.brute_force_search(
-            start_mint_idx,
-            swap_start_amount,
-            swap_start_amount,
-            vec![start_mint_idx],
-            vec![],
-            &mut sent_arbs,
-        );
-
-        swap_start_amount /= 2; // half input amount and search again
-        if swap_start_amount < min_swap_amount {
+    // pools are shared with the graph during a search only
+    let mut pools: Vec<Rc<Box<dyn PoolOperations>>> = pools.into_iter().map(Rc::new).collect();
+    let mut updater = PoolUpdater::new(pool_update_pks);
+    let mut live_pools = vec![false; pools.len()]; // set with all their accounts
+    let pool_ids: Vec<PoolId> = pools.iter().map(|pool| PoolId::of(&***pool)).collect();
+
+    let mut round = 0;
+    loop {
+        round += 1;
+        // before any skipped round (--follow-txs without a tx)
+        if args.rounds != 0 && round > args.rounds {
             break;
-        } // dont get too small
+        }
+        info!("getting pool amounts...");
+        let updates = match source.next_updates() {
+            Some(updates) => updates,
+            None => break,
+        };
+        let context_slot = updates.iter().map(|update| update.slot).max().unwrap_or_default();
+        let updated_pools = updater.apply(updates);
+        debug!("slot {}: {} pools updated", context_slot, updated_pools.len());
+
+        let init_token_acc = updater.account(&owner_start_addr).expect("no start mint ATA");
+        let init_token_balance = unpack_token_account(&init_token_acc.data).amount as u128;
+        info!("starting balance = {}", arbitrager.tokens.fmt_amount(&start_mint, init_token_balance));
+        // the owner's + every other operator's
+        let mut operator_balances = vec![init_token_balance];
+        for addr in operator_start_addrs[1..].iter() {
+            let balance = match updater.account(addr) {
+                Some(account) => unpack_token_account(&account.data).amount as u128,
+                None => {
+                    warn!("no start mint ATA {}, its operator is skipped", addr);
+                    0
+                }
+            };
+            operator_balances.push(balance);
+        }
+
+        // the txs which landed/reverted since the last round
+        arbitrager.update_health();
+        let now = Instant::now();
+        let health = arbitrager.health.borrow().summary(now);
+        if health.disabled.is_empty() {
+            debug!("{}", health);
+        } else {
+            info!("{}", health);
+        }
+        if let Some(path) = args.health_file.as_ref() {
+            let stats = arbitrager.health.borrow().stats(now);
+            if let Err(err) = std::fs::write(path, serde_json::to_string_pretty(&stats).unwrap()) {
+                warn!("failed to write {}: {}", path, err);
+            }
+        }
+
+        info!("setting up exchange graph...");
+        arbitrager.graph = PoolGraph::new(); // drop the last round's pointers
+        for pool_idx in updated_pools {
+            let update = match updater.pool_accounts(pool_idx) {
+                Some(accounts) => match Rc::get_mut(&mut pools[pool_idx]) {
+                    Some(pool) => pool.set_update_accounts(accounts, cluster.clone()),
+                    // still pointed to from outside the graph -- skip it this round
+                    None => Err(QuoteError::StaleState("pool is still shared".to_string())),
+                },
+                None => Err(QuoteError::StaleState("missing account".to_string())),
+            };
+            // an account is missing or invalid -- only this pool is out
+            if let Err(err) = update.as_ref() {
+                if live_pools[pool_idx] {
+                    warn!("disabling {} pool {}: {}", pools[pool_idx].get_name(), pool_idx, err);
+                }
+            }
+            live_pools[pool_idx] = update.is_ok();
+        }
+
+        arbitrager.graph_edges = vec![HashSet::new(); arbitrager.token_mints.len()];
+        for (pool_count, pool) in pools.iter().enumerate() {
+            if !live_pools[pool_count] {
+                continue; // no state to quote with
+            }
+            if !arbitrager.health.borrow().is_enabled(&pool_ids[pool_count], now) {
+                continue; // circuit breaker
+            }
+            // add pool to graph
+            let idxs = &all_mint_idxs[pool_count * 2..(pool_count + 1) * 2].to_vec();
+            arbitrager.graph_edges[idxs[0]].insert(idxs[1]);
+            arbitrager.graph_edges[idxs[1]].insert(idxs[0]);
+            let idx0 = PoolIndex(idxs[0]);
+            let idx1 = PoolIndex(idxs[1]);
+
+            let pool_ptr = PoolQuote::at_slot(pool.clone(), updater.pool_slots[pool_count]);
+            add_pool_to_graph(&mut arbitrager.graph, idx0, idx1, &pool_ptr);
+            add_pool_to_graph(&mut arbitrager.graph, idx1, idx0, &pool_ptr);
+        }
+
+        // USD prices from our own pools
+        let live: Vec<&dyn PoolOperations> = pools
+            .iter()
+            .zip(&live_pools)
+            .filter(|(_, live)| **live)
+            .map(|(pool, _)| &***pool)
+            .collect();
+        arbitrager.tokens.update_prices(&live, &usdc_mint);
+
+        if let (1, Some(min_amount_out)) = (round, args.sweep_dust) {
+            info!("sweeping dust...");
+            let owner_atas: Vec<Pubkey> = arbitrager
+                .token_mints
+                .iter()
+                .map(|mint| derive_token_address(&arbitrager.owner.pubkey(), mint))
+                .collect();
+            let mut balances = vec![];
+            for (chunk_idx, ata_chunk) in owner_atas.chunks(99).enumerate() {
+                let accounts = connection.get_multiple_accounts(ata_chunk).unwrap();
+                for (i, account) in accounts.into_iter().enumerate() {
+                    if let Some(account) = account {
+                        let amount = unpack_token_account(&account.data).amount as u128;
+                        balances.push((chunk_idx * 99 + i, amount));
+                    }
+                }
+            }
+
+            let sweep = DustSweep {
+                base_mints: vec![(start_mint_idx, min_amount_out)],
+                slippage_bps: args.sweep_slippage_bps,
+                max_hops: 3,
+            };
+            arbitrager.sweep_dust(&sweep, &balances);
+        }
+
+        // only search right after a tx moved our pools
+        let trigger_accounts = match source.trigger() {
+            Some(trigger) => {
+                info!("following {} (slot {})", trigger.signature, trigger.slot);
+                Some(trigger.accounts())
+            }
+            None if args.follow_txs => continue,
+            None => None,
+        };
+
+        info!("searching for arbitrages...");
+        let min_swap_amount = 10_u128.pow(6_u32); // scaled! -- 1 USDC
+        let mut swap_start_amount = *operator_balances.iter().max().unwrap(); // scaled!
+        let mut candidates = vec![]; // every size -- overlapping ones are re-quoted before they go out
+
+        for _ in 0..4 {
+            arbitrager.brute_force_search(
+                start_mint_idx,
+                swap_start_amount,
+                swap_start_amount,
+                vec![start_mint_idx],
+                vec![],
+                &mut candidates,
+            );
+
+            swap_start_amount /= 2; // half input amount and search again
+            if swap_start_amount < min_swap_amount {
+                break;
+            } // dont get too small
+        }
+        // the rest was there before the tx
+        if let Some(accounts) = trigger_accounts.as_ref() {
+            candidates.retain(|route| route_touches(route, accounts));
+        }
+        arbitrager.send_routes(candidates, &operator_balances);
+    }
+
+    // wait for the sends still in flight
+    if let Some(sender) = arbitrager.sender.take() {
+        runtime.block_on(sender.close());
     }
 }
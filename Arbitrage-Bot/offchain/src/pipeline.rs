@@ -0,0 +1,275 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signature;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use solana_transaction_status::UiTransactionEncoding;
+
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use log::warn;
+
+use crate::constants::JITO_TIP_ACCOUNTS;
+use crate::source::AccountUpdate;
+
+// The blocking RpcClient can't be called from async code, every RPC call of
+// the pipeline runs on the blocking pool of the runtime
+
+/// Fetch `pks` in chunks of the RPC limit, all chunks at once -- each
+/// update has the context slot of its chunk
+pub fn spawn_get_multiple_accounts(
+    runtime: &Handle,
+    connection: Arc<RpcClient>,
+    pks: Vec<Pubkey>,
+) -> JoinHandle<Vec<AccountUpdate>> {
+    runtime.spawn(async move {
+        // max 100 accounts per get_multiple_accounts
+        let chunks: Vec<JoinHandle<Vec<AccountUpdate>>> = pks
+            .chunks(99)
+            .map(|chunk| {
+                let connection = connection.clone();
+                let chunk = chunk.to_vec();
+                tokio::task::spawn_blocking(move || {
+                    let response = connection
+                        .get_multiple_accounts_with_commitment(&chunk, connection.commitment())
+                        .unwrap();
+                    chunk
+                        .iter()
+                        .zip(response.value)
+                        .map(|(pubkey, account)| AccountUpdate {
+                            pubkey: *pubkey,
+                            account,
+                            slot: response.context.slot,
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+
+        let mut updates = vec![];
+        for chunk in chunks {
+            updates.extend(chunk.await.unwrap());
+        }
+        updates
+    })
+}
+
+/// Latest blockhash, refreshed in the background
+#[derive(Clone)]
+pub struct BlockhashCache(Arc<RwLock<Hash>>);
+
+impl BlockhashCache {
+    pub fn spawn(runtime: &Handle, connection: Arc<RpcClient>, refresh: Duration) -> Self {
+        let cache = BlockhashCache(Arc::new(RwLock::new(connection.get_latest_blockhash().unwrap())));
+
+        let latest = cache.0.clone();
+        runtime.spawn(async move {
+            loop {
+                tokio::time::sleep(refresh).await;
+                let connection = connection.clone();
+                let blockhash = tokio::task::spawn_blocking(move || connection.get_latest_blockhash())
+                    .await
+                    .unwrap();
+                match blockhash {
+                    Ok(blockhash) => *latest.write().unwrap() = blockhash,
+                    // keep the last one, it's valid for ~150 slots
+                    Err(err) => warn!("blockhash refresh failed: {}", err),
+                }
+            }
+        });
+        cache
+    }
+
+    pub fn get(&self) -> Hash {
+        *self.0.read().unwrap()
+    }
+}
+
+lazy_static! {
+    // only used on the blocking pool -- a blocking client can't be made or
+    // dropped in async code
+    static ref HTTP: reqwest::blocking::Client = reqwest::blocking::Client::new();
+}
+
+/// Sends txs as Jito bundles to a block engine
+#[derive(Debug, Clone)]
+pub struct Jito {
+    pub url: String, // e.g. https://mainnet.block-engine.jito.wtf/api/v1/bundles
+    pub tip_lamports: u64,
+}
+
+impl Jito {
+    /// The tip, to a tip account picked at random
+    pub fn tip_ix(&self, payer: &Pubkey) -> Instruction {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos() as usize;
+        let tip_account = JITO_TIP_ACCOUNTS[nanos % JITO_TIP_ACCOUNTS.len()];
+        system_instruction::transfer(payer, &tip_account, self.tip_lamports)
+    }
+
+    /// Blocking -- returns the bundle id
+    pub fn send_bundle(&self, txs: &[Transaction]) -> anyhow::Result<String> {
+        let txs: Vec<String> = txs
+            .iter()
+            .map(|tx| base64::encode(bincode::serialize(tx).unwrap()))
+            .collect();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendBundle",
+            "params": [txs, { "encoding": "base64" }],
+        });
+        let response: serde_json::Value = HTTP.post(&self.url).json(&request).send()?.json()?;
+        match response.get("result").and_then(|id| id.as_str()) {
+            Some(bundle_id) => Ok(bundle_id.to_string()),
+            None => Err(anyhow::anyhow!("sendBundle failed: {}", response)),
+        }
+    }
+}
+
+// a sent tx which isn't confirmed by then has expired
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(90);
+const CONFIRM_POLL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxStatus {
+    Landed,
+    Failed(String), // landed + reverted
+    Dropped,        // the send failed or it never landed
+}
+
+#[derive(Debug, Clone)]
+pub struct TxOutcome {
+    pub signature: Signature,
+    pub status: TxStatus,
+    pub logs: Vec<String>, // of a landed tx, empty if they couldn't be fetched
+}
+
+/// Sends txs in the background -- `send` returns right away and one slow
+/// send doesn't hold up the next, where each tx ends up comes back through
+/// `outcomes`
+///
+/// With `jito` each tx goes out as a bundle of its own (it has to carry the
+/// tip ix) instead of over RPC
+pub struct TxSender {
+    pub blockhash: BlockhashCache,
+    pub jito: Option<Jito>,
+    txs: mpsc::UnboundedSender<Transaction>,
+    outcomes: Mutex<mpsc::UnboundedReceiver<TxOutcome>>,
+    task: JoinHandle<()>,
+}
+
+impl TxSender {
+    pub fn spawn(runtime: &Handle, connection: Arc<RpcClient>, blockhash: BlockhashCache, jito: Option<Jito>) -> Self {
+        let (txs, mut rx) = mpsc::unbounded_channel::<Transaction>();
+        let (outcome_tx, outcomes) = mpsc::unbounded_channel();
+        let bundles = jito.clone();
+
+        let task = runtime.spawn(async move {
+            let mut sends = vec![];
+            while let Some(tx) = rx.recv().await {
+                let connection = connection.clone();
+                let signature = tx.signatures[0];
+                let send = tokio::task::spawn_blocking({
+                    let connection = connection.clone();
+                    let bundles = bundles.clone();
+                    move || match bundles {
+                        // just our tx -- a followed tx already executed, it can't be bundled
+                        Some(jito) => jito.send_bundle(&[tx]).map(|bundle_id| format!("bundle: {}", bundle_id)),
+                        None => {
+                            let config = RpcSendTransactionConfig {
+                                skip_preflight: true,
+                                ..RpcSendTransactionConfig::default()
+                            };
+                            connection
+                                .send_transaction_with_config(&tx, config)
+                                .map(|signature| format!("signature: {:?}", signature))
+                                .map_err(anyhow::Error::from)
+                        }
+                    }
+                });
+                sends.push(send);
+
+                // confirmations aren't waited for on close
+                let outcome_tx = outcome_tx.clone();
+                tokio::spawn(async move {
+                    let status = confirm(connection.clone(), signature).await;
+                    let logs = match status {
+                        TxStatus::Landed => tx_logs(connection, signature).await,
+                        _ => vec![],
+                    };
+                    let _ = outcome_tx.send(TxOutcome { signature, status, logs });
+                });
+            }
+            for send in sends {
+                match send.await.unwrap() {
+                    Ok(sent) => println!("{}", sent),
+                    Err(err) => warn!("send failed: {}", err),
+                }
+            }
+        });
+
+        TxSender { blockhash, jito, txs, outcomes: Mutex::new(outcomes), task }
+    }
+
+    pub fn send(&self, tx: Transaction) {
+        self.txs.send(tx).unwrap();
+    }
+
+    /// Outcomes of the sent txs since the last call
+    pub fn outcomes(&self) -> Vec<TxOutcome> {
+        let mut outcomes = vec![];
+        let mut rx = self.outcomes.lock().unwrap();
+        while let Ok(outcome) = rx.try_recv() {
+            outcomes.push(outcome);
+        }
+        outcomes
+    }
+
+    /// Wait for the queued sends
+    pub async fn close(self) {
+        drop(self.txs);
+        self.task.await.unwrap();
+    }
+}
+
+// poll the status of a sent tx until it lands or expires
+async fn confirm(connection: Arc<RpcClient>, signature: Signature) -> TxStatus {
+    let start = tokio::time::Instant::now();
+    while start.elapsed() < CONFIRM_TIMEOUT {
+        tokio::time::sleep(CONFIRM_POLL).await;
+        let connection = connection.clone();
+        let status = tokio::task::spawn_blocking(move || connection.get_signature_status(&signature))
+            .await
+            .unwrap();
+        match status {
+            Ok(Some(Ok(()))) => return TxStatus::Landed,
+            Ok(Some(Err(err))) => return TxStatus::Failed(err.to_string()),
+            Ok(None) => {}
+            Err(err) => warn!("status of {} failed: {}", signature, err),
+        }
+    }
+    TxStatus::Dropped
+}
+
+// logs of a landed tx -- the executed amount of each hop is in its events
+async fn tx_logs(connection: Arc<RpcClient>, signature: Signature) -> Vec<String> {
+    let tx = tokio::task::spawn_blocking(move || connection.get_transaction(&signature, UiTransactionEncoding::Json))
+        .await
+        .unwrap();
+    match tx {
+        Ok(tx) => tx.transaction.meta.and_then(|meta| meta.log_messages).unwrap_or_default(),
+        Err(err) => {
+            warn!("logs of {} failed: {}", signature, err);
+            vec![]
+        }
+    }
+}
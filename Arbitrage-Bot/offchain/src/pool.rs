@@ -1,8 +1,10 @@
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::Program;
 use solana_sdk::account::Account;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use tmp::ix_data::DexKind;
 
+use crate::error::QuoteError;
 use crate::pools::*;
 use std::fmt::Debug;
 
@@ -51,25 +53,41 @@ pub fn pool_factory(tipe: &PoolType, json_str: &String) -> Box<dyn PoolOperation
 pub trait PoolOperations: Debug {
     fn get_name(&self) -> String;
     fn get_update_accounts(&self) -> Vec<Pubkey>;
-    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, cluster: Cluster);
+    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, cluster: Cluster) -> Result<(), QuoteError>;
 
-    fn mint_2_addr(&self, mint: &Pubkey) -> Pubkey;
+    fn mint_2_addr(&self, mint: &Pubkey) -> Result<Pubkey, QuoteError>;
     fn get_mints(&self) -> Vec<Pubkey>;
-    fn mint_2_scale(&self, mint: &Pubkey) -> u64;
+    fn mint_2_scale(&self, mint: &Pubkey) -> Result<u64, QuoteError>;
 
+    // an error = no edge for the pair on the current state
     fn get_quote_with_amounts_scaled(
         &self,
         amount_in: u128,
         mint_in: &Pubkey,
         mint_out: &Pubkey,
-    ) -> u128;
+    ) -> Result<u128, QuoteError>;
+    // smallest input the quote above turns into at least `amount_out`
+    fn get_quote_exact_out(
+        &self,
+        amount_out: u128,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> Result<u128, QuoteError>;
     fn swap_ix(
         &self,
         program: &Program,
         owner: &Pubkey,
         mint_in: &Pubkey,
         mint_out: &Pubkey,
-    ) -> Vec<Instruction>;
+        min_amount_out: Option<u64>,
+    ) -> Result<Vec<Instruction>, QuoteError>;
+    // accounts of the pool as a hop of the on-chain route ix (see tmp::route)
+    fn route_hop(
+        &self,
+        owner: &Pubkey,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> Result<(DexKind, Vec<AccountMeta>), QuoteError>;
 
     fn can_trade(&self, mint_in: &Pubkey, mint_out: &Pubkey) -> bool; // used for tests
 }
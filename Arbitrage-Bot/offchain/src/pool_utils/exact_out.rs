@@ -0,0 +1,63 @@
+//! Exact-output quotes: the smallest input whose forward quote reaches a
+//! wanted output
+use crate::error::QuoteError;
+
+/// `a / b` rounded up, None if `b` = 0
+pub fn ceil_div(a: u128, b: u128) -> Option<u128> {
+    let quotient = a.checked_div(b)?;
+    Some(if a % b == 0 { quotient } else { quotient + 1 })
+}
+
+/// Smallest input with `quote(input) >= amount_out`, starting from the
+/// closed-form `estimate` of the curve
+///
+/// Curves round (and fees take a minimum), so the estimate can be a few units
+/// off -- it's bracketed by galloping away from it and then bisected against
+/// the forward quote, which has to be monotone
+pub fn settle_exact_in(
+    estimate: u128,
+    amount_out: u128,
+    quote: impl Fn(u128) -> Result<u128, QuoteError>,
+) -> Result<u128, QuoteError> {
+    if amount_out == 0 {
+        return Ok(0);
+    }
+    let enough = |amount_in| quote(amount_in).map_or(false, |out| out >= amount_out);
+
+    // lo quotes under `amount_out`, hi at least it
+    let (mut lo, mut hi) = if enough(estimate) {
+        let (mut hi, mut step) = (estimate, 1u128);
+        loop {
+            let below = hi.saturating_sub(step);
+            if !enough(below) {
+                break (below, hi);
+            }
+            if below == 0 {
+                return Ok(0);
+            }
+            hi = below;
+            step = step.saturating_mul(2);
+        }
+    } else {
+        let (mut lo, mut step) = (estimate, 1u128);
+        loop {
+            // no input buys that much
+            let above = lo.checked_add(step).ok_or(QuoteError::InsufficientLiquidity)?;
+            if quote(above)? >= amount_out {
+                break (lo, above);
+            }
+            lo = above;
+            step = step.checked_mul(2).ok_or(QuoteError::InsufficientLiquidity)?;
+        }
+    };
+
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if enough(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Ok(hi)
+}
@@ -8,6 +8,9 @@ pub mod fees;
 pub mod offset;
 pub mod stable;
 
+// inverse quotes
+pub mod exact_out;
+
 // pool specific details 
 pub mod orca;
 pub mod serum;
\ No newline at end of file
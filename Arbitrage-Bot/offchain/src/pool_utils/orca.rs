@@ -1,69 +1,209 @@
-use anyhow::Result;
 use crate::{
-    pool_utils,
+    error::QuoteError,
     pool_utils::base::{SwapCurve, CurveType},
+    pool_utils::calculator::{CurveCalculator, TradeDirection},
+    pool_utils::exact_out::{ceil_div, settle_exact_in},
     pool_utils::fees::Fees,
-    pool_utils::{constant_product::ConstantProductCurve, stable::StableCurve},
+    pool_utils::{
+        constant_price::ConstantPriceCurve, constant_product::ConstantProductCurve,
+        offset::OffsetCurve,
+        stable::{compute_a, compute_d, compute_new_destination_amount, StableCurve},
+    },
 };
-use core::panic;
 use std::sync::Arc;
 
+/// Extra params of the token-swap curves, only the ones of the curve type are used
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CurveParams {
+    pub amp: u64,            // Stable
+    pub token_b_price: u64,  // ConstantPrice
+    pub token_b_offset: u64, // Offset
+}
+
+pub fn get_swap_curve(curve_type: CurveType, params: &CurveParams) -> SwapCurve {
+    let calculator: Arc<dyn CurveCalculator + Sync + Send> = match curve_type {
+        CurveType::ConstantProduct => Arc::new(ConstantProductCurve {}),
+        CurveType::ConstantPrice => Arc::new(ConstantPriceCurve { token_b_price: params.token_b_price }),
+        CurveType::Stable => Arc::new(StableCurve { amp: params.amp }),
+        CurveType::Offset => Arc::new(OffsetCurve { token_b_offset: params.token_b_offset }),
+    };
+    SwapCurve { curve_type, calculator }
+}
+
+/// Quote of a token-swap pool -- `trade_direction` matters for the curves
+/// which aren't symmetric (ConstantPrice, Offset)
 pub fn get_pool_quote_with_amounts(
-    amount_in: u128, 
-    curve_type: CurveType, 
-    amp: u64,
+    amount_in: u128,
+    swap_curve: &SwapCurve,
+    trade_direction: TradeDirection,
     fees: &Fees,
     input_token_pool_amount: u128,
     output_token_pool_amount: u128,
     slippage_percent: Option<[u128;2]>,
-) -> Result<u128> {
-    let mut quote;
-    let trade_direction = pool_utils::calculator::TradeDirection::AtoB;
-    
-    if curve_type == CurveType::ConstantProduct { // constant product (1 for orca)
-        let swap_curve = SwapCurve {
-            curve_type: CurveType::ConstantProduct,
-            calculator: Arc::new(ConstantProductCurve {}),
-        };
-        let swap_quote = swap_curve.swap(
-            amount_in, 
-            input_token_pool_amount, 
-            output_token_pool_amount, 
-            trade_direction, 
-            fees
-        );
-        quote = match swap_quote {
-            Some(v) => { v.destination_amount_swapped },
-            None => {
-                // println!("swap err: {} {} {}", amount_in, 
-                //     input_token_pool_amount, 
-                //     output_token_pool_amount);
-                0
+) -> Result<u128, QuoteError> {
+    let swap_quote = swap_curve.swap(
+        amount_in,
+        input_token_pool_amount,
+        output_token_pool_amount,
+        trade_direction,
+        fees
+    );
+    let mut quote = match swap_quote {
+        // Offset prices B against liquidity the pool doesn't hold -- never
+        // quote past the real vault
+        Some(v) if v.destination_amount_swapped > output_token_pool_amount => {
+            return Err(QuoteError::InsufficientLiquidity);
+        }
+        Some(v) => { v.destination_amount_swapped },
+        None => {
+            let dry_swap = |amount| swap_curve.calculator.swap_without_fees(
+                amount,
+                input_token_pool_amount,
+                output_token_pool_amount,
+                trade_direction,
+            );
+            // more out than the reserve (ConstantPrice, Offset)
+            let drains_pool = output_token_pool_amount == 0 || dry_swap(amount_in)
+                .map_or(false, |v| v.destination_amount_swapped > output_token_pool_amount);
+            if drains_pool {
+                return Err(QuoteError::InsufficientLiquidity);
             }
+            // the curve works at the scale of the pool -- a smaller input only
+            // rounds to nothing (or to less than the fees)
+            let probe = input_token_pool_amount.max(1);
+            if amount_in <= probe && dry_swap(probe).is_some() {
+                return Ok(0);
+            }
+            return Err(QuoteError::MathOverflow);
         }
+    };
 
-    } else if curve_type == CurveType::Stable { // stableswap (2 for orca)
-        let swap_curve = SwapCurve {
-            curve_type: CurveType::Stable,
-            calculator: Arc::new(StableCurve { amp }),
-        };
-        quote = swap_curve.swap(
-            amount_in, 
-            input_token_pool_amount, 
-            output_token_pool_amount, 
-            trade_direction, 
-            fees
-        ).unwrap().destination_amount_swapped;
+    // add slippage amount if its given
+    if let Some([num, denom]) = slippage_percent {
+        quote = quote
+            .checked_mul(denom.saturating_sub(num))
+            .and_then(|v| v.checked_div(denom))
+            .ok_or(QuoteError::MathOverflow)?;
+    }
 
-    } else {
-        panic!("invalid curve type for swap: {:?}", curve_type);
+    Ok(quote)
+}
+
+// fees are a fraction of the input, each step of `add_fees` gets closer
+const FEE_ITERATIONS: usize = 32;
+
+/// Input of a token-swap pool which buys at least `amount_out` -- the
+/// inverse of `get_pool_quote_with_amounts` (without slippage)
+pub fn get_pool_quote_exact_out(
+    amount_out: u128,
+    curve_type: CurveType,
+    params: &CurveParams,
+    trade_direction: TradeDirection,
+    fees: &Fees,
+    input_token_pool_amount: u128,
+    output_token_pool_amount: u128,
+) -> Result<u128, QuoteError> {
+    if amount_out == 0 {
+        return Ok(0);
     }
-            
-    // add slippage amount if its given 
-    if let Some([num, denom]) = slippage_percent {
-        quote = quote * (denom - num) / denom
+    if amount_out > output_token_pool_amount {
+        return Err(QuoteError::InsufficientLiquidity);
+    }
+    let amount_swapped = curve_exact_in(
+        amount_out,
+        curve_type,
+        params,
+        trade_direction,
+        input_token_pool_amount,
+        output_token_pool_amount,
+    )?;
+    let estimate = add_fees(fees, amount_swapped).ok_or(QuoteError::MathOverflow)?;
+
+    let swap_curve = get_swap_curve(curve_type, params);
+    settle_exact_in(estimate, amount_out, |amount_in| get_pool_quote_with_amounts(
+        amount_in,
+        &swap_curve,
+        trade_direction,
+        fees,
+        input_token_pool_amount,
+        output_token_pool_amount,
+        None,
+    ))
+}
+
+// fee-less input which buys `amount_out`, from the invariant of the curve
+fn curve_exact_in(
+    amount_out: u128,
+    curve_type: CurveType,
+    params: &CurveParams,
+    trade_direction: TradeDirection,
+    input_token_pool_amount: u128,
+    output_token_pool_amount: u128,
+) -> Result<u128, QuoteError> {
+    match curve_type {
+        CurveType::ConstantProduct => {
+            constant_product_exact_in(amount_out, input_token_pool_amount, output_token_pool_amount)
+        }
+        CurveType::Offset => {
+            // the offset is on token B, priced like a reserve
+            let offset = params.token_b_offset as u128;
+            let (src, dst) = match trade_direction {
+                TradeDirection::AtoB => (Some(input_token_pool_amount), output_token_pool_amount.checked_add(offset)),
+                TradeDirection::BtoA => (input_token_pool_amount.checked_add(offset), Some(output_token_pool_amount)),
+            };
+            match (src, dst) {
+                (Some(src), Some(dst)) => constant_product_exact_in(amount_out, src, dst),
+                _ => Err(QuoteError::MathOverflow),
+            }
+        }
+        CurveType::ConstantPrice => {
+            // one B costs `token_b_price` A
+            let price = params.token_b_price as u128;
+            match trade_direction {
+                TradeDirection::AtoB => amount_out.checked_mul(price),
+                TradeDirection::BtoA => ceil_div(amount_out, price),
+            }
+            .ok_or(QuoteError::MathOverflow)
+        }
+        CurveType::Stable => {
+            let remaining = output_token_pool_amount - amount_out;
+            if remaining == 0 {
+                return Err(QuoteError::InsufficientLiquidity);
+            }
+            // the invariant is symmetric: solve it for the new input reserve
+            let leverage = compute_a(params.amp).ok_or(QuoteError::MathOverflow)?;
+            let new_input = compute_d(leverage, input_token_pool_amount, output_token_pool_amount)
+                .and_then(|d| compute_new_destination_amount(leverage, remaining, d))
+                .ok_or(QuoteError::MathOverflow)?;
+            Ok(new_input.saturating_sub(input_token_pool_amount))
+        }
     }
+}
 
+// x * y = k -- the input which leaves y - out in the pool
+fn constant_product_exact_in(amount_out: u128, src: u128, dst: u128) -> Result<u128, QuoteError> {
+    let remaining = match dst.checked_sub(amount_out) {
+        Some(remaining) if remaining > 0 => remaining,
+        _ => return Err(QuoteError::InsufficientLiquidity),
+    };
+    let new_src = src
+        .checked_mul(dst)
+        .and_then(|invariant| ceil_div(invariant, remaining))
+        .ok_or(QuoteError::MathOverflow)?;
+    Ok(new_src.saturating_sub(src))
+}
 
-    Ok(quote)
+// smallest source amount left with `amount` once the trade + owner fees are
+// taken -- each step adds what's still missing, which never overshoots
+fn add_fees(fees: &Fees, amount: u128) -> Option<u128> {
+    let mut source = amount;
+    for _ in 0..FEE_ITERATIONS {
+        let total_fees = fees.trading_fee(source)?.checked_add(fees.owner_trading_fee(source)?)?;
+        let less_fees = source.saturating_sub(total_fees);
+        if less_fees >= amount {
+            break;
+        }
+        source = source.checked_add(amount - less_fees)?;
+    }
+    Some(source)
 }
@@ -141,4 +141,175 @@ impl FeeTier {
 #[inline]
 pub fn referrer_rebate(amount: u64) -> u64 {
     amount / 5
-}
\ No newline at end of file
+}
+
+// ORDERBOOK WALK
+
+/// A resting order of a book side, price and quantity in lots
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BookOrder {
+    pub price: u64,
+    pub quantity: u64,
+}
+
+/// Base bought with `amount_in` quote, filling `asks` best (lowest) first
+///
+/// Each fill pays the taker fee on what it fills, like the matching engine
+pub fn bid_quote(
+    asks: impl IntoIterator<Item = BookOrder>,
+    coin_lot_size: u64,
+    pc_lot_size: u64,
+    fee_tier: FeeTier,
+    amount_in: u64,
+) -> u64 {
+    if coin_lot_size == 0 || pc_lot_size == 0 {
+        return 0;
+    }
+    let mut asks = asks.into_iter();
+    let mut amount_in = amount_in;
+    let mut amount_out: u64 = 0;
+    let mut best_ask: Option<BookOrder> = None;
+
+    loop {
+        // next order once the best one is filled
+        if best_ask.map_or(true, |ask| ask.quantity == 0) {
+            best_ask = asks.next();
+        }
+        let ask = match best_ask.as_mut() {
+            Some(ask) if ask.price > 0 => ask,
+            _ => break, // no more asks
+        };
+
+        let max_pc_qty = fee_tier.remove_taker_fee(amount_in) / pc_lot_size;
+        let trade_qty = ask.quantity.min(max_pc_qty / ask.price);
+        if trade_qty == 0 { // fin
+            break;
+        }
+        ask.quantity -= trade_qty;
+        amount_out = amount_out.saturating_add(trade_qty.saturating_mul(coin_lot_size));
+
+        // trade_qty * price <= max_pc_qty -- can't overflow
+        let native_fill_price = trade_qty * ask.price * pc_lot_size;
+        let native_taker_fee = fee_tier.taker_fee(native_fill_price);
+        amount_in = amount_in
+            .saturating_sub(native_fill_price)
+            .saturating_sub(native_taker_fee);
+    }
+    amount_out
+}
+
+/// Quote received for `amount_in` base, filling `bids` best (highest) first
+///
+/// Fees are taken from the quote of each fill
+pub fn ask_quote(
+    bids: impl IntoIterator<Item = BookOrder>,
+    coin_lot_size: u64,
+    pc_lot_size: u64,
+    fee_tier: FeeTier,
+    amount_in: u64,
+) -> u64 {
+    if coin_lot_size == 0 || pc_lot_size == 0 {
+        return 0;
+    }
+    let mut unfilled_qty = amount_in / coin_lot_size;
+    let mut amount_out: u64 = 0;
+
+    for bid in bids {
+        let trade_qty = bid.quantity.min(unfilled_qty);
+        if trade_qty == 0 { // fin
+            break;
+        }
+        unfilled_qty -= trade_qty;
+
+        let native_taker_pc_qty = trade_qty
+            .saturating_mul(bid.price)
+            .saturating_mul(pc_lot_size);
+        let native_taker_fee = fee_tier.taker_fee(native_taker_pc_qty);
+        amount_out = amount_out.saturating_add(native_taker_pc_qty - native_taker_fee);
+    }
+    amount_out
+}
+
+/// Quote which buys at least `amount_out` base from `asks` (whole lots), None
+/// if the book is too thin -- the inverse of `bid_quote`, fees included
+pub fn bid_quote_exact_out(
+    asks: impl IntoIterator<Item = BookOrder>,
+    coin_lot_size: u64,
+    pc_lot_size: u64,
+    fee_tier: FeeTier,
+    amount_out: u64,
+) -> Option<u64> {
+    if coin_lot_size == 0 || pc_lot_size == 0 {
+        return None;
+    }
+    let mut unfilled_qty = amount_out / coin_lot_size + (amount_out % coin_lot_size != 0) as u64;
+    let mut amount_in: u64 = 0;
+
+    for ask in asks {
+        if unfilled_qty == 0 || ask.price == 0 || ask.quantity == 0 { // fin (like `bid_quote`)
+            break;
+        }
+        let trade_qty = ask.quantity.min(unfilled_qty);
+        unfilled_qty -= trade_qty;
+
+        let native_fill_price = trade_qty.checked_mul(ask.price)?.checked_mul(pc_lot_size)?;
+        let native_taker_fee = fee_tier.taker_fee(native_fill_price);
+        amount_in = amount_in.checked_add(native_fill_price)?.checked_add(native_taker_fee)?;
+    }
+    if unfilled_qty == 0 {
+        Some(amount_in)
+    } else {
+        None
+    }
+}
+
+/// Base (whole lots) which sells for at least `amount_out` quote into `bids`,
+/// None if the book is too thin -- the inverse of `ask_quote`
+pub fn ask_quote_exact_out(
+    bids: impl IntoIterator<Item = BookOrder>,
+    coin_lot_size: u64,
+    pc_lot_size: u64,
+    fee_tier: FeeTier,
+    amount_out: u64,
+) -> Option<u64> {
+    if coin_lot_size == 0 || pc_lot_size == 0 {
+        return None;
+    }
+    let net = |qty: u64, lot_pc_qty: u64| -> Option<u64> {
+        let native_taker_pc_qty = qty.checked_mul(lot_pc_qty)?;
+        Some(native_taker_pc_qty - fee_tier.taker_fee(native_taker_pc_qty))
+    };
+    let mut unfilled_out = amount_out;
+    let mut lots: u64 = 0;
+
+    for bid in bids {
+        if unfilled_out == 0 || bid.quantity == 0 { // fin (like `ask_quote`)
+            break;
+        }
+        let lot_pc_qty = bid.price.checked_mul(pc_lot_size)?;
+        if net(bid.quantity, lot_pc_qty)? < unfilled_out {
+            // the whole order, it's filled before the next one
+            lots = lots.checked_add(bid.quantity)?;
+            unfilled_out -= net(bid.quantity, lot_pc_qty)?;
+            continue;
+        }
+        // fewest lots of the order which cover the rest, after the fee -- a
+        // lot nets at most `lot_pc_qty`, so adding the missing lots never overshoots
+        let mut trade_qty = unfilled_out / lot_pc_qty;
+        loop {
+            let missing = unfilled_out.saturating_sub(net(trade_qty, lot_pc_qty)?);
+            if missing == 0 {
+                break;
+            }
+            trade_qty += missing / lot_pc_qty + (missing % lot_pc_qty != 0) as u64;
+        }
+        lots = lots.checked_add(trade_qty)?;
+        unfilled_out = 0;
+    }
+    if unfilled_out == 0 {
+        lots.checked_mul(coin_lot_size)
+    } else {
+        None
+    }
+}
@@ -5,7 +5,8 @@ use {
             CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult, TradeDirection,
             TradingTokenResult,
         },
-        error::SwapError,
+        pool_utils::exact_out::{ceil_div, settle_exact_in},
+        error::{QuoteError, SwapError},
     },
     arrayref::{array_mut_ref, array_ref},
     solana_program::{
@@ -35,28 +36,91 @@ impl Stable {
         pool_amounts: [u128; 2],    // [0] = src_amount, [1] = dst_amount
         percision_multipliers: [u64; 2], 
         scaled_amount_in: u128, 
-     ) -> u128 {
+     ) -> Result<u128, QuoteError> {
+        if pool_amounts[0] == 0 || pool_amounts[1] == 0 {
+            return Err(QuoteError::InsufficientLiquidity);
+        }
+        self.checked_quote(pool_amounts, percision_multipliers, scaled_amount_in)
+            .ok_or(QuoteError::MathOverflow)
+    }
+
+    fn checked_quote(
+        &self, 
+        pool_amounts: [u128; 2],
+        percision_multipliers: [u64; 2], 
+        scaled_amount_in: u128, 
+    ) -> Option<u128> {
         // stableswap with percision multipliers 
-        let xp: Vec<u128> = vec![
-            pool_amounts[0] * percision_multipliers[0] as u128,
-            pool_amounts[1] * percision_multipliers[1] as u128,
+        let xp = [
+            pool_amounts[0].checked_mul(percision_multipliers[0] as u128)?,
+            pool_amounts[1].checked_mul(percision_multipliers[1] as u128)?,
         ];
-        let dx = scaled_amount_in * percision_multipliers[0] as u128;
+        let dx = scaled_amount_in.checked_mul(percision_multipliers[0] as u128)?;
 
-        let x = xp[0] + dx;
-        let leverage = compute_a(self.amp).unwrap();
-        let d = compute_d(leverage, xp[0], xp[1]).unwrap();
-        let y = compute_new_destination_amount(leverage, x, d).unwrap();
-        let dy = xp[1] - y;
-        let out_amount = dy.checked_div(percision_multipliers[1] as u128).unwrap();
+        let x = xp[0].checked_add(dx)?;
+        let leverage = compute_a(self.amp)?;
+        let d = compute_d(leverage, xp[0], xp[1])?;
+        let y = compute_new_destination_amount(leverage, x, d)?;
+        let dy = xp[1].checked_sub(y)?;
+        let out_amount = dy.checked_div(percision_multipliers[1] as u128)?;
 
         // reduce fees at the end
         let fees = out_amount
-            .checked_mul(self.fee_numerator).unwrap()
-            .checked_div(self.fee_denominator).unwrap();
+            .checked_mul(self.fee_numerator)?
+            .checked_div(self.fee_denominator)?;
         
+        out_amount.checked_sub(fees)
+    }
+
+    /// Input which gets at least `amount_out` -- the invariant is symmetric,
+    /// so `compute_new_destination_amount` also solves for the source balance
+    pub fn get_quote_exact_out(
+        &self, 
+        pool_amounts: [u128; 2],    // [0] = src_amount, [1] = dst_amount
+        percision_multipliers: [u64; 2], 
+        amount_out: u128, 
+    ) -> Result<u128, QuoteError> {
+        if pool_amounts[0] == 0 || pool_amounts[1] == 0 {
+            return Err(QuoteError::InsufficientLiquidity);
+        }
+        if amount_out == 0 {
+            return Ok(0);
+        }
+        let estimate = self.exact_in_estimate(pool_amounts, percision_multipliers, amount_out)?;
+        settle_exact_in(estimate, amount_out, |amount_in| {
+            self.get_quote(pool_amounts, percision_multipliers, amount_in)
+        })
+    }
+
+    fn exact_in_estimate(
+        &self, 
+        pool_amounts: [u128; 2],
+        percision_multipliers: [u64; 2], 
+        amount_out: u128, 
+    ) -> Result<u128, QuoteError> {
+        let overflow = || QuoteError::MathOverflow;
+        let xp = [
+            pool_amounts[0].checked_mul(percision_multipliers[0] as u128).ok_or_else(overflow)?,
+            pool_amounts[1].checked_mul(percision_multipliers[1] as u128).ok_or_else(overflow)?,
+        ];
+
+        // the fee comes off the output
+        let out_amount = self.fee_denominator
+            .checked_sub(self.fee_numerator)
+            .and_then(|rate| ceil_div(amount_out.checked_mul(self.fee_denominator)?, rate))
+            .ok_or_else(overflow)?;
+        let dy = out_amount.checked_mul(percision_multipliers[1] as u128).ok_or_else(overflow)?;
+        let y = match xp[1].checked_sub(dy) {
+            Some(y) if y > 0 => y,
+            _ => return Err(QuoteError::InsufficientLiquidity),
+        };
 
-        out_amount - fees
+        let leverage = compute_a(self.amp).ok_or_else(overflow)?;
+        let x = compute_d(leverage, xp[0], xp[1])
+            .and_then(|d| compute_new_destination_amount(leverage, y, d))
+            .ok_or_else(overflow)?;
+        // back to the scale of the source, rounded up
+        ceil_div(x.saturating_sub(xp[0]), percision_multipliers[0] as u128).ok_or_else(overflow)
     }
 }
 
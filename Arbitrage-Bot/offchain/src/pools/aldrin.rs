@@ -10,18 +10,22 @@ use anchor_client::Program;
 use anchor_client::Cluster;
 
 use solana_sdk::account::Account;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 
 use tmp::accounts as tmp_accounts;
 use tmp::instruction as tmp_ix;
+use tmp::ix_data::DexKind;
 
-use crate::serialize::token::{Token, WrappedPubkey, unpack_token_account};
+use crate::serialize::token::{Token, WrappedPubkey};
 use crate::serialize::pool::JSONFeeStructure; 
 use crate::pool::PoolOperations;
+use crate::pools::{check_direction, pool_reserves, token_scale, token_vault, vault_amounts};
+use crate::error::QuoteError;
 use crate::pool_utils::base::CurveType;
-use crate::utils::{str2pubkey, derive_token_address};
+use crate::utils::{str2pubkey, derive_token_address, derive_swap_state_address};
 use crate::pool_utils::{
-    orca::{get_pool_quote_with_amounts},
+    orca::{get_pool_quote_exact_out, get_pool_quote_with_amounts, get_swap_curve, CurveParams},
+    calculator::TradeDirection,
     fees::Fees,
 };
 use crate::constants::*;
@@ -52,17 +56,47 @@ pub struct AldrinPool {
     pub pool_amounts: HashMap<String, u128>
 }
 
+const ALDRIN_CURVE_PARAMS: CurveParams = CurveParams {
+    amp: 170, // from sdk 
+    token_b_price: 0,
+    token_b_offset: 0,
+};
+
+impl AldrinPool {
+    fn fees(&self) -> Fees {
+        let trader_fee = &self.fees.trader_fee;
+        let owner_fee = &self.fees.owner_fee;
+        Fees {
+            trade_fee_numerator: trader_fee.numerator,
+            trade_fee_denominator: trader_fee.denominator,
+            owner_trade_fee_numerator: owner_fee.numerator,
+            owner_trade_fee_denominator: owner_fee.denominator,
+            owner_withdraw_fee_numerator: 0,
+            owner_withdraw_fee_denominator: 0,
+            host_fee_numerator: 0,
+            host_fee_denominator: 0,
+        }
+    }
+
+    fn curve_type(&self) -> CurveType {
+        if self.curve_type == 1 { 
+            CurveType::Stable
+        } else {
+            CurveType::ConstantProduct 
+        }
+    }
+}
+
 impl PoolOperations for AldrinPool {
     fn swap_ix(&self, 
         program: &Program,
         owner: &Pubkey,
-        _mint_in: &Pubkey, 
-        mint_out: &Pubkey
-    ) -> Vec<Instruction> {
-        let (state_pda, _) = Pubkey::find_program_address(
-            &[b"swap_state"], 
-            &program.id()
-        );
+        mint_in: &Pubkey, 
+        mint_out: &Pubkey,
+        min_amount_out: Option<u64>,
+    ) -> Result<Vec<Instruction>, QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let state_pda = derive_swap_state_address(&program.id(), owner);
 
         let base_token_mint = &self.token_ids[0];
         let quote_token_mint = &self.token_ids[1];
@@ -105,9 +139,8 @@ impl PoolOperations for AldrinPool {
                     token_program: *TOKEN_PROGRAM_ID,
                     swap_state: state_pda, 
                 })
-                .args(tmp_ix::AldrinSwapV1 { is_inverted })
-                .instructions()
-                .unwrap();
+                .args(tmp_ix::AldrinSwapV1 { is_inverted, min_amount_out })
+                .instructions()?;
         } else { 
             swap_ix = program
                 .request()
@@ -127,11 +160,63 @@ impl PoolOperations for AldrinPool {
                     token_program: *TOKEN_PROGRAM_ID,
                     swap_state: state_pda, 
                 })
-                .args(tmp_ix::AldrinSwapV2 { is_inverted })
-                .instructions()
-                .unwrap();
+                .args(tmp_ix::AldrinSwapV2 { is_inverted, min_amount_out })
+                .instructions()?;
         }
-        swap_ix
+        Ok(swap_ix)
+    }
+
+    fn route_hop(&self, 
+        owner: &Pubkey,
+        mint_in: &Pubkey, 
+        mint_out: &Pubkey,
+    ) -> Result<(DexKind, Vec<AccountMeta>), QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let base_token_mint = &self.token_ids[0];
+        let quote_token_mint = &self.token_ids[1];
+
+        let base_token_vault = self.tokens
+            .get(base_token_mint)
+            .unwrap()
+            .addr.0;
+        let quote_token_vault = self.tokens
+            .get(quote_token_mint)
+            .unwrap()
+            .addr.0;
+
+        let is_inverted = &mint_out.to_string() == quote_token_mint;
+        let user_base_ata = derive_token_address(
+            owner, 
+            &Pubkey::from_str(base_token_mint).unwrap()
+        );
+        let user_quote_ata = derive_token_address(
+            owner, 
+            &Pubkey::from_str(quote_token_mint).unwrap()
+        );
+
+        // aldrin swap ix order + program -- v2 has the curve before the token program
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.pool_public_key.0, false),
+            AccountMeta::new_readonly(self.pool_signer.0, false),
+            AccountMeta::new(self.pool_mint.0, false),
+            AccountMeta::new(base_token_vault, false),
+            AccountMeta::new(quote_token_vault, false),
+            AccountMeta::new(self.fee_pool_token_account.0, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(user_base_ata, false),
+            AccountMeta::new(user_quote_ata, false),
+        ];
+        let dex = if self.pool_version == 1 {
+            accounts.push(AccountMeta::new_readonly(*TOKEN_PROGRAM_ID, false));
+            accounts.push(AccountMeta::new_readonly(*ALDRIN_V1_PROGRAM_ID, false));
+            DexKind::AldrinV1 { is_inverted }
+        } else {
+            accounts.push(AccountMeta::new_readonly(self.curve.0, false));
+            accounts.push(AccountMeta::new_readonly(*TOKEN_PROGRAM_ID, false));
+            accounts.push(AccountMeta::new_readonly(*ALDRIN_V2_PROGRAM_ID, false));
+            DexKind::AldrinV2 { is_inverted }
+        };
+        Ok((dex, accounts))
     }
 
     fn get_quote_with_amounts_scaled(
@@ -139,43 +224,41 @@ impl PoolOperations for AldrinPool {
         scaled_amount_in: u128, 
         mint_in: &Pubkey,
         mint_out: &Pubkey,
-    ) -> u128 {
+    ) -> Result<u128, QuoteError> {
         
-        let pool_src_amount = *self.pool_amounts.get(&mint_in.to_string()).unwrap();
-        let pool_dst_amount = *self.pool_amounts.get(&mint_out.to_string()).unwrap();
-
-        // compute fees 
-        let trader_fee = &self.fees.trader_fee;
-        let owner_fee = &self.fees.owner_fee;
-        let fees = Fees {
-            trade_fee_numerator: trader_fee.numerator,
-            trade_fee_denominator: trader_fee.denominator,
-            owner_trade_fee_numerator: owner_fee.numerator,
-            owner_trade_fee_denominator: owner_fee.denominator,
-            owner_withdraw_fee_numerator: 0,
-            owner_withdraw_fee_denominator: 0,
-            host_fee_numerator: 0,
-            host_fee_denominator: 0,
-        };
-
-        let ctype = if self.curve_type == 1 { 
-            CurveType::Stable
-        } else {
-            CurveType::ConstantProduct 
-        };
+        let [pool_src_amount, pool_dst_amount] = pool_reserves(&self.pool_amounts, mint_in, mint_out)?;
 
         // get quote -- works for either constant product or stable swap 
-        
+        let swap_curve = get_swap_curve(self.curve_type(), &ALDRIN_CURVE_PARAMS);
 
         get_pool_quote_with_amounts(
             scaled_amount_in,
-            ctype,
-            170, // from sdk 
-            &fees, 
+            &swap_curve,
+            TradeDirection::AtoB, // both curves are symmetric
+            &self.fees(), 
             pool_src_amount, 
             pool_dst_amount, 
             None,
-        ).unwrap()
+        )
+    }
+
+    fn get_quote_exact_out(
+        &self, 
+        amount_out: u128, 
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> Result<u128, QuoteError> {
+        let [pool_src_amount, pool_dst_amount] = pool_reserves(&self.pool_amounts, mint_in, mint_out)?;
+
+        get_pool_quote_exact_out(
+            amount_out,
+            self.curve_type(),
+            &ALDRIN_CURVE_PARAMS,
+            TradeDirection::AtoB,
+            &self.fees(),
+            pool_src_amount,
+            pool_dst_amount,
+        )
     }
 
     fn can_trade(&self, 
@@ -203,40 +286,25 @@ impl PoolOperations for AldrinPool {
         let accounts = self
             .get_mints()
             .iter()
-            .map(|mint| self.mint_2_addr(mint))
+            .map(|mint| self.tokens[&mint.to_string()].addr.0)
             .collect();        
         accounts 
     }
 
-    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, _cluster: Cluster) { 
-        let ids: Vec<String> = self
-            .get_mints()
-            .iter()
-            .map(|mint| mint.to_string())
-            .collect();
-        let id0 = &ids[0];
-        let id1 = &ids[1];
-        
-        let acc_data0 = &accounts[0].as_ref().unwrap().data;
-        let acc_data1 = &accounts[1].as_ref().unwrap().data;
-
-        let amount0 = unpack_token_account(acc_data0).amount as u128;
-        let amount1 = unpack_token_account(acc_data1).amount as u128;
-
-        self.pool_amounts.insert(id0.clone(), amount0);
-        self.pool_amounts.insert(id1.clone(), amount1);
+    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, _cluster: Cluster) -> Result<(), QuoteError> { 
+        let amounts = vault_amounts(&self.get_update_accounts(), &accounts)?;
+        for (mint, amount) in self.get_mints().iter().zip(amounts) {
+            self.pool_amounts.insert(mint.to_string(), amount);
+        }
+        Ok(())
     }
 
-    fn mint_2_addr(&self, mint: &Pubkey) -> Pubkey {
-        let token = self.tokens.get(&mint.to_string()).unwrap();
-        
-        token.addr.0
+    fn mint_2_addr(&self, mint: &Pubkey) -> Result<Pubkey, QuoteError> {
+        token_vault(&self.tokens, mint)
     }
 
-    fn mint_2_scale(&self, mint: &Pubkey) -> u64 {
-        let token = self.tokens.get(&mint.to_string()).unwrap();
-                
-        token.scale
+    fn mint_2_scale(&self, mint: &Pubkey) -> Result<u64, QuoteError> {
+        token_scale(&self.tokens, mint)
     }
 
     fn get_mints(&self) -> Vec<Pubkey> {
@@ -2,20 +2,23 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use serde;
 use serde::{Deserialize, Serialize};
-use crate::serialize::token::{Token, WrappedPubkey, unpack_token_account};
+use crate::serialize::token::{Token, WrappedPubkey};
 use crate::pool::PoolOperations;
+use crate::pools::{check_direction, pool_reserves, token_scale, token_vault, vault_amounts};
+use crate::error::QuoteError;
 
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::Cluster;
 use anchor_client::Program;
 
 use solana_sdk::account::Account;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 
 use tmp::accounts as tmp_accounts;
 use tmp::instruction as tmp_ix;
+use tmp::ix_data::DexKind;
 
-use crate::utils::{str2pubkey, derive_token_address};
+use crate::utils::{str2pubkey, derive_token_address, derive_swap_state_address};
 use crate::constants::*;
 use crate::pool_utils::stable::Stable;
 
@@ -37,17 +40,40 @@ pub struct MercurialPool {
     pub pool_amounts: HashMap<String, u128>
 }
 
+impl MercurialPool {
+    fn calculator(&self) -> Stable {
+        Stable {
+            amp: self.amp, 
+            fee_numerator: self.fee_numerator as u128, 
+            fee_denominator: 10_u128.pow(10),
+        }
+    }
+
+    // [in, out] multipliers
+    fn percision_multipliers(&self, mint_in: &Pubkey) -> Result<[u64; 2], QuoteError> {
+        let input_idx = self.token_ids
+            .iter()
+            .position(|m| *m == mint_in.to_string())
+            .ok_or(QuoteError::UnknownMint(*mint_in))?;
+        let output_idx = (input_idx + 1) % 2; 
+
+        Ok([
+            self.precision_multiplier[input_idx], 
+            self.precision_multiplier[output_idx]
+        ])
+    }
+}
+
 impl PoolOperations for MercurialPool {
     fn swap_ix(&self, 
         program: &Program,
         owner: &Pubkey,
         mint_in: &Pubkey, 
-        mint_out: &Pubkey
-    ) -> Vec<Instruction> {
-        let (swap_state_pda, _) = Pubkey::find_program_address(
-            &[b"swap_state"], 
-            &program.id()
-        );
+        mint_out: &Pubkey,
+        min_amount_out: Option<u64>,
+    ) -> Result<Vec<Instruction>, QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let swap_state_pda = derive_swap_state_address(&program.id(), owner);
         let user_src = derive_token_address(owner, mint_in);
         let user_dst = derive_token_address(owner, mint_out); 
 
@@ -68,11 +94,37 @@ impl PoolOperations for MercurialPool {
                 mercurial_swap_program: *MERCURIAL_PROGRAM_ID,
                 swap_state: swap_state_pda,
             })
-            .args(tmp_ix::MercurialSwap { })
-            .instructions()
-            .unwrap();        
+            .args(tmp_ix::MercurialSwap { min_amount_out })
+            .instructions()?;
         
-        swap_ix
+        Ok(swap_ix)
+    }
+
+    fn route_hop(&self, 
+        owner: &Pubkey,
+        mint_in: &Pubkey, 
+        mint_out: &Pubkey,
+    ) -> Result<(DexKind, Vec<AccountMeta>), QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let user_src = derive_token_address(owner, mint_in);
+        let user_dst = derive_token_address(owner, mint_out); 
+
+        let pool0 = &self.tokens[&self.token_ids[0]].addr;
+        let pool1 = &self.tokens[&self.token_ids[1]].addr;
+
+        // mercurial swap ix order + program 
+        let accounts = vec![
+            AccountMeta::new(self.pool_account.0, false),
+            AccountMeta::new_readonly(*TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(self.authority.0, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(pool0.0, false), // src/dst order doesnt matter ??
+            AccountMeta::new(pool1.0, false), // src/dst order doesnt matter ??
+            AccountMeta::new(user_src, false),
+            AccountMeta::new(user_dst, false),
+            AccountMeta::new_readonly(*MERCURIAL_PROGRAM_ID, false),
+        ];
+        Ok((DexKind::Mercurial, accounts))
     }
 
     fn get_quote_with_amounts_scaled(
@@ -80,40 +132,27 @@ impl PoolOperations for MercurialPool {
         scaled_amount_in: u128, 
         mint_in: &Pubkey,
         mint_out: &Pubkey,
-    ) -> u128 {
-        let fee_denom = 10_u128.pow(10); 
-
-        let calculator = Stable {
-            amp: self.amp, 
-            fee_numerator: self.fee_numerator as u128, 
-            fee_denominator: fee_denom,
-        };
-
+    ) -> Result<u128, QuoteError> {
         // only stable swap pools here 
-        let pool_src_amount = self.pool_amounts.get(&mint_in.to_string()).unwrap();
-        let pool_dst_amount = self.pool_amounts.get(&mint_out.to_string()).unwrap();
-        let pool_amounts = [*pool_src_amount, *pool_dst_amount];
-
-        let input_idx = self.token_ids
-            .iter()
-            .position(|m| *m == mint_in.to_string())
-            .unwrap();
-        let output_idx = (input_idx + 1) % 2; 
-
-        let percision_multipliers = [
-            self.precision_multiplier[input_idx], 
-            self.precision_multiplier[output_idx]
-        ];
-
-        
+        let pool_amounts = pool_reserves(&self.pool_amounts, mint_in, mint_out)?;
 
-        calculator.get_quote(
+        self.calculator().get_quote(
             pool_amounts,    
-            percision_multipliers, 
+            self.percision_multipliers(mint_in)?, 
             scaled_amount_in 
         )
     }
 
+    fn get_quote_exact_out(
+        &self, 
+        amount_out: u128, 
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> Result<u128, QuoteError> {
+        let pool_amounts = pool_reserves(&self.pool_amounts, mint_in, mint_out)?;
+        self.calculator().get_quote_exact_out(pool_amounts, self.percision_multipliers(mint_in)?, amount_out)
+    }
+
     fn get_name(&self) -> String {
          
         "Mercurial".to_string()
@@ -134,41 +173,26 @@ impl PoolOperations for MercurialPool {
         let accounts = self
             .get_mints()
             .iter()
-            .map(|mint| self.mint_2_addr(mint))
+            .map(|mint| self.tokens[&mint.to_string()].addr.0)
             .collect();        
         accounts 
     }
 
-    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, _cluster: Cluster) { 
-        let ids: Vec<String> = self
-            .get_mints()
-            .iter()
-            .map(|mint| mint.to_string())
-            .collect();
-        let id0 = &ids[0];
-        let id1 = &ids[1];
-        
-        let acc_data0 = &accounts[0].as_ref().unwrap().data;
-        let acc_data1 = &accounts[1].as_ref().unwrap().data;
-
-        let amount0 = unpack_token_account(acc_data0).amount as u128;
-        let amount1 = unpack_token_account(acc_data1).amount as u128;
-
-        self.pool_amounts.insert(id0.clone(), amount0);
-        self.pool_amounts.insert(id1.clone(), amount1);
+    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, _cluster: Cluster) -> Result<(), QuoteError> { 
+        let amounts = vault_amounts(&self.get_update_accounts(), &accounts)?;
+        for (mint, amount) in self.get_mints().iter().zip(amounts) {
+            self.pool_amounts.insert(mint.to_string(), amount);
+        }
+        Ok(())
     }
 
 
-    fn mint_2_addr(&self, mint: &Pubkey) -> Pubkey {
-        let token = self.tokens.get(&mint.to_string()).unwrap();
-        
-        token.addr.0
+    fn mint_2_addr(&self, mint: &Pubkey) -> Result<Pubkey, QuoteError> {
+        token_vault(&self.tokens, mint)
     }
 
-    fn mint_2_scale(&self, mint: &Pubkey) -> u64 {
-        let token = self.tokens.get(&mint.to_string()).unwrap();
-                
-        token.scale
+    fn mint_2_scale(&self, mint: &Pubkey) -> Result<u64, QuoteError> {
+        token_scale(&self.tokens, mint)
     }
 
     fn get_mints(&self) -> Vec<Pubkey> {
@@ -11,4 +11,68 @@ pub mod saber;
 pub use saber::*; 
 
 pub mod serum; 
-pub use serum::*; 
\ No newline at end of file
+pub use serum::*; 
+
+use std::collections::HashMap;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use solana_sdk::account::Account;
+
+use crate::error::QuoteError;
+use crate::serialize::token::{unpack_token_account, Token};
+
+/// Err unless `mint_in` -> `mint_out` swaps two different mints of the pool
+pub fn check_direction(mints: &[Pubkey], mint_in: &Pubkey, mint_out: &Pubkey) -> Result<(), QuoteError> {
+    if mint_in != mint_out && mints.contains(mint_in) && mints.contains(mint_out) {
+        Ok(())
+    } else {
+        Err(QuoteError::UnsupportedDirection { mint_in: *mint_in, mint_out: *mint_out })
+    }
+}
+
+/// Vault of `mint` in the token map of a pool JSON
+pub fn token_vault(tokens: &HashMap<String, Token>, mint: &Pubkey) -> Result<Pubkey, QuoteError> {
+    tokens
+        .get(&mint.to_string())
+        .map(|token| token.addr.0)
+        .ok_or(QuoteError::UnknownMint(*mint))
+}
+
+/// Scale of `mint` in the token map of a pool JSON
+pub fn token_scale(tokens: &HashMap<String, Token>, mint: &Pubkey) -> Result<u64, QuoteError> {
+    tokens
+        .get(&mint.to_string())
+        .map(|token| token.scale)
+        .ok_or(QuoteError::UnknownMint(*mint))
+}
+
+/// [in, out] reserves of a pool quoted from its vaults
+pub fn pool_reserves(
+    pool_amounts: &HashMap<String, u128>,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+) -> Result<[u128; 2], QuoteError> {
+    if pool_amounts.is_empty() {
+        return Err(QuoteError::StaleState("vault amounts not set".to_string()));
+    }
+    match (pool_amounts.get(&mint_in.to_string()), pool_amounts.get(&mint_out.to_string())) {
+        (Some(src), Some(dst)) if mint_in != mint_out => Ok([*src, *dst]),
+        _ => Err(QuoteError::UnsupportedDirection { mint_in: *mint_in, mint_out: *mint_out }),
+    }
+}
+
+/// Amounts of the `vaults` token accounts -- all of them or an error, so a
+/// pool is never left with one new and one old reserve
+pub fn vault_amounts(vaults: &[Pubkey], accounts: &[Option<Account>]) -> Result<Vec<u128>, QuoteError> {
+    vaults
+        .iter()
+        .enumerate()
+        .map(|(i, vault)| match accounts.get(i) {
+            Some(Some(account)) if account.data.len() >= 165 => {
+                Ok(unpack_token_account(&account.data).amount as u128)
+            }
+            Some(Some(_)) => Err(QuoteError::StaleState(format!("vault {} isn't a token account", vault))),
+            _ => Err(QuoteError::StaleState(format!("missing vault {}", vault))),
+        })
+        .collect()
+}
@@ -3,23 +3,28 @@ use std::fmt::Debug;
 use serde;
 use serde::{Deserialize, Serialize};
 use solana_sdk::account::Account;
-use crate::serialize::token::{Token, WrappedPubkey, unpack_token_account};
+use crate::serialize::token::{Token, WrappedPubkey};
 use crate::serialize::pool::JSONFeeStructure; 
 use crate::pool::PoolOperations;
+use crate::pools::{check_direction, pool_reserves, token_scale, token_vault, vault_amounts};
+use crate::error::QuoteError;
 
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::Cluster;
 use anchor_client::Program;
 
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 
 use tmp::accounts as tmp_accounts;
 use tmp::instruction as tmp_ix;
+use tmp::ix_data::DexKind;
 
 use crate::pool_utils::base::CurveType;
-use crate::utils::{str2pubkey, derive_token_address};
+use std::convert::TryFrom;
+use crate::utils::{str2pubkey, derive_token_address, derive_swap_state_address};
 use crate::pool_utils::{
-    orca::{get_pool_quote_with_amounts},
+    orca::{get_pool_quote_exact_out, get_pool_quote_with_amounts, get_swap_curve, CurveParams},
+    calculator::TradeDirection,
     fees::Fees,
 };
 use crate::constants::*;
@@ -39,22 +44,63 @@ pub struct OrcaPool {
     pub curve_type: u8,
     #[serde(default)]
     pub amp: u64,
+    // token-swap forks with a ConstantPrice or Offset curve
+    #[serde(default)]
+    pub token_b_price: u64,
+    #[serde(default)]
+    pub token_b_offset: u64,
     // to set later 
     #[serde(skip)]
     pub pool_amounts: HashMap<String, u128>
 }
 
+impl OrcaPool {
+    fn fees(&self) -> Fees {
+        let trader_fee = &self.fee_structure.trader_fee;
+        let owner_fee = &self.fee_structure.owner_fee;
+        Fees {
+            trade_fee_numerator: trader_fee.numerator,
+            trade_fee_denominator: trader_fee.denominator,
+            owner_trade_fee_numerator: owner_fee.numerator,
+            owner_trade_fee_denominator: owner_fee.denominator,
+            owner_withdraw_fee_numerator: 0,
+            owner_withdraw_fee_denominator: 0,
+            host_fee_numerator: 0,
+            host_fee_denominator: 0,
+        }
+    }
+
+    fn curve(&self) -> Result<(CurveType, CurveParams), QuoteError> {
+        let ctype = CurveType::try_from(self.curve_type)
+            .map_err(|_| QuoteError::StaleState(format!("invalid curve type {}", self.curve_type)))?;
+        let params = CurveParams {
+            amp: self.amp,
+            token_b_price: self.token_b_price,
+            token_b_offset: self.token_b_offset,
+        };
+        Ok((ctype, params))
+    }
+
+    // token A = first of the token ids (the pool's token A account)
+    fn trade_direction(&self, mint_in: &Pubkey) -> TradeDirection {
+        if mint_in.to_string() == self.token_ids[0] {
+            TradeDirection::AtoB
+        } else {
+            TradeDirection::BtoA
+        }
+    }
+}
+
 impl PoolOperations for OrcaPool {
     fn swap_ix(&self, 
         program: &Program,
         owner: &Pubkey,
         mint_in: &Pubkey, 
-        mint_out: &Pubkey
-    ) -> Vec<Instruction> {
-        let (swap_state, _) = Pubkey::find_program_address(
-            &[b"swap_state"], 
-            &program.id()
-        );
+        mint_out: &Pubkey,
+        min_amount_out: Option<u64>,
+    ) -> Result<Vec<Instruction>, QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let swap_state = derive_swap_state_address(&program.id(), owner);
         let user_src = derive_token_address(owner, mint_in);
         let user_dst = derive_token_address(owner, mint_out); 
 
@@ -63,8 +109,8 @@ impl PoolOperations for OrcaPool {
             &ORCA_PROGRAM_ID 
         );
 
-        let pool_src = self.mint_2_addr(mint_in);
-        let pool_dst = self.mint_2_addr(mint_out);
+        let pool_src = self.mint_2_addr(mint_in)?;
+        let pool_dst = self.mint_2_addr(mint_out)?;
 
         let swap_ix = program
             .request()
@@ -82,11 +128,44 @@ impl PoolOperations for OrcaPool {
                 token_swap_program: *ORCA_PROGRAM_ID,
                 swap_state,
             })
-            .args(tmp_ix::OrcaSwap { })
-            .instructions()
-            .unwrap();
+            .args(tmp_ix::OrcaSwap { min_amount_out })
+            .instructions()?;
 
-        swap_ix
+        Ok(swap_ix)
+    }
+
+    fn route_hop(&self, 
+        owner: &Pubkey,
+        mint_in: &Pubkey, 
+        mint_out: &Pubkey,
+    ) -> Result<(DexKind, Vec<AccountMeta>), QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let user_src = derive_token_address(owner, mint_in);
+        let user_dst = derive_token_address(owner, mint_out); 
+
+        let (authority_pda, _) = Pubkey::find_program_address(
+            &[&self.address.to_bytes()],
+            &ORCA_PROGRAM_ID 
+        );
+
+        let pool_src = self.mint_2_addr(mint_in)?;
+        let pool_dst = self.mint_2_addr(mint_out)?;
+
+        // orca swap ix order + program 
+        let accounts = vec![
+            AccountMeta::new_readonly(self.address.0, false),
+            AccountMeta::new_readonly(authority_pda, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(user_src, false),
+            AccountMeta::new(pool_src, false),
+            AccountMeta::new(pool_dst, false),
+            AccountMeta::new(user_dst, false),
+            AccountMeta::new(self.pool_token_mint.0, false),
+            AccountMeta::new(self.fee_account.0, false),
+            AccountMeta::new_readonly(*TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*ORCA_PROGRAM_ID, false),
+        ];
+        Ok((DexKind::Orca, accounts))
     }
 
     fn get_quote_with_amounts_scaled(
@@ -94,44 +173,41 @@ impl PoolOperations for OrcaPool {
         scaled_amount_in: u128, 
         mint_in: &Pubkey,
         mint_out: &Pubkey,
-    ) -> u128 {
+    ) -> Result<u128, QuoteError> {
         
-        let pool_src_amount = self.pool_amounts.get(&mint_in.to_string()).unwrap();
-        let pool_dst_amount = self.pool_amounts.get(&mint_out.to_string()).unwrap();
+        let [pool_src_amount, pool_dst_amount] = pool_reserves(&self.pool_amounts, mint_in, mint_out)?;
+        let (ctype, params) = self.curve()?;
+        let swap_curve = get_swap_curve(ctype, &params);
 
-        // compute fees 
-        let trader_fee = &self.fee_structure.trader_fee;
-        let owner_fee = &self.fee_structure.owner_fee;
-        let fees = Fees {
-            trade_fee_numerator: trader_fee.numerator,
-            trade_fee_denominator: trader_fee.denominator,
-            owner_trade_fee_numerator: owner_fee.numerator,
-            owner_trade_fee_denominator: owner_fee.denominator,
-            owner_withdraw_fee_numerator: 0,
-            owner_withdraw_fee_denominator: 0,
-            host_fee_numerator: 0,
-            host_fee_denominator: 0,
-        };
-        let ctype = if self.curve_type == 0 { 
-            CurveType::ConstantProduct 
-        } else if self.curve_type == 2 {
-            CurveType::Stable
-        } else { 
-            panic!("invalid self curve type: {:?}", self.curve_type);
-        };
-
-        // get quote -- works for either constant product or stable swap 
-        
-        
         get_pool_quote_with_amounts(
             scaled_amount_in,
-            ctype,
-            self.amp, 
-            &fees, 
-            *pool_src_amount, 
-            *pool_dst_amount, 
+            &swap_curve,
+            self.trade_direction(mint_in),
+            &self.fees(), 
+            pool_src_amount, 
+            pool_dst_amount, 
             None,
-        ).unwrap()
+        )
+    }
+
+    fn get_quote_exact_out(
+        &self, 
+        amount_out: u128, 
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> Result<u128, QuoteError> {
+        let [pool_src_amount, pool_dst_amount] = pool_reserves(&self.pool_amounts, mint_in, mint_out)?;
+        let (ctype, params) = self.curve()?;
+
+        get_pool_quote_exact_out(
+            amount_out,
+            ctype,
+            &params,
+            self.trade_direction(mint_in),
+            &self.fees(),
+            pool_src_amount,
+            pool_dst_amount,
+        )
     }
 
     fn get_update_accounts(&self) -> Vec<Pubkey> {
@@ -139,7 +215,7 @@ impl PoolOperations for OrcaPool {
         let accounts = self
             .get_mints()
             .iter()
-            .map(|mint| self.mint_2_addr(mint))
+            .map(|mint| self.tokens[&mint.to_string()].addr.0)
             .collect();        
         accounts 
     }
@@ -154,23 +230,12 @@ impl PoolOperations for OrcaPool {
         true
     }
 
-    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, _cluster: Cluster) { 
-        let ids: Vec<String> = self
-            .get_mints()
-            .iter()
-            .map(|mint| mint.to_string())
-            .collect();
-        let id0 = &ids[0];
-        let id1 = &ids[1];
-        
-        let acc_data0 = &accounts[0].as_ref().unwrap().data;
-        let acc_data1 = &accounts[1].as_ref().unwrap().data;
-
-        let amount0 = unpack_token_account(acc_data0).amount as u128;
-        let amount1 = unpack_token_account(acc_data1).amount as u128;
-
-        self.pool_amounts.insert(id0.clone(), amount0);
-        self.pool_amounts.insert(id1.clone(), amount1);
+    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, _cluster: Cluster) -> Result<(), QuoteError> { 
+        let amounts = vault_amounts(&self.get_update_accounts(), &accounts)?;
+        for (mint, amount) in self.get_mints().iter().zip(amounts) {
+            self.pool_amounts.insert(mint.to_string(), amount);
+        }
+        Ok(())
     }
 
     fn get_name(&self) -> String {
@@ -178,16 +243,12 @@ impl PoolOperations for OrcaPool {
         "Orca".to_string()
     }
 
-    fn mint_2_addr(&self, mint: &Pubkey) -> Pubkey {
-        let token = self.tokens.get(&mint.to_string()).unwrap();
-        
-        token.addr.0
+    fn mint_2_addr(&self, mint: &Pubkey) -> Result<Pubkey, QuoteError> {
+        token_vault(&self.tokens, mint)
     }
 
-    fn mint_2_scale(&self, mint: &Pubkey) -> u64 {
-        let token = self.tokens.get(&mint.to_string()).unwrap();
-                
-        token.scale
+    fn mint_2_scale(&self, mint: &Pubkey) -> Result<u64, QuoteError> {
+        token_scale(&self.tokens, mint)
     }
 
     fn get_mints(&self) -> Vec<Pubkey> {
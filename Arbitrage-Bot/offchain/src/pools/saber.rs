@@ -3,20 +3,23 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use serde;
 use serde::{Deserialize, Serialize};
-use crate::serialize::token::{Token, WrappedPubkey, unpack_token_account};
+use crate::serialize::token::{Token, WrappedPubkey};
 use crate::pool::PoolOperations;
+use crate::pools::{check_direction, pool_reserves, token_scale, token_vault, vault_amounts};
+use crate::error::QuoteError;
 
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::Cluster;
 use anchor_client::Program;
 
 use solana_sdk::account::Account;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 
 use tmp::accounts as tmp_accounts;
 use tmp::instruction as tmp_ix;
+use tmp::ix_data::DexKind;
 
-use crate::utils::{str2pubkey, derive_token_address};
+use crate::utils::{str2pubkey, derive_token_address, derive_swap_state_address};
 use crate::constants::*;
 use crate::pool_utils::stable::Stable;
 
@@ -37,23 +40,34 @@ pub struct SaberPool {
     pub pool_amounts: HashMap<String, u128>
 }
 
+impl SaberPool {
+    fn calculator(&self) -> Stable {
+        Stable {
+            amp: self.target_amp, 
+            fee_numerator: self.fee_numerator as u128, 
+            fee_denominator: self.fee_denominator as u128,
+        }
+    }
+}
+
 impl PoolOperations for SaberPool {
     fn swap_ix(&self, 
         program: &Program,
         owner: &Pubkey,
         mint_in: &Pubkey, 
-        mint_out: &Pubkey
-    ) -> Vec<Instruction> {
-        let (swap_state, _) = Pubkey::find_program_address(
-            &[b"swap_state"], 
-            &program.id()
-        );
+        mint_out: &Pubkey,
+        min_amount_out: Option<u64>,
+    ) -> Result<Vec<Instruction>, QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let swap_state = derive_swap_state_address(&program.id(), owner);
         let user_src = derive_token_address(owner, mint_in);
         let user_dst = derive_token_address(owner, mint_out); 
         
-        let pool_src = self.tokens.get(&mint_in.to_string()).unwrap().addr.0;
-        let pool_dst = self.tokens.get(&mint_out.to_string()).unwrap().addr.0;
-        let fee_acc = self.fee_accounts.get(&mint_out.to_string()).unwrap();
+        let pool_src = self.mint_2_addr(mint_in)?;
+        let pool_dst = self.mint_2_addr(mint_out)?;
+        let fee_acc = self.fee_accounts
+            .get(&mint_out.to_string())
+            .ok_or(QuoteError::UnknownMint(*mint_out))?;
 
         let swap_ix = program
             .request()
@@ -70,10 +84,40 @@ impl PoolOperations for SaberPool {
                 swap_state, 
                 token_program: *TOKEN_PROGRAM_ID,
             }) 
-            .args(tmp_ix::SaberSwap {}) 
-            .instructions()
-            .unwrap();
-        swap_ix
+            .args(tmp_ix::SaberSwap { min_amount_out }) 
+            .instructions()?;
+        Ok(swap_ix)
+    }
+
+    fn route_hop(&self, 
+        owner: &Pubkey,
+        mint_in: &Pubkey, 
+        mint_out: &Pubkey,
+    ) -> Result<(DexKind, Vec<AccountMeta>), QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let user_src = derive_token_address(owner, mint_in);
+        let user_dst = derive_token_address(owner, mint_out); 
+        
+        let pool_src = self.mint_2_addr(mint_in)?;
+        let pool_dst = self.mint_2_addr(mint_out)?;
+        let fee_acc = self.fee_accounts
+            .get(&mint_out.to_string())
+            .ok_or(QuoteError::UnknownMint(*mint_out))?;
+
+        // saber swap ix order + program 
+        let accounts = vec![
+            AccountMeta::new(self.pool_account.0, false),
+            AccountMeta::new_readonly(self.authority.0, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(user_src, false),
+            AccountMeta::new(pool_src, false),
+            AccountMeta::new(pool_dst, false),
+            AccountMeta::new(user_dst, false),
+            AccountMeta::new(fee_acc.0, false),
+            AccountMeta::new_readonly(*TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*SABER_PROGRAM_ID, false),
+        ];
+        Ok((DexKind::Saber, accounts))
     }
 
     fn get_quote_with_amounts_scaled(
@@ -81,26 +125,25 @@ impl PoolOperations for SaberPool {
         scaled_amount_in: u128, 
         mint_in: &Pubkey,
         mint_out: &Pubkey,
-    ) -> u128 {
-
-        let calculator = Stable {
-            amp: self.target_amp, 
-            fee_numerator: self.fee_numerator as u128, 
-            fee_denominator: self.fee_denominator as u128,
-        };
-
-        let pool_src_amount = self.pool_amounts.get(&mint_in.to_string()).unwrap();
-        let pool_dst_amount = self.pool_amounts.get(&mint_out.to_string()).unwrap();
-        let pool_amounts = [*pool_src_amount, *pool_dst_amount];
+    ) -> Result<u128, QuoteError> {
+        let pool_amounts = pool_reserves(&self.pool_amounts, mint_in, mint_out)?;
         let percision_multipliers = [1, 1];
 
-        
-        calculator.get_quote(
+        self.calculator().get_quote(
             pool_amounts,    
             percision_multipliers, 
             scaled_amount_in 
         )
+    }
 
+    fn get_quote_exact_out(
+        &self, 
+        amount_out: u128, 
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> Result<u128, QuoteError> {
+        let pool_amounts = pool_reserves(&self.pool_amounts, mint_in, mint_out)?;
+        self.calculator().get_quote_exact_out(pool_amounts, [1, 1], amount_out)
     }
 
     fn get_update_accounts(&self) -> Vec<Pubkey> {
@@ -108,28 +151,17 @@ impl PoolOperations for SaberPool {
         let accounts = self
             .get_mints()
             .iter()
-            .map(|mint| self.mint_2_addr(mint))
+            .map(|mint| self.tokens[&mint.to_string()].addr.0)
             .collect();        
         accounts 
     }
 
-    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, _cluster: Cluster) { 
-        let ids: Vec<String> = self
-            .get_mints()
-            .iter()
-            .map(|mint| mint.to_string())
-            .collect();
-        let id0 = &ids[0];
-        let id1 = &ids[1];
-        
-        let acc_data0 = &accounts[0].as_ref().unwrap().data;
-        let acc_data1 = &accounts[1].as_ref().unwrap().data;
-
-        let amount0 = unpack_token_account(acc_data0).amount as u128;
-        let amount1 = unpack_token_account(acc_data1).amount as u128;
-
-        self.pool_amounts.insert(id0.clone(), amount0);
-        self.pool_amounts.insert(id1.clone(), amount1);
+    fn set_update_accounts(&mut self, accounts: Vec<Option<Account>>, _cluster: Cluster) -> Result<(), QuoteError> { 
+        let amounts = vault_amounts(&self.get_update_accounts(), &accounts)?;
+        for (mint, amount) in self.get_mints().iter().zip(amounts) {
+            self.pool_amounts.insert(mint.to_string(), amount);
+        }
+        Ok(())
     }
 
     fn can_trade(&self, 
@@ -147,16 +179,12 @@ impl PoolOperations for SaberPool {
         "Saber".to_string()
     }
 
-    fn mint_2_addr(&self, mint: &Pubkey) -> Pubkey {
-        let token = self.tokens.get(&mint.to_string()).unwrap();
-        
-        token.addr.0
+    fn mint_2_addr(&self, mint: &Pubkey) -> Result<Pubkey, QuoteError> {
+        token_vault(&self.tokens, mint)
     }
 
-    fn mint_2_scale(&self, mint: &Pubkey) -> u64 {
-        let token = self.tokens.get(&mint.to_string()).unwrap();
-                
-        token.scale
+    fn mint_2_scale(&self, mint: &Pubkey) -> Result<u64, QuoteError> {
+        token_scale(&self.tokens, mint)
     }
 
     fn get_mints(&self) -> Vec<Pubkey> {
@@ -5,28 +5,30 @@ use std::fmt::Debug;
 use serde;
 use serde::{Deserialize, Serialize};
 use crate::pool::PoolOperations;
+use crate::pools::check_direction;
+use crate::error::QuoteError;
 use crate::serialize::token::{WrappedPubkey};
 
-use crate::utils::{derive_token_address}; 
+use crate::utils::{derive_token_address, derive_swap_state_address}; 
 
 use solana_sdk::pubkey::Pubkey;
 
 use anchor_spl::dex::serum_dex::{
-    critbit::{SlabView},
-    matching::OrderBookState,
+    critbit::{Slab, SlabView},
     state::Market,
 };
 use std::ops::DerefMut;
 
 
 use anchor_client::{Program, Cluster};
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 
 use solana_sdk::clock::Epoch;
 use solana_sdk::account::Account;
 use solana_sdk::account_info::AccountInfo;
 use crate::constants::*;
 use crate::pool_utils::serum::*;
+use crate::pool_utils::exact_out::settle_exact_in;
 
 use anchor_spl::dex::serum_dex::{
     matching::Side,
@@ -35,6 +37,7 @@ use anchor_spl::dex::serum_dex::{
 use std::str::FromStr;
 use tmp::accounts as tmp_accounts;
 use tmp::instruction as tmp_instructions;
+use tmp::ix_data::DexKind;
 
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -59,6 +62,19 @@ pub struct SerumPool {
     pub open_orders: Option<HashMap<String, String>>
 }
 
+/// market -> open orders of the operator wallet, written by the `open_orders` bin
+pub const OPEN_ORDERS_PATH: &str = "./serum_open_orders.json";
+
+pub fn read_open_orders(path: &str) -> HashMap<String, String> {
+    let oo_str = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&oo_str).unwrap()
+}
+
+pub fn write_open_orders(path: &str, open_orders: &HashMap<String, String>) {
+    let oo_str = serde_json::to_string(open_orders).unwrap();
+    std::fs::write(path, oo_str).unwrap();
+}
+
 fn account_info<'a>(pk: &'a Pubkey, account: &'a mut Account) -> AccountInfo<'a> {
     AccountInfo::new(
         pk, 
@@ -72,116 +88,75 @@ fn account_info<'a>(pk: &'a Pubkey, account: &'a mut Account) -> AccountInfo<'a>
     )
 }
 
-struct Iteration { 
-    amount_in: u64, 
-    amount_out: u64,
-}
-
-// bid: quote -> base 
-fn bid_iteration(
-    iteration: &mut Iteration,
-    fee_tier: &FeeTier, 
-    ob: &mut OrderBookState,
-) -> bool {
-    let quote_lot_size = ob.market_state.pc_lot_size;
-    let base_lot_size = ob.market_state.coin_lot_size;
-
-    let start_amount_in = iteration.amount_in;
-    let max_pc_qty = fee_tier.remove_taker_fee(iteration.amount_in) / quote_lot_size;
-    let mut pc_qty_remaining = max_pc_qty; 
-
-    let done = loop {
-        let flag = match ob.asks.find_min() { // min = best ask 
-            Some(_) => false, 
-            None => true
+// orders of a (cloned) book side, best first -- removed as they're walked
+fn book_orders<'a>(slab: &'a mut Slab, side: Side) -> impl Iterator<Item = BookOrder> + 'a {
+    std::iter::from_fn(move || {
+        let best = match side {
+            Side::Bid => slab.find_max()?, // max = best bid
+            Side::Ask => slab.find_min()?, // min = best ask
         };
-        if flag { break true; }
-        let best_ask = ob.asks.find_min().unwrap(); 
-        let best_offer_ref = ob.asks.get_mut(best_ask).unwrap().as_leaf_mut().unwrap();
-     
-        let trade_price = best_offer_ref.price();
-        let offer_size = best_offer_ref.quantity();
-        let trade_qty = offer_size
-            .min(pc_qty_remaining / best_offer_ref.price().get());
-
-        if trade_qty == 0 { // fin 
-            break true;
-        }
-
-        pc_qty_remaining -= trade_qty * trade_price.get();
-        iteration.amount_out += trade_qty * base_lot_size; 
+        let leaf = slab.get(best)?.as_leaf()?;
+        let order = BookOrder {
+            price: leaf.price().get(),
+            quantity: leaf.quantity(),
+        };
+        let order_id = leaf.order_id();
+        slab.remove_by_key(order_id);
+        Some(order)
+    })
+}
 
-        best_offer_ref.set_quantity(best_offer_ref.quantity() - trade_qty);
+impl SerumPool {
+    // `walk` over the book side `mint_in` trades against, best order first
+    // (asks to buy base, bids to sell it) -- with the lot sizes of the market
+    fn walk_book<T>(
+        &self,
+        mint_in: &Pubkey,
+        walk: impl FnOnce(&mut dyn Iterator<Item = BookOrder>, u64, u64) -> T,
+    ) -> Result<T, QuoteError> {
+        let market_pk = self.own_address.0; 
+        let stale = |what: &str| QuoteError::StaleState(format!("{} of market {}", what, market_pk));
 
-        if best_offer_ref.quantity() == 0 {
-            let best_offer_id = best_offer_ref.order_id();
-            ob.asks.remove_by_key(best_offer_id)
-                .unwrap();
-        }
-        break false; 
-    };
+        // clone accounts for simulation (improve later?)
+        let (mut market_acc, mut bid_acc, mut ask_acc) = match self.accounts.as_deref() {
+            Some([Some(market), Some(bids), Some(asks)]) => (market.clone(), bids.clone(), asks.clone()),
+            _ => return Err(stale("missing accounts")),
+        };
 
-    let native_accum_fill_price = (max_pc_qty - pc_qty_remaining) * quote_lot_size;
-    let native_taker_fee = fee_tier.taker_fee(native_accum_fill_price);
-    let native_pc_qty_remaining =
-        start_amount_in - native_accum_fill_price - native_taker_fee;
-    iteration.amount_in = native_pc_qty_remaining; 
+        let market_acc_info = &account_info(&self.own_address.0, &mut market_acc);
+        let bids_acc = &account_info(&self.bids.0, &mut bid_acc);
+        let asks_acc = &account_info(&self.asks.0, &mut ask_acc);
 
-    done
-}
+        let market = Market::load(
+            market_acc_info, 
+            &SERUM_PROGRAM_ID
+        ).map_err(|_| stale("invalid state"))?;
+        let coin_lot_size = market.coin_lot_size;
+        let pc_lot_size = market.pc_lot_size;
+        let mut bids = market.load_bids_mut(bids_acc).map_err(|_| stale("invalid bids"))?;
+        let mut asks = market.load_asks_mut(asks_acc).map_err(|_| stale("invalid asks"))?;
 
-// ask: base -> quote 
-fn ask_iteration(
-    iteration: &mut Iteration,
-    fee_tier: &FeeTier, 
-    ob: &mut OrderBookState,
-) -> bool {
-    let pc_lot_size = ob.market_state.pc_lot_size;
-    let coin_lot_size = ob.market_state.coin_lot_size;
-
-    let max_qty = iteration.amount_in; 
-    let mut unfilled_qty = max_qty / coin_lot_size;
-    let mut accum_fill_price = 0;
-
-    let done = loop {
-        let best_bid = match ob.bids.find_max() { // min = best ask 
-            Some(best_bid) => {
-                best_bid
-            }, 
-            None => {
-                break true; // no more bids
-            }
+        let mut orders = if *mint_in == self.quote_mint.0 {
+            // bid: quote -> base
+            book_orders(asks.deref_mut(), Side::Ask).peekable()
+        } else {
+            // ask: base -> quote
+            book_orders(bids.deref_mut(), Side::Bid).peekable()
         };
-        let best_bid_ref = ob.bids.get_mut(best_bid).unwrap().as_leaf_mut().unwrap();
-     
-        let trade_price = best_bid_ref.price();
-        let bid_size = best_bid_ref.quantity();
-        let trade_qty = bid_size.min(unfilled_qty);
-
-        if trade_qty == 0 { // fin 
-            break true;
-        }
-
-        best_bid_ref.set_quantity(best_bid_ref.quantity() - trade_qty);
-        unfilled_qty -= trade_qty;
-        accum_fill_price += trade_qty * trade_price.get();
-
-        if best_bid_ref.quantity() == 0 {
-            let best_offer_id = best_bid_ref.order_id();
-            ob.bids.remove_by_key(best_offer_id)
-                .unwrap();
+        if orders.peek().is_none() {
+            return Err(QuoteError::InsufficientLiquidity);
         }
-        break false; 
-    };
-    // fees applied after
-    let native_taker_pc_qty = accum_fill_price * pc_lot_size;
-    let native_taker_fee = fee_tier.taker_fee(native_taker_pc_qty);
-    let net_taker_pc_qty = native_taker_pc_qty - native_taker_fee;
-
-    iteration.amount_out += net_taker_pc_qty;
-    iteration.amount_in = unfilled_qty * coin_lot_size; 
+        Ok(walk(&mut orders, coin_lot_size, pc_lot_size))
+    }
 
-    done
+    // open orders of the operator wallet on this market
+    fn open_orders_address(&self) -> Result<Pubkey, QuoteError> {
+        self.open_orders
+            .as_ref()
+            .and_then(|oos| oos.get(&self.own_address.0.to_string()))
+            .and_then(|oo| Pubkey::from_str(oo).ok())
+            .ok_or_else(|| QuoteError::StaleState(format!("no open orders for market {}", self.own_address.0)))
+    }
 }
 
 impl PoolOperations for SerumPool {
@@ -202,25 +177,29 @@ impl PoolOperations for SerumPool {
         &mut self, 
         accounts: Vec<Option<Account>>,
         cluster: Cluster,
-    ) {
-        self.accounts = Some(accounts);
-        let oo_path = match cluster { 
-            Cluster::Localnet => {
-                "./serum_open_orders.json"
-            }, 
-            Cluster::Mainnet => {
-                panic!("TODO"); // idk 
-                "./serum_open_orders.json"
-            },
-            _ => panic!("clsuter {} not supported", cluster)
+    ) -> Result<(), QuoteError> {
+        match cluster { 
+            Cluster::Localnet | Cluster::Mainnet => {}, 
+            _ => return Err(QuoteError::UnsupportedCluster(cluster.to_string())),
         };
-        let oo_str = std::fs::read_to_string(oo_path).unwrap();
-        let oo_book: HashMap<String, String> = serde_json::from_str(&oo_str).unwrap();
-        self.open_orders = Some(oo_book); 
+        // market, bids, asks
+        if accounts.len() != 3 || accounts.iter().any(|account| account.is_none()) {
+            return Err(QuoteError::StaleState(format!("missing accounts of market {}", self.own_address.0)));
+        }
+        self.accounts = Some(accounts);
+        self.open_orders = Some(read_open_orders(OPEN_ORDERS_PATH)); 
+        Ok(())
     }
 
-    fn mint_2_addr(&self, _mint: &Pubkey) -> Pubkey {
-        panic!("ahhh")
+    // the market's vault of the mint
+    fn mint_2_addr(&self, mint: &Pubkey) -> Result<Pubkey, QuoteError> {
+        if *mint == self.base_mint.0 {
+            Ok(self.base_vault.0)
+        } else if *mint == self.quote_mint.0 {
+            Ok(self.quote_vault.0)
+        } else {
+            Err(QuoteError::UnknownMint(*mint))
+        }
     }
 
     fn get_mints(&self) -> Vec<Pubkey> {
@@ -232,13 +211,13 @@ impl PoolOperations for SerumPool {
         mints
     }
 
-    fn mint_2_scale(&self, mint: &Pubkey) -> u64 {
+    fn mint_2_scale(&self, mint: &Pubkey) -> Result<u64, QuoteError> {
         if *mint == self.base_mint.0 {
-            self.base_scale
-        } else if *mint == self.quote_mint.0 { 
-            self.quote_scale
-        } else { 
-            panic!("invalid mint bro")
+            Ok(self.base_scale)
+        } else if *mint == self.quote_mint.0 {
+            Ok(self.quote_scale)
+        } else {
+            Err(QuoteError::UnknownMint(*mint))
         }
     }
 
@@ -246,87 +225,67 @@ impl PoolOperations for SerumPool {
         &self, 
         amount_in: u128, 
         mint_in: &Pubkey,
-        _mint_out: &Pubkey,
-    ) -> u128 {
-
-        let market_pk = self.own_address.0; 
-        let fee_tier = FeeTier::from_srm_and_msrm_balances(&market_pk, 0, 0);
-        let mut iteration = Iteration {
-            amount_in: amount_in as u64,
-            amount_out: 0,
-        };
-
-        let market_acc = &self.accounts.as_ref().unwrap()[0];
-        let bids_acc = &self.accounts.as_ref().unwrap()[1];
-        let asks_acc = &self.accounts.as_ref().unwrap()[2];
-        
-        // clone accounts for simulation (improve later?)
-        let market_acc = &mut market_acc.clone().unwrap();
-        let bid_acc = &mut bids_acc.clone().unwrap();
-        let ask_acc = &mut asks_acc.clone().unwrap();
-
-        let market_acc_info = &account_info(&self.own_address.0, market_acc);
-        let bids_acc = &account_info(&self.bids.0, bid_acc);
-        let asks_acc = &account_info(&self.asks.0, ask_acc);
-
-        let mut market = Market::load(
-            market_acc_info, 
-            &SERUM_PROGRAM_ID
-        ).unwrap();
-        let mut bids = market.load_bids_mut(bids_acc).unwrap();
-        let mut asks = market.load_asks_mut(asks_acc).unwrap();
-
-        let mut ob = OrderBookState {
-            bids: bids.deref_mut(),
-            asks: asks.deref_mut(),
-            market_state: market.deref_mut(),
-        };
-        
-        if *mint_in == self.quote_mint.0 {
-            // bid: quote -> base
-            loop {
-                let done = bid_iteration(
-                    &mut iteration, 
-                    &fee_tier, 
-                    &mut ob, 
-                );
-                if done { break; }
+        mint_out: &Pubkey,
+    ) -> Result<u128, QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+
+        let fee_tier = FeeTier::from_srm_and_msrm_balances(&self.own_address.0, 0, 0);
+        let amount_in = u64::try_from(amount_in).map_err(|_| QuoteError::MathOverflow)?;
+        let buys_base = *mint_in == self.quote_mint.0;
+
+        self.walk_book(mint_in, |orders, coin_lot_size, pc_lot_size| {
+            if buys_base {
+                bid_quote(orders, coin_lot_size, pc_lot_size, fee_tier, amount_in) as u128
+            } else {
+                ask_quote(orders, coin_lot_size, pc_lot_size, fee_tier, amount_in) as u128
             }
-            iteration.amount_out as u128
-
-        } else if *mint_in == self.base_mint.0 {
-            // ask: base -> quote
-            loop {
-                let done = ask_iteration(
-                    &mut iteration, 
-                    &fee_tier, 
-                    &mut ob, 
-                );
-                if done { break; }
-            }
-            iteration.amount_out as u128
+        })
+    }
 
-        } else { 
-            panic!("invalid mints");
-        }
+    fn get_quote_exact_out(
+        &self, 
+        amount_out: u128, 
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> Result<u128, QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+
+        let fee_tier = FeeTier::from_srm_and_msrm_balances(&self.own_address.0, 0, 0);
+        let amount_out = u64::try_from(amount_out).map_err(|_| QuoteError::MathOverflow)?;
+        let buys_base = *mint_in == self.quote_mint.0;
+
+        // the search re-walks the book, so it's read once
+        let (orders, coin_lot_size, pc_lot_size) = self.walk_book(mint_in, |orders, coin_lot_size, pc_lot_size| {
+            (orders.collect::<Vec<BookOrder>>(), coin_lot_size, pc_lot_size)
+        })?;
+        let estimate = if buys_base {
+            bid_quote_exact_out(orders.iter().copied(), coin_lot_size, pc_lot_size, fee_tier, amount_out)
+        } else {
+            ask_quote_exact_out(orders.iter().copied(), coin_lot_size, pc_lot_size, fee_tier, amount_out)
+        }.ok_or(QuoteError::InsufficientLiquidity)?;
+
+        settle_exact_in(estimate as u128, amount_out as u128, |amount_in| {
+            let amount_in = u64::try_from(amount_in).map_err(|_| QuoteError::MathOverflow)?;
+            let orders = orders.iter().copied();
+            Ok(if buys_base {
+                bid_quote(orders, coin_lot_size, pc_lot_size, fee_tier, amount_in)
+            } else {
+                ask_quote(orders, coin_lot_size, pc_lot_size, fee_tier, amount_in)
+            } as u128)
+        })
     }
 
     fn swap_ix(&self, 
         program: &Program,
         owner: &Pubkey,
         mint_in: &Pubkey, 
-        _mint_out: &Pubkey
-    ) -> Vec<Instruction> {
+        mint_out: &Pubkey,
+        min_amount_out: Option<u64>,
+    ) -> Result<Vec<Instruction>, QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let open_orders = self.open_orders_address()?;
 
-        let oos = self.open_orders.as_ref().unwrap(); 
-        let open_orders = Pubkey::from_str(
-            oos.get(&self.own_address.0.to_string()).unwrap()
-        ).unwrap();
-
-        let (swap_state, _) = Pubkey::find_program_address(
-            &[b"swap_state"], 
-            &program.id()
-        );
+        let swap_state = derive_swap_state_address(&program.id(), owner);
 
         let base_ata = derive_token_address(owner, &self.base_mint);
         let quote_ata = derive_token_address(owner, &self.quote_mint);
@@ -357,12 +316,48 @@ impl PoolOperations for SerumPool {
                 rent: solana_sdk::sysvar::rent::id(),
                 swap_state,
             })
-            .args(tmp_instructions::SerumSwap { side: _side });
+            .args(tmp_instructions::SerumSwap { side: _side, min_amount_out });
 
          
 
-        request
-            .instructions().unwrap()
+        Ok(request.instructions()?)
+    }
+
+    fn route_hop(&self, 
+        owner: &Pubkey,
+        mint_in: &Pubkey, 
+        mint_out: &Pubkey,
+    ) -> Result<(DexKind, Vec<AccountMeta>), QuoteError> {
+        check_direction(&self.get_mints(), mint_in, mint_out)?;
+        let open_orders = self.open_orders_address()?;
+
+        let base_ata = derive_token_address(owner, &self.base_mint);
+        let quote_ata = derive_token_address(owner, &self.quote_mint);
+        
+        let side = if *mint_in == self.quote_mint.0 { Side::Bid }  else { Side::Ask };
+        let payer_acc = if side == Side::Ask { base_ata } else { quote_ata };
+        let _side = if side == Side::Ask { tmp::Side::Ask } else { tmp::Side::Bid };
+
+        // SerumSwap accounts order without the swap state 
+        let accounts = vec![
+            AccountMeta::new(self.own_address.0, false),
+            AccountMeta::new(open_orders, false),
+            AccountMeta::new(self.request_queue.0, false),
+            AccountMeta::new(self.event_queue.0, false),
+            AccountMeta::new(self.bids.0, false),
+            AccountMeta::new(self.asks.0, false),
+            AccountMeta::new(payer_acc, false),
+            AccountMeta::new(self.base_vault.0, false),
+            AccountMeta::new(self.quote_vault.0, false),
+            AccountMeta::new_readonly(self.vault_signer.0, false),
+            AccountMeta::new(base_ata, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(quote_ata, false),
+            AccountMeta::new_readonly(*SERUM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::rent::id(), false),
+        ];
+        Ok((DexKind::Serum { side: _side }, accounts))
     }
 
     fn can_trade(&self, 
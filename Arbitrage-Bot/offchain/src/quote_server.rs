@@ -0,0 +1,323 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Keypair;
+
+use anchor_client::{Client, Cluster, Program};
+
+use solana_sdk::instruction::Instruction;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use client::constants::*;
+use client::pool::{PoolDir, PoolType};
+use client::router::{load_pools, update_pools, PoolRouter, Route};
+use client::utils::derive_swap_state_address;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(short, long)]
+    pub cluster: String,
+    /// address to listen on, the server has no auth -- keep it local
+    #[clap(long, default_value = "127.0.0.1")]
+    pub bind: String,
+    #[clap(long, default_value_t = 8080)]
+    pub port: u16,
+    /// re-fetch the pool state when it is older than this
+    #[clap(long, default_value_t = 5)]
+    pub refresh_secs: u64,
+    #[clap(long, default_value_t = 3)]
+    pub default_max_hops: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HopJson {
+    pool: String,
+    input_mint: String,
+    output_mint: String,
+    in_amount: String, // u64 as string, JSON numbers lose precision
+    out_amount: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteJson {
+    input_mint: String,
+    output_mint: String,
+    in_amount: String,
+    out_amount: String,
+    hops: Vec<HopJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapRequestJson {
+    input_mint: String,
+    output_mint: String,
+    amount: u64,
+    max_hops: Option<usize>,
+    owner: String, // wallet which signs the swap -- needs its tmp swap state (init_program)
+    #[serde(default = "default_slippage_bps")]
+    slippage_bps: u64,
+}
+
+fn default_slippage_bps() -> u64 {
+    50
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountMetaJson {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InstructionJson {
+    program_id: String,
+    accounts: Vec<AccountMetaJson>,
+    data: String, // base64
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapInstructionsJson {
+    quote: QuoteJson,
+    instructions: Vec<InstructionJson>,
+}
+
+fn quote_json(route: &Route) -> QuoteJson {
+    let hops = route
+        .pools
+        .iter()
+        .enumerate()
+        .map(|(i, pool)| HopJson {
+            pool: pool.0.get_name(),
+            input_mint: route.mints[i].to_string(),
+            output_mint: route.mints[i + 1].to_string(),
+            in_amount: route.amounts[i].to_string(),
+            out_amount: route.amounts[i + 1].to_string(),
+        })
+        .collect();
+    QuoteJson {
+        input_mint: route.mints[0].to_string(),
+        output_mint: route.mints[route.mints.len() - 1].to_string(),
+        in_amount: route.amounts[0].to_string(),
+        out_amount: route.amount_out().to_string(),
+        hops,
+    }
+}
+
+fn instruction_json(ix: &Instruction) -> InstructionJson {
+    InstructionJson {
+        program_id: ix.program_id.to_string(),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|meta| AccountMetaJson {
+                pubkey: meta.pubkey.to_string(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: base64::encode(&ix.data),
+    }
+}
+
+// pool graph with the time its state was fetched
+struct RouterState {
+    router: PoolRouter,
+    updated_at: Instant,
+}
+
+struct QuoteServer {
+    connection: RpcClient,
+    cluster: Cluster,
+    pool_dirs: Vec<PoolDir>,
+    program: Program,
+    refresh: Duration,
+    default_max_hops: usize,
+    state: Option<RouterState>,
+}
+
+type HttpResult = Result<String, (u16, String)>;
+
+fn bad_request(msg: &str) -> (u16, String) {
+    (400, msg.to_string())
+}
+
+fn parse_pubkey(s: &str, name: &str) -> Result<Pubkey, (u16, String)> {
+    Pubkey::from_str(s).map_err(|_| bad_request(&format!("invalid {}", name)))
+}
+
+impl QuoteServer {
+    fn router(&mut self) -> Result<&PoolRouter, (u16, String)> {
+        let is_stale = match self.state.as_ref() {
+            Some(state) => state.updated_at.elapsed() > self.refresh,
+            None => true,
+        };
+        if is_stale {
+            // pools are shared through Rc -- rebuild the graph instead of updating in place
+            let pools = update_pools(&self.connection, load_pools(&self.pool_dirs), &self.cluster)
+                .map_err(|err| (503, format!("failed to fetch pools: {}", err)))?;
+            info!("refreshed {} pools", pools.len());
+            self.state = Some(RouterState {
+                router: PoolRouter::new(pools),
+                updated_at: Instant::now(),
+            });
+        }
+        Ok(&self.state.as_ref().unwrap().router)
+    }
+
+    fn best_route(&mut self, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64, max_hops: Option<usize>) -> Result<Route, (u16, String)> {
+        let max_hops = max_hops.unwrap_or(self.default_max_hops);
+        if max_hops == 0 || max_hops > 4 { // max tx size
+            return Err(bad_request("maxHops must be between 1 and 4"));
+        }
+        self.router()?
+            .best_route(input_mint, output_mint, amount as u128, max_hops)
+            .ok_or((404, "no route".to_string()))
+    }
+
+    // GET /quote?inputMint=..&outputMint=..&amount=..&maxHops=..
+    fn quote(&mut self, query: &HashMap<String, String>) -> HttpResult {
+        let get = |name: &str| query.get(name).ok_or(bad_request(&format!("missing {}", name)));
+        let input_mint = parse_pubkey(get("inputMint")?, "inputMint")?;
+        let output_mint = parse_pubkey(get("outputMint")?, "outputMint")?;
+        let amount = get("amount")?.parse::<u64>().map_err(|_| bad_request("invalid amount"))?;
+        let max_hops = match query.get("maxHops") {
+            Some(max_hops) => Some(max_hops.parse::<usize>().map_err(|_| bad_request("invalid maxHops"))?),
+            None => None,
+        };
+
+        let route = self.best_route(&input_mint, &output_mint, amount, max_hops)?;
+        Ok(serde_json::to_string(&quote_json(&route)).unwrap())
+    }
+
+    // the hops run through the tmp program, which keeps the amount between
+    // them in the owner's swap state
+    fn check_swap_state(&self, owner: &Pubkey) -> Result<(), (u16, String)> {
+        let swap_state = derive_swap_state_address(&self.program.id(), owner);
+        let account = self
+            .connection
+            .get_account_with_commitment(&swap_state, self.connection.commitment())
+            .map_err(|err| (503, format!("failed to fetch the swap state: {}", err)))?
+            .value;
+        match account {
+            Some(account) if account.owner == self.program.id() => Ok(()),
+            _ => Err(bad_request(&format!("owner has no swap state {} -- run init_program first", swap_state))),
+        }
+    }
+
+    // POST /swap-instructions {inputMint, outputMint, amount, maxHops?, owner, slippageBps?}
+    fn swap_instructions(&mut self, body: &str) -> HttpResult {
+        let req: SwapRequestJson = serde_json::from_str(body).map_err(|e| bad_request(&e.to_string()))?;
+        let input_mint = parse_pubkey(&req.input_mint, "inputMint")?;
+        let output_mint = parse_pubkey(&req.output_mint, "outputMint")?;
+        let owner = parse_pubkey(&req.owner, "owner")?;
+        self.check_swap_state(&owner)?;
+
+        let route = self.best_route(&input_mint, &output_mint, req.amount, req.max_hops)?;
+        let ixs = route
+            .swap_instructions(&self.program, &owner, req.slippage_bps)
+            .map_err(|err| (500, err.to_string()))?;
+        let resp = SwapInstructionsJson {
+            quote: quote_json(&route),
+            instructions: ixs.iter().map(instruction_json).collect(),
+        };
+        Ok(serde_json::to_string(&resp).unwrap())
+    }
+
+    fn handle(&mut self, request: &mut Request) -> HttpResult {
+        let url = request.url().to_string();
+        let (path, query) = match url.split_once('?') {
+            Some((path, query)) => (path, query),
+            None => (url.as_str(), ""),
+        };
+        let query: HashMap<String, String> = query
+            .split('&')
+            .filter_map(|kv| kv.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        match (request.method(), path) {
+            (Method::Get, "/quote") => self.quote(&query),
+            (Method::Post, "/swap-instructions") => {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body)
+                    .map_err(|_| bad_request("invalid body"))?;
+                self.swap_instructions(&body)
+            }
+            _ => Err((404, "not found".to_string())),
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let cluster = match args.cluster.as_str() {
+        "localnet" => Cluster::Localnet,
+        "mainnet" => Cluster::Mainnet,
+        _ => panic!("invalid cluster type"),
+    };
+
+    env_logger::init();
+
+    let connection = RpcClient::new_with_commitment(cluster.url(), CommitmentConfig::confirmed());
+
+    // ixs are only built, never signed -- any payer works
+    let provider = Client::new_with_options(
+        cluster.clone(),
+        Rc::new(Keypair::new()),
+        CommitmentConfig::confirmed(),
+    );
+    let program = provider.program(*ARB_PROGRAM_ID);
+
+    let pool_dirs = vec![
+        PoolDir { tipe: PoolType::OrcaPoolType, dir_path: "../pools/orca".to_string() },
+        PoolDir { tipe: PoolType::MercurialPoolType, dir_path: "../pools/mercurial".to_string() },
+        PoolDir { tipe: PoolType::SaberPoolType, dir_path: "../pools/saber/".to_string() },
+    ];
+
+    let mut server = QuoteServer {
+        connection,
+        cluster,
+        pool_dirs,
+        program,
+        refresh: Duration::from_secs(args.refresh_secs),
+        default_max_hops: args.default_max_hops,
+        state: None,
+    };
+
+    // pools are Rc -- requests are served one at a time on this thread
+    let http = Server::http((args.bind.as_str(), args.port)).unwrap();
+    info!("quote server listening on {}:{}", args.bind, args.port);
+    let json_header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+
+    for mut request in http.incoming_requests() {
+        let (status, body) = match server.handle(&mut request) {
+            Ok(body) => (200, body),
+            Err((status, msg)) => (status, serde_json::json!({ "error": msg }).to_string()),
+        };
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(json_header.clone());
+        if let Err(e) = request.respond(response) {
+            info!("failed to respond: {}", e);
+        }
+    }
+}
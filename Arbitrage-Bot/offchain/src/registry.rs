@@ -0,0 +1,164 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use solana_program::program_pack::Pack;
+use spl_token::state::Mint;
+
+use std::collections::HashMap;
+
+use log::warn;
+use serde_json::Value;
+
+use crate::pool::{PoolDir, PoolOperations};
+use crate::serialize::token::Token;
+use crate::utils::read_json_dir;
+
+#[derive(Debug, Clone)]
+pub struct TokenInfo {
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+}
+
+/// Symbols + decimals of the mints of the pools and their USD prices
+#[derive(Debug, Clone, Default)]
+pub struct TokenRegistry {
+    pub tokens: HashMap<Pubkey, TokenInfo>,
+    pub usd_prices: HashMap<Pubkey, f64>, // of a whole token
+}
+
+impl TokenRegistry {
+    /// Tokens listed in the `tokens` map of the pool JSONs (serum markets
+    /// have none -- see `update_decimals`)
+    pub fn from_pool_dirs(pool_dirs: &[PoolDir]) -> Self {
+        let mut registry = TokenRegistry::default();
+        for pool_dir in pool_dirs {
+            for pool_path in read_json_dir(&pool_dir.dir_path) {
+                let json_str = std::fs::read_to_string(&pool_path).unwrap();
+                let json: Value = serde_json::from_str(&json_str).unwrap();
+                let tokens = match json.get("tokens").and_then(|tokens| tokens.as_object()) {
+                    Some(tokens) => tokens,
+                    None => continue,
+                };
+                for token in tokens.values() {
+                    let token: Token = serde_json::from_value(token.clone()).unwrap();
+                    registry.insert(*token.mint, &token.tag, &token.name, token.scale as u8);
+                }
+            }
+        }
+        registry
+    }
+
+    pub fn insert(&mut self, mint: Pubkey, symbol: &str, name: &str, decimals: u8) {
+        let info = TokenInfo { symbol: symbol.to_string(), name: name.to_string(), decimals };
+        self.tokens.entry(mint).or_insert(info);
+    }
+
+    /// Decimals of `mints` from their mint accounts -- the JSONs can be
+    /// wrong, mints which aren't listed get their short address as symbol
+    pub fn update_decimals(&mut self, connection: &RpcClient, mints: &[Pubkey]) {
+        // max 100 accounts per get_multiple_accounts
+        for chunk in mints.chunks(99) {
+            let accounts = connection.get_multiple_accounts(chunk).unwrap();
+            for (mint, account) in chunk.iter().zip(accounts) {
+                let decimals = match account.map(|account| Mint::unpack(&account.data)) {
+                    Some(Ok(state)) => state.decimals,
+                    _ => {
+                        warn!("no mint account for {}", mint);
+                        continue;
+                    }
+                };
+                let info = self.tokens.entry(*mint).or_insert_with(|| TokenInfo {
+                    symbol: mint.to_string()[..4].to_string(),
+                    name: mint.to_string(),
+                    decimals,
+                });
+                if info.decimals != decimals {
+                    warn!("{} has {} decimals, not {}", info.symbol, decimals, info.decimals);
+                    info.decimals = decimals;
+                }
+            }
+        }
+    }
+
+    /// Price every token reachable from `usd_mint` through `pools`: a token
+    /// is priced by selling 1/100 of it into its best pool against an
+    /// already priced token (USDC pools first)
+    pub fn update_prices(&mut self, pools: &[&dyn PoolOperations], usd_mint: &Pubkey) {
+        let mut prices = HashMap::new();
+        prices.insert(*usd_mint, 1.0);
+
+        loop {
+            let mut new_prices: HashMap<Pubkey, f64> = HashMap::new();
+            for pool in pools {
+                let mints = pool.get_mints();
+                for (mint_in, mint_out) in [(mints[0], mints[1]), (mints[1], mints[0])] {
+                    if prices.contains_key(&mint_in) {
+                        continue;
+                    }
+                    let (price_out, info_in, info_out) =
+                        match (prices.get(&mint_out), self.tokens.get(&mint_in), self.tokens.get(&mint_out)) {
+                            (Some(price_out), Some(info_in), Some(info_out)) => (price_out, info_in, info_out),
+                            _ => continue,
+                        };
+                    let amount_in = (10_u128.pow(info_in.decimals as u32) / 100).max(1);
+                    let amount_out = match pool.get_quote_with_amounts_scaled(amount_in, &mint_in, &mint_out) {
+                        Ok(amount_out) if amount_out > 0 => amount_out,
+                        _ => continue,
+                    };
+                    let price = ui_amount(amount_out, info_out.decimals) * price_out
+                        / ui_amount(amount_in, info_in.decimals);
+                    let best = new_prices.entry(mint_in).or_insert(price);
+                    *best = best.max(price);
+                }
+            }
+            // one hop further from USD each pass
+            if new_prices.is_empty() {
+                break;
+            }
+            prices.extend(new_prices);
+        }
+        self.usd_prices = prices;
+    }
+
+    pub fn symbol(&self, mint: &Pubkey) -> String {
+        match self.tokens.get(mint) {
+            Some(info) => info.symbol.clone(),
+            None => mint.to_string()[..4].to_string(),
+        }
+    }
+
+    /// `amount` (scaled) in whole tokens, None = unknown decimals
+    pub fn ui_amount(&self, mint: &Pubkey, amount: u128) -> Option<f64> {
+        self.tokens.get(mint).map(|info| ui_amount(amount, info.decimals))
+    }
+
+    pub fn usd_value(&self, mint: &Pubkey, amount: u128) -> Option<f64> {
+        Some(self.ui_amount(mint, amount)? * self.usd_prices.get(mint)?)
+    }
+
+    /// e.g. `12.5 USDC`, the raw amount if the decimals are unknown
+    pub fn fmt_amount(&self, mint: &Pubkey, amount: u128) -> String {
+        match self.ui_amount(mint, amount) {
+            Some(ui_amount) => format!("{} {}", ui_amount, self.symbol(mint)),
+            None => format!("{} {}", amount, self.symbol(mint)),
+        }
+    }
+
+    pub fn fmt_usd(&self, mint: &Pubkey, amount: u128) -> String {
+        match self.usd_value(mint, amount) {
+            Some(usd) => format!("${:.2}", usd),
+            None => "$?".to_string(),
+        }
+    }
+
+    /// e.g. `USDC -> SOL -> USDC`
+    pub fn fmt_path(&self, mints: &[Pubkey]) -> String {
+        let symbols: Vec<String> = mints.iter().map(|mint| self.symbol(mint)).collect();
+        symbols.join(" -> ")
+    }
+}
+
+pub fn ui_amount(amount: u128, decimals: u8) -> f64 {
+    amount as f64 / 10_f64.powi(decimals as i32)
+}
@@ -0,0 +1,266 @@
+use anchor_client::solana_client::client_error::ClientError;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::{Cluster, Program};
+
+use solana_sdk::instruction::Instruction;
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use log::warn;
+
+use crate::error::QuoteError;
+use crate::pool::{pool_factory, PoolDir, PoolOperations};
+use crate::utils::{
+    apply_slippage, derive_swap_state_address, derive_token_address, read_json_dir, PoolEdge,
+    PoolGraph, PoolIndex, PoolQuote,
+};
+
+use tmp::accounts as tmp_accounts;
+use tmp::instruction as tmp_ix;
+
+/// Parse every 2-mint pool of the pool dirs
+pub fn load_pools(pool_dirs: &[PoolDir]) -> Vec<Box<dyn PoolOperations>> {
+    let mut pools = vec![];
+    for pool_dir in pool_dirs {
+        for pool_path in read_json_dir(&pool_dir.dir_path) {
+            let json_str = std::fs::read_to_string(&pool_path).unwrap();
+            let pool = pool_factory(&pool_dir.tipe, &json_str);
+            if pool.get_mints().len() != 2 { // only support 2 mint pools
+                warn!("skipping pool with mints != 2: {:?}", pool_path);
+                continue;
+            }
+            pools.push(pool);
+        }
+    }
+    pools
+}
+
+/// Fetch the accounts each pool quotes from and set them, pools with a
+/// missing or invalid account are dropped, a failed RPC call fails the update
+pub fn update_pools(
+    connection: &RpcClient,
+    pools: Vec<Box<dyn PoolOperations>>,
+    cluster: &Cluster,
+) -> Result<Vec<Box<dyn PoolOperations>>, ClientError> {
+    let update_pks: Vec<Vec<Pubkey>> = pools.iter().map(|pool| pool.get_update_accounts()).collect();
+
+    let mut update_accounts = vec![];
+    // max 100 accounts per get_multiple_accounts
+    for chunk in update_pks.concat().chunks(99) {
+        update_accounts.extend(connection.get_multiple_accounts(chunk)?);
+    }
+
+    let mut updated = vec![];
+    let mut account_ptr = 0;
+    for (mut pool, pks) in pools.into_iter().zip(update_pks.iter()) {
+        let accounts = update_accounts[account_ptr..account_ptr + pks.len()].to_vec();
+        account_ptr += pks.len();
+        if let Err(err) = pool.set_update_accounts(accounts, cluster.clone()) {
+            warn!("skipping {} pool: {}", pool.get_name(), err);
+            continue;
+        }
+        updated.push(pool);
+    }
+    Ok(updated)
+}
+
+/// A route with the amount after each hop (`amounts[0]` = amount in)
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub mints: Vec<Pubkey>,
+    pub pools: Vec<PoolQuote>,
+    pub amounts: Vec<u128>,
+}
+
+impl Route {
+    pub fn amount_out(&self) -> u128 {
+        self.amounts[self.amounts.len() - 1]
+    }
+
+    /// Swap ixs of `owner` along the route, each hop bounded `slippage_bps`
+    /// under its quote
+    ///
+    /// The swap ixs go through the tmp program, which passes the amount
+    /// between hops in `owner`'s swap state -- `owner` needs one
+    /// (`init_program`). The route doesn't come back to its input mint, so
+    /// the hops are bracketed by start/end_dust_sweep (open the swap state
+    /// without a profit check) instead of start_swap/profit_or_revert
+    pub fn swap_instructions(
+        &self,
+        program: &Program,
+        owner: &Pubkey,
+        slippage_bps: u64,
+    ) -> Result<Vec<Instruction>, QuoteError> {
+        let mut ixs = vec![];
+        let swap_state_pda = derive_swap_state_address(&program.id(), owner);
+        let src_ata = derive_token_address(owner, &self.mints[0]);
+
+        let ix = program
+            .request()
+            .accounts(tmp_accounts::StartDustSweep {
+                src: src_ata,
+                swap_state: swap_state_pda,
+                authority: *owner,
+            })
+            .args(tmp_ix::StartDustSweep {
+                swap_input: self.amounts[0] as u64,
+            })
+            .instructions()?;
+        ixs.push(ix);
+
+        for (i, pool) in self.pools.iter().enumerate() {
+            let min_amount_out = Some(apply_slippage(self.amounts[i + 1], slippage_bps));
+            ixs.push(pool.0.swap_ix(program, owner, &self.mints[i], &self.mints[i + 1], min_amount_out)?);
+        }
+
+        let ix = program
+            .request()
+            .accounts(tmp_accounts::EndDustSweep {
+                swap_state: swap_state_pda,
+                authority: *owner,
+            })
+            .args(tmp_ix::EndDustSweep {})
+            .instructions()?;
+        ixs.push(ix);
+
+        Ok(ixs.concat())
+    }
+}
+
+/// The pool graph as a router between any two mints
+pub struct PoolRouter {
+    pub token_mints: Vec<Pubkey>,
+    pub mint2idx: HashMap<Pubkey, usize>,
+    pub graph_edges: Vec<HashSet<usize>>, // used for quick searching over the graph
+    pub graph: PoolGraph,
+}
+
+impl PoolRouter {
+    pub fn new(pools: Vec<Box<dyn PoolOperations>>) -> Self {
+        let mut router = PoolRouter {
+            token_mints: vec![],
+            mint2idx: HashMap::new(),
+            graph_edges: vec![],
+            graph: PoolGraph::new(),
+        };
+
+        for pool in pools {
+            let mints = pool.get_mints();
+            let idx0 = router.mint_idx(mints[0]);
+            let idx1 = router.mint_idx(mints[1]);
+            router.graph_edges[idx0].insert(idx1);
+            router.graph_edges[idx1].insert(idx0);
+
+            let quote = PoolQuote::new(Rc::new(pool));
+            router.add_edge(idx0, idx1, quote.clone());
+            router.add_edge(idx1, idx0, quote);
+        }
+        router
+    }
+
+    fn mint_idx(&mut self, mint: Pubkey) -> usize {
+        if let Some(idx) = self.mint2idx.get(&mint) {
+            return *idx;
+        }
+        let idx = self.token_mints.len();
+        self.mint2idx.insert(mint, idx);
+        self.token_mints.push(mint);
+        self.graph_edges.push(HashSet::new());
+        idx
+    }
+
+    fn add_edge(&mut self, idx0: usize, idx1: usize, quote: PoolQuote) {
+        let edges = self
+            .graph
+            .0
+            .entry(PoolIndex(idx0))
+            .or_insert_with(|| PoolEdge(HashMap::new()));
+        edges.0.entry(PoolIndex(idx1)).or_insert_with(|| vec![]).push(quote);
+    }
+
+    /// Non-cyclic route of at most `max_hops` with the largest output, None
+    /// if the mints are unknown or not connected
+    pub fn best_route(
+        &self,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+        amount_in: u128,
+        max_hops: usize,
+    ) -> Option<Route> {
+        let src_idx = *self.mint2idx.get(mint_in)?;
+        let dst_idx = *self.mint2idx.get(mint_out)?;
+        if src_idx == dst_idx || amount_in == 0 {
+            return None;
+        }
+
+        let mut best = None;
+        self.search(dst_idx, max_hops, vec![src_idx], vec![], vec![amount_in], &mut best);
+        best.map(|(path, pools, amounts)| Route {
+            mints: path.iter().map(|idx: &usize| self.token_mints[*idx]).collect(),
+            pools,
+            amounts,
+        })
+    }
+
+    fn search(
+        &self,
+        dst_idx: usize,
+        max_hops: usize,
+        path: Vec<usize>,
+        pool_path: Vec<PoolQuote>,
+        amounts: Vec<u128>,
+        best: &mut Option<(Vec<usize>, Vec<PoolQuote>, Vec<u128>)>,
+    ) {
+        if pool_path.len() == max_hops {
+            return;
+        }
+        let src_curr = path[path.len() - 1]; // last mint
+        let src_mint = self.token_mints[src_curr];
+        let curr_balance = amounts[amounts.len() - 1];
+
+        for dst_mint_idx in &self.graph_edges[src_curr] {
+            let dst_mint_idx = *dst_mint_idx;
+            if path.contains(&dst_mint_idx) {
+                continue;
+            }
+            let dst_mint = self.token_mints[dst_mint_idx];
+            let pools = self
+                .graph
+                .0
+                .get(&PoolIndex(src_curr))
+                .unwrap()
+                .0
+                .get(&PoolIndex(dst_mint_idx))
+                .unwrap();
+
+            for pool in pools {
+                // a pool which can't quote is skipped like an empty one
+                let new_balance = match pool.0.get_quote_with_amounts_scaled(curr_balance, &src_mint, &dst_mint) {
+                    Ok(new_balance) if new_balance > 0 => new_balance,
+                    _ => continue,
+                };
+
+                let mut new_path = path.clone();
+                new_path.push(dst_mint_idx);
+                let mut new_pool_path = pool_path.clone();
+                new_pool_path.push(pool.clone()); // clone the pointer
+                let mut new_amounts = amounts.clone();
+                new_amounts.push(new_balance);
+
+                if dst_mint_idx == dst_idx {
+                    let is_better = match best.as_ref() {
+                        Some((_, _, best_amounts)) => new_balance > best_amounts[best_amounts.len() - 1],
+                        None => true,
+                    };
+                    if is_better {
+                        *best = Some((new_path, new_pool_path, new_amounts));
+                    }
+                } else {
+                    self.search(dst_idx, max_hops, new_path, new_pool_path, new_amounts, best);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::error::QuoteError;
+use crate::health::PoolId;
+use crate::utils::PoolQuote;
+
+// routes considered for a batch, most profitable first
+const MAX_BATCH_CANDIDATES: usize = 32;
+
+/// A profitable route found by the search, not sent yet
+#[derive(Debug, Clone)]
+pub struct RouteCandidate {
+    pub mint_idxs: Vec<usize>, // start mint .. start mint
+    pub pools: Vec<PoolQuote>,
+    pub amount_in: u128,
+    pub amount_out: u128,
+}
+
+impl RouteCandidate {
+    pub fn profit(&self) -> u128 {
+        self.amount_out.saturating_sub(self.amount_in)
+    }
+}
+
+/// Swaps of the routes sent so far, so the next ones are quoted as if they
+/// had executed -- (pool, mint in) -> (total in, total out)
+///
+/// A hop in the direction of earlier swaps continues their walk down the
+/// curve / book, which is exact for path-independent curves and orderbooks
+/// and only under the real output when fees stay in the pool. A hop against
+/// them is quoted on the state before them, also an underestimate (they
+/// made what it buys more plentiful)
+#[derive(Debug, Clone, Default)]
+pub struct ExecutedSwaps(HashMap<(PoolId, Pubkey), (u128, u128)>);
+
+impl ExecutedSwaps {
+    pub fn quote(
+        &self,
+        pool: &PoolQuote,
+        amount_in: u128,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> Result<u128, QuoteError> {
+        match self.0.get(&(PoolId::of(&**pool.0), *mint_in)) {
+            Some((prev_in, prev_out)) => {
+                let amount_in = prev_in.checked_add(amount_in).ok_or(QuoteError::MathOverflow)?;
+                let amount_out = pool.0.get_quote_with_amounts_scaled(amount_in, mint_in, mint_out)?;
+                Ok(amount_out.saturating_sub(*prev_out))
+            }
+            None => pool.0.get_quote_with_amounts_scaled(amount_in, mint_in, mint_out),
+        }
+    }
+
+    /// Add a sent swap -- one which can't be quoted anymore (the pool ran
+    /// dry under the earlier ones) is taken to have bought nothing
+    pub fn record(&mut self, pool: &PoolQuote, amount_in: u128, mint_in: &Pubkey, mint_out: &Pubkey) {
+        let amount_out = self.quote(pool, amount_in, mint_in, mint_out).unwrap_or(0);
+        let (total_in, total_out) = self.0.entry((PoolId::of(&**pool.0), *mint_in)).or_default();
+        *total_in += amount_in;
+        *total_out += amount_out;
+    }
+
+    /// Whether any sent route swapped through `pool`
+    pub fn touches(&self, pool: &PoolQuote) -> bool {
+        let id = PoolId::of(&**pool.0);
+        self.0.keys().any(|(pool, _)| *pool == id)
+    }
+
+    pub fn quote_route(&self, mints: &[Pubkey], pools: &[PoolQuote], amount_in: u128) -> Result<u128, QuoteError> {
+        let mut amount = amount_in;
+        for (i, pool) in pools.iter().enumerate() {
+            amount = self.quote(pool, amount, &mints[i], &mints[i + 1])?;
+        }
+        Ok(amount)
+    }
+}
+
+/// The most profitable routes which can go out at once -- no two write the
+/// same pool account and each gets its own wallet with the balance for it
+/// (every route of a wallet writes its start ATA + swap state)
+///
+/// `write_accounts` = pool accounts each route writes, `balances` = start
+/// mint balance of each wallet -- returns (route, wallet) pairs
+pub fn pick_batch(
+    routes: &[RouteCandidate],
+    write_accounts: &[HashSet<Pubkey>],
+    balances: &[u128],
+) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..routes.len()).collect();
+    order.sort_by(|a, b| routes[*b].profit().cmp(&routes[*a].profit()));
+    order.truncate(MAX_BATCH_CANDIDATES);
+
+    let mut best = (0, vec![]);
+    search_batch(routes, write_accounts, balances, &order, 0, &mut vec![], &mut HashSet::new(), 0, &mut best);
+
+    let amounts: Vec<u128> = best.1.iter().map(|idx| routes[*idx].amount_in).collect();
+    let wallets = assign_wallets(&amounts, balances).unwrap_or_default();
+    best.1.into_iter().zip(wallets).collect()
+}
+
+// branch + bound over `order` -- routes are taken or skipped in profit order
+#[allow(clippy::too_many_arguments)]
+fn search_batch(
+    routes: &[RouteCandidate],
+    write_accounts: &[HashSet<Pubkey>],
+    balances: &[u128],
+    order: &[usize],
+    next: usize,
+    chosen: &mut Vec<usize>,
+    written: &mut HashSet<Pubkey>,
+    profit: u128,
+    best: &mut (u128, Vec<usize>),
+) {
+    if profit > best.0 {
+        *best = (profit, chosen.clone());
+    }
+    let slots = balances.len().saturating_sub(chosen.len());
+    // the next routes are the most profitable left
+    let bound: u128 = order[next..].iter().take(slots).map(|idx| routes[*idx].profit()).sum();
+    if slots == 0 || profit + bound <= best.0 {
+        return;
+    }
+
+    for (i, route_idx) in order.iter().enumerate().skip(next) {
+        let accounts = &write_accounts[*route_idx];
+        if !accounts.is_disjoint(written) {
+            continue;
+        }
+        chosen.push(*route_idx);
+        let amounts: Vec<u128> = chosen.iter().map(|idx| routes[*idx].amount_in).collect();
+        if assign_wallets(&amounts, balances).is_some() {
+            written.extend(accounts.iter().copied());
+            search_batch(
+                routes, write_accounts, balances, order, i + 1, chosen, written,
+                profit + routes[*route_idx].profit(), best,
+            );
+            for account in accounts {
+                written.remove(account);
+            }
+        }
+        chosen.pop();
+    }
+}
+
+/// Wallet of each amount, None if they don't all fit -- largest amount to
+/// the largest balance
+pub fn assign_wallets(amounts: &[u128], balances: &[u128]) -> Option<Vec<usize>> {
+    if amounts.len() > balances.len() {
+        return None;
+    }
+    let mut by_amount: Vec<usize> = (0..amounts.len()).collect();
+    by_amount.sort_by(|a, b| amounts[*b].cmp(&amounts[*a]));
+    let mut by_balance: Vec<usize> = (0..balances.len()).collect();
+    by_balance.sort_by(|a, b| balances[*b].cmp(&balances[*a]));
+
+    let mut wallets = vec![0; amounts.len()];
+    for (amount_idx, wallet_idx) in by_amount.into_iter().zip(by_balance) {
+        if amounts[amount_idx] > balances[wallet_idx] {
+            return None;
+        }
+        wallets[amount_idx] = wallet_idx;
+    }
+    Some(wallets)
+}
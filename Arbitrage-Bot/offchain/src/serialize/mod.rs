@@ -1,2 +1,3 @@
 pub mod pool; 
-pub mod token;
\ No newline at end of file
+pub mod token;
+pub mod open_orders;
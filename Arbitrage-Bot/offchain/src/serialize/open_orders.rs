@@ -0,0 +1,86 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use arrayref::{array_ref, array_refs};
+
+// Serum accounts start with 5 bytes of "serum" padding and end with 7 bytes of "padding"
+pub const OPEN_ORDERS_SPAN: usize = 3228;
+pub const OPEN_ORDERS_OWNER_OFFSET: usize = 45;
+const MARKET_SPAN: usize = 388;
+
+/// Open orders account of a serum market
+#[derive(Debug, Clone)]
+pub struct OpenOrders {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub native_coin_free: u64,
+    pub native_coin_total: u64,
+    pub native_pc_free: u64,
+    pub native_pc_total: u64,
+    pub free_slot_bits: u128,
+}
+
+impl OpenOrders {
+    /// Number of orders resting on the book
+    pub fn order_count(&self) -> u32 {
+        128 - self.free_slot_bits.count_ones()
+    }
+
+    /// Funds which a settle moves back to the owner's wallets
+    pub fn has_free_funds(&self) -> bool {
+        self.native_coin_free > 0 || self.native_pc_free > 0
+    }
+
+    /// The DEX only closes accounts without orders or funds
+    pub fn can_close(&self) -> bool {
+        self.order_count() == 0 && self.native_coin_total == 0 && self.native_pc_total == 0
+    }
+}
+
+pub fn unpack_open_orders(data: &[u8]) -> OpenOrders {
+    let src = array_ref![data, 0, OPEN_ORDERS_SPAN];
+    let (
+        _head, _account_flags, market, owner, 
+        native_coin_free, native_coin_total, native_pc_free, native_pc_total, 
+        free_slot_bits, _rest,
+    ) = array_refs![src, 5, 8, 32, 32, 8, 8, 8, 8, 16, 3103];
+
+    OpenOrders {
+        market: Pubkey::new_from_array(*market),
+        owner: Pubkey::new_from_array(*owner),
+        native_coin_free: u64::from_le_bytes(*native_coin_free),
+        native_coin_total: u64::from_le_bytes(*native_coin_total),
+        native_pc_free: u64::from_le_bytes(*native_pc_free),
+        native_pc_total: u64::from_le_bytes(*native_pc_total),
+        free_slot_bits: u128::from_le_bytes(*free_slot_bits),
+    }
+}
+
+/// Market accounts which a settle needs
+#[derive(Debug, Clone)]
+pub struct MarketVaults {
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub vault_signer: Pubkey,
+}
+
+pub fn unpack_market_vaults(market: &Pubkey, data: &[u8], dex_program: &Pubkey) -> MarketVaults {
+    let src = array_ref![data, 0, MARKET_SPAN];
+    let (
+        _head, _account_flags, _own_address, vault_signer_nonce, 
+        coin_mint, pc_mint, coin_vault, _coin_deposits_total, _coin_fees_accrued, pc_vault, _rest,
+    ) = array_refs![src, 5, 8, 32, 8, 32, 32, 32, 8, 8, 32, 191];
+
+    let vault_signer = Pubkey::create_program_address(
+        &[market.as_ref(), vault_signer_nonce], 
+        dex_program,
+    ).unwrap();
+
+    MarketVaults {
+        coin_mint: Pubkey::new_from_array(*coin_mint),
+        pc_mint: Pubkey::new_from_array(*pc_mint),
+        coin_vault: Pubkey::new_from_array(*coin_vault),
+        pc_vault: Pubkey::new_from_array(*pc_vault),
+        vault_signer,
+    }
+}
@@ -0,0 +1,247 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{Keypair, Signer};
+use anchor_client::solana_sdk::signature::read_keypair_file;
+
+use anchor_client::Cluster;
+
+use solana_sdk::instruction::Instruction;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+use std::collections::HashSet;
+
+use clap::{Parser, Subcommand};
+
+use log::warn;
+
+use client::constants::*;
+use client::pool::{pool_factory, PoolDir, PoolType};
+use client::serialize::token::unpack_token_account;
+use client::utils::{
+    create_ata_idempotent_ix, derive_token_address, get_owned_program_accounts, read_json_dir,
+};
+
+const TOKEN_ACCOUNT_SPAN: usize = 165;
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+
+// ixs per tx -- stays under the tx size limit
+const IXS_PER_TX: usize = 10;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(short, long)]
+    pub cluster: String,
+    /// operator wallet which owns the token accounts
+    #[clap(short, long)]
+    pub owner: String,
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// create the missing ATAs of every mint in the pool set
+    Create,
+    /// wrap or unwrap SOL so the wSOL ATA holds `target` lamports
+    Wrap {
+        #[clap(long)]
+        target: u64,
+    },
+    /// close the zero balance ATAs of mints which are no longer traded
+    Close,
+    /// print the balance and rent of the ATA of each mint
+    Report,
+}
+
+// every mint traded by a pool of the pool set
+fn pool_set_mints() -> Vec<Pubkey> {
+    let pool_dirs = vec![
+        PoolDir { tipe: PoolType::OrcaPoolType, dir_path: "../pools/orca".to_string() },
+        PoolDir { tipe: PoolType::MercurialPoolType, dir_path: "../pools/mercurial".to_string() },
+        PoolDir { tipe: PoolType::SaberPoolType, dir_path: "../pools/saber/".to_string() },
+        PoolDir { tipe: PoolType::AldrinPoolType, dir_path: "../pools/aldrin/".to_string() },
+        PoolDir { tipe: PoolType::SerumPoolType, dir_path: "../pools/serum/".to_string() },
+    ];
+
+    let mut token_mints = vec![];
+    for pool_dir in pool_dirs {
+        for pool_path in read_json_dir(&pool_dir.dir_path) {
+            let json_str = std::fs::read_to_string(&pool_path).unwrap();
+            let pool = pool_factory(&pool_dir.tipe, &json_str);
+            let pool_mints = pool.get_mints();
+            if pool_mints.len() != 2 { // only support 2 mint pools
+                warn!("skipping pool with mints != 2: {:?}", pool_path);
+                continue
+            }
+            for mint in pool_mints {
+                if !token_mints.contains(&mint) {
+                    token_mints.push(mint);
+                }
+            }
+        }
+    }
+    token_mints
+}
+
+// (balance, lamports) of the ATA of each mint -- None = no ATA
+fn ata_balances(connection: &RpcClient, owner: &Pubkey, mints: &[Pubkey]) -> Vec<Option<(u64, u64)>> {
+    let atas: Vec<Pubkey> = mints.iter().map(|mint| derive_token_address(owner, mint)).collect();
+    let mut balances = vec![];
+    // max 100 accounts per get_multiple_accounts
+    for chunk in atas.chunks(99) {
+        let accounts = connection.get_multiple_accounts(chunk).unwrap();
+        for account in accounts {
+            balances.push(account.map(|account| {
+                (unpack_token_account(&account.data).amount, account.lamports)
+            }));
+        }
+    }
+    balances
+}
+
+// send the ixs in chunks, a failed chunk doesn't stop the others
+fn send_chunks(connection: &RpcClient, owner: &Keypair, ixs: &[Instruction]) -> usize {
+    let mut failed = 0;
+    for chunk in ixs.chunks(IXS_PER_TX) {
+        let recent_hash = connection.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            chunk,
+            Some(&owner.pubkey()),
+            &[owner],
+            recent_hash,
+        );
+        match connection.send_and_confirm_transaction(&tx) {
+            Ok(signature) => println!("  {} ixs: {}", chunk.len(), signature),
+            Err(e) => {
+                println!("  {} ixs failed: {:#?}", chunk.len(), e);
+                failed += chunk.len();
+            }
+        }
+    }
+    failed
+}
+
+fn main() {
+    let args = Args::parse();
+    let cluster = match args.cluster.as_str() {
+        "localnet" => Cluster::Localnet,
+        "mainnet" => Cluster::Mainnet,
+        _ => panic!("invalid cluster type"),
+    };
+
+    env_logger::init();
+
+    // ** setup RPC connection
+    let connection = RpcClient::new_with_commitment(
+        cluster.url(),
+        CommitmentConfig::confirmed()
+    );
+    let owner = read_keypair_file(&args.owner).unwrap();
+    let owner_pk = owner.pubkey();
+
+    let token_mints = pool_set_mints();
+
+    match args.command {
+        Command::Create => {
+            let balances = ata_balances(&connection, &owner_pk, &token_mints);
+            let create_ixs: Vec<Instruction> = token_mints
+                .iter()
+                .zip(balances.iter())
+                .filter(|(_, balance)| balance.is_none())
+                .map(|(mint, _)| create_ata_idempotent_ix(&owner_pk, &owner_pk, mint))
+                .collect();
+
+            println!("creating {} / {} token accounts...", create_ixs.len(), token_mints.len());
+            let failed = send_chunks(&connection, &owner, &create_ixs);
+            // idempotent -- re-run to retry the failed ones
+            println!("created {}, failed {}", create_ixs.len() - failed, failed);
+        }
+        Command::Wrap { target } => {
+            let wsol_ata = derive_token_address(&owner_pk, &NATIVE_MINT);
+            let balance = ata_balances(&connection, &owner_pk, &[*NATIVE_MINT])[0]
+                .map(|(amount, _)| amount)
+                .unwrap_or(0);
+
+            let mut ixs = vec![];
+            if balance > target {
+                // closing unwraps everything, then wrap the target again
+                ixs.push(spl_token::instruction::close_account(
+                    &TOKEN_PROGRAM_ID, &wsol_ata, &owner_pk, &owner_pk, &[],
+                ).unwrap());
+            }
+            let wrap_amount = if balance > target { target } else { target - balance };
+            if wrap_amount > 0 {
+                ixs.push(create_ata_idempotent_ix(&owner_pk, &owner_pk, &NATIVE_MINT));
+                ixs.push(system_instruction::transfer(&owner_pk, &wsol_ata, wrap_amount));
+                ixs.push(spl_token::instruction::sync_native(&TOKEN_PROGRAM_ID, &wsol_ata).unwrap());
+            }
+
+            if ixs.is_empty() {
+                println!("wSOL balance already at {}", target);
+                return;
+            }
+            println!("wSOL balance {} -> {}", balance, target);
+            send_chunks(&connection, &owner, &ixs);
+        }
+        Command::Close => {
+            let traded: HashSet<Pubkey> = token_mints.iter().cloned().collect();
+            let token_accounts = get_owned_program_accounts(
+                &connection,
+                &TOKEN_PROGRAM_ID,
+                TOKEN_ACCOUNT_SPAN,
+                TOKEN_ACCOUNT_OWNER_OFFSET,
+                &owner_pk,
+            );
+
+            let mut reclaimed = 0;
+            let mut close_ixs = vec![];
+            for (address, account) in token_accounts.iter() {
+                let token_account = unpack_token_account(&account.data);
+                let mint = token_account.mint;
+                // only our empty ATAs -- other token accounts (e.g. treasuries) are kept
+                if token_account.amount > 0
+                    || traded.contains(&mint)
+                    || mint == *NATIVE_MINT // see `wrap`
+                    || *address != derive_token_address(&owner_pk, &mint)
+                {
+                    continue;
+                }
+                println!("closing ATA {} of {}", address, mint);
+                close_ixs.push(spl_token::instruction::close_account(
+                    &TOKEN_PROGRAM_ID, address, &owner_pk, &owner_pk, &[],
+                ).unwrap());
+                reclaimed += account.lamports;
+            }
+
+            let failed = send_chunks(&connection, &owner, &close_ixs);
+            println!("closed {}, failed {} (~{} lamports reclaimed)", close_ixs.len() - failed, failed, reclaimed);
+        }
+        Command::Report => {
+            let balances = ata_balances(&connection, &owner_pk, &token_mints);
+            let mut total_rent = 0;
+            let mut missing = 0;
+            for (mint, balance) in token_mints.iter().zip(balances.iter()) {
+                match balance {
+                    Some((amount, lamports)) => {
+                        println!("{} balance: {} rent: {}", mint, amount, lamports);
+                        total_rent += lamports;
+                    }
+                    None => {
+                        println!("{} no ATA", mint);
+                        missing += 1;
+                    }
+                }
+            }
+            let sol_balance = connection.get_balance(&owner_pk).unwrap();
+            println!(
+                "{} mints, {} without ATA, rent in ATAs: {} lamports, SOL balance: {} lamports",
+                token_mints.len(), missing, total_rent, sol_balance,
+            );
+        }
+    }
+}
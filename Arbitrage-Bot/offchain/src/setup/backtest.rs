@@ -0,0 +1,143 @@
+use anchor_client::Cluster;
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use clap::Parser;
+
+use log::{info, warn};
+
+use client::backtest::{Backtester, BacktestReport, Opportunity};
+use client::pool::{PoolDir, PoolType};
+use client::router::load_pools;
+use client::serialize::token::unpack_token_account;
+use client::snapshot::Snapshot;
+use client::utils::{derive_token_address, read_json_dir, str2pubkey};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(short, long)]
+    pub cluster: String,
+    /// snapshots of the snapshot bin (<slot>.json)
+    #[clap(long, default_value = "snapshots")]
+    pub dir: String,
+    /// an arb found on a snapshot executes on the snapshot this many later
+    #[clap(long, default_value_t = 1)]
+    pub latency: usize,
+    #[clap(long, default_value_t = 3)]
+    pub max_hops: usize,
+    /// scaled input of the search -- defaults to the start ATA balance of each snapshot
+    #[clap(long)]
+    pub amount: Option<u128>,
+    /// dexes of the pool set (comma separated)
+    #[clap(long, default_value = "orca,mercurial,saber")]
+    pub dexes: String,
+    /// rows of the pool and route tables
+    #[clap(long, default_value_t = 10)]
+    pub top: usize,
+}
+
+// the snapshot bin names files <slot>.json
+fn snapshot_slot(path: &str) -> u64 {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.parse().ok())
+        .unwrap_or_else(|| panic!("not a <slot>.json snapshot: {}", path))
+}
+
+fn main() {
+    let args = Args::parse();
+    let cluster = match args.cluster.as_str() {
+        "localnet" => Cluster::Localnet,
+        "mainnet" => Cluster::Mainnet,
+        _ => panic!("invalid cluster type"),
+    };
+
+    env_logger::init();
+
+    let mut pool_dirs = vec![];
+    for dex in args.dexes.split(',') {
+        let pool_dir = match dex {
+            "orca" => PoolDir { tipe: PoolType::OrcaPoolType, dir_path: "../pools/orca".to_string() },
+            "mercurial" => PoolDir { tipe: PoolType::MercurialPoolType, dir_path: "../pools/mercurial".to_string() },
+            "saber" => PoolDir { tipe: PoolType::SaberPoolType, dir_path: "../pools/saber/".to_string() },
+            _ => panic!("dex {} isn't recorded by the snapshot bin", dex),
+        };
+        pool_dirs.push(pool_dir);
+    }
+    let mut backtester = Backtester::new(load_pools(&pool_dirs), args.max_hops);
+    info!("backtesting {} pools of {} mints", backtester.pools.len(), backtester.token_mints.len());
+
+    let mut paths = read_json_dir(&args.dir);
+    paths.sort_by_key(|path| snapshot_slot(path));
+
+    let mut report = BacktestReport {
+        latency: args.latency,
+        top: args.top,
+        ..BacktestReport::default()
+    };
+    let min_swap_amount = 10_u128.pow(6_u32); // scaled! -- 1 USDC
+    // (snapshot idx it executes at, arb)
+    let mut pending: Vec<(usize, Opportunity)> = vec![];
+
+    for (snapshot_idx, path) in paths.iter().enumerate() {
+        let snapshot = Snapshot::load(path);
+        backtester.set_snapshot(&snapshot, &cluster);
+        report.snapshots += 1;
+
+        // arbs of earlier snapshots which execute on this one
+        let (executing, later): (Vec<_>, Vec<_>) = pending
+            .into_iter()
+            .partition(|(execute_idx, _)| *execute_idx == snapshot_idx);
+        pending = later;
+        for (_, opportunity) in executing {
+            let amount_out = backtester.quote_route(&opportunity.mints, &opportunity.pools, opportunity.amount_in);
+            report.record(&backtester, &opportunity, Some(amount_out));
+        }
+
+        let start_mint = str2pubkey(&snapshot.start_mint);
+        let start_mint_idx = *backtester.mint2idx.get(&start_mint).unwrap();
+        let start_amount = match args.amount {
+            Some(amount) => amount,
+            None => {
+                let owner_start_addr = derive_token_address(&str2pubkey(&snapshot.owner), &start_mint);
+                match &snapshot.get_multiple_accounts(&[owner_start_addr])[0] {
+                    Some(account) => unpack_token_account(&account.data).amount as u128,
+                    None => {
+                        warn!("no start ATA in {}", path);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        // the sizes main searches with -- an arb only counts at its largest size
+        let mut found = HashSet::new();
+        let mut swap_start_amount = start_amount;
+        for _ in 0..4 {
+            if swap_start_amount < min_swap_amount {
+                break;
+            }
+            for opportunity in backtester.find_arbitrages(snapshot.slot, start_mint_idx, swap_start_amount) {
+                if !found.insert((opportunity.mints.clone(), opportunity.pools.clone())) {
+                    continue;
+                }
+                if args.latency == 0 {
+                    let amount_out = opportunity.amount_out;
+                    report.record(&backtester, &opportunity, Some(amount_out));
+                } else {
+                    pending.push((snapshot_idx + args.latency, opportunity));
+                }
+            }
+            swap_start_amount /= 2;
+        }
+        info!("slot {}: {} arbs", snapshot.slot, found.len());
+    }
+
+    for (_, opportunity) in pending {
+        report.record(&backtester, &opportunity, None);
+    }
+    println!("{}", report);
+}
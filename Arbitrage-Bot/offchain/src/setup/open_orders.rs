@@ -0,0 +1,243 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{Keypair, Signer};
+use anchor_client::solana_sdk::signature::read_keypair_file;
+
+use anchor_client::{Client, Cluster, Program};
+
+use solana_sdk::instruction::Instruction;
+use solana_sdk::transaction::Transaction;
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use clap::{Parser, Subcommand};
+
+use tmp::accounts as tmp_accounts;
+use tmp::instruction as tmp_instructions;
+
+use client::constants::*;
+use client::pool::{PoolDir, PoolType};
+use client::pools::SerumPool;
+use client::pools::serum::{write_open_orders, OPEN_ORDERS_PATH};
+use client::serialize::open_orders::{
+    unpack_market_vaults, unpack_open_orders, OpenOrders, OPEN_ORDERS_OWNER_OFFSET, OPEN_ORDERS_SPAN,
+};
+use client::utils::{derive_token_address, get_owned_program_accounts, read_json_dir};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(short, long)]
+    pub cluster: String,
+    /// operator wallet which owns the open orders
+    #[clap(short, long)]
+    pub owner: String,
+    /// serum pool set -- markets outside of it are closed by `close`
+    #[clap(long, default_value = "../pools/serum/")]
+    pub pool_dir: String,
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// list the open orders accounts of each market
+    List,
+    /// settle the free funds of every open orders account into the owner's ATAs
+    Settle,
+    /// settle and close the open orders of markets not in the pool set
+    Close,
+    /// write the market -> open orders mapping used by SerumPool
+    Write {
+        #[clap(long, default_value = OPEN_ORDERS_PATH)]
+        out: String,
+    },
+}
+
+// open orders accounts of `owner` on the serum program
+fn get_open_orders(connection: &RpcClient, owner: &Pubkey) -> Vec<(Pubkey, OpenOrders, u64)> {
+    let accounts = get_owned_program_accounts(
+        connection,
+        &SERUM_PROGRAM_ID,
+        OPEN_ORDERS_SPAN,
+        OPEN_ORDERS_OWNER_OFFSET,
+        owner,
+    );
+    let mut open_orders: Vec<_> = accounts
+        .into_iter()
+        .map(|(address, account)| (address, unpack_open_orders(&account.data), account.lamports))
+        .collect();
+    open_orders.sort_by_key(|(_, oo, _)| oo.market);
+    open_orders
+}
+
+fn settle_ix(
+    program: &Program,
+    connection: &RpcClient,
+    owner: &Pubkey,
+    address: &Pubkey,
+    oo: &OpenOrders,
+) -> Vec<Instruction> {
+    let market_account = connection.get_account(&oo.market).unwrap();
+    let vaults = unpack_market_vaults(&oo.market, &market_account.data, &SERUM_PROGRAM_ID);
+
+    program.request()
+        .accounts(tmp_accounts::SettleOpenOrders {
+            market: oo.market,
+            open_orders: *address,
+            authority: *owner,
+            coin_vault: vaults.coin_vault,
+            pc_vault: vaults.pc_vault,
+            // ATAs must exist -- see the `accounts` bin
+            coin_wallet: derive_token_address(owner, &vaults.coin_mint),
+            pc_wallet: derive_token_address(owner, &vaults.pc_mint),
+            vault_signer: vaults.vault_signer,
+            dex_program: *SERUM_PROGRAM_ID,
+            token_program: *TOKEN_PROGRAM_ID,
+        })
+        .args(tmp_instructions::SettleOpenOrders {})
+        .instructions()
+        .unwrap()
+}
+
+fn close_ix(program: &Program, owner: &Pubkey, address: &Pubkey, oo: &OpenOrders) -> Vec<Instruction> {
+    program.request()
+        .accounts(tmp_accounts::CloseAccount {
+            open_orders: *address,
+            authority: *owner,
+            destination: *owner, // rent goes back to the owner
+            market: oo.market,
+            dex_program: *SERUM_PROGRAM_ID,
+        })
+        .args(tmp_instructions::CloseOpenOrders {})
+        .instructions()
+        .unwrap()
+}
+
+fn send(connection: &RpcClient, owner: &Keypair, ixs: Vec<Instruction>) {
+    let recent_hash = connection.get_latest_blockhash().unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&owner.pubkey()),
+        &[owner],
+        recent_hash,
+    );
+    match connection.send_and_confirm_transaction(&tx) {
+        Ok(signature) => println!("  {}", signature),
+        Err(e) => println!("  error: {:#?}", e),
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let cluster = match args.cluster.as_str() {
+        "localnet" => Cluster::Localnet,
+        "mainnet" => Cluster::Mainnet,
+        _ => panic!("invalid cluster type"),
+    };
+
+    env_logger::init();
+
+    // ** setup RPC connection
+    let connection = RpcClient::new_with_commitment(
+        cluster.url(),
+        CommitmentConfig::confirmed()
+    );
+
+    let provider = Client::new_with_options(
+        cluster,
+        Rc::new(read_keypair_file(&args.owner).unwrap()),
+        CommitmentConfig::confirmed()
+    );
+    let program = provider.program(*ARB_PROGRAM_ID);
+    let owner = read_keypair_file(&args.owner).unwrap();
+
+    // markets of the pool set
+    let serum_dir = PoolDir {
+        tipe: PoolType::SerumPoolType,
+        dir_path: args.pool_dir.clone(),
+    };
+    let markets: HashSet<Pubkey> = read_json_dir(&serum_dir.dir_path)
+        .iter()
+        .map(|path| {
+            let json_str = std::fs::read_to_string(path).unwrap();
+            let pool: SerumPool = serde_json::from_str(&json_str).unwrap();
+            pool.own_address.0
+        })
+        .collect();
+
+    let open_orders = get_open_orders(&connection, &owner.pubkey());
+
+    match args.command {
+        Command::List => {
+            for (address, oo, lamports) in open_orders.iter() {
+                let tag = if markets.contains(&oo.market) { "" } else { " (not in pool set)" };
+                println!("market {}{}", oo.market, tag);
+                println!(
+                    "  {} orders: {} base free/total: {}/{} quote free/total: {}/{} rent: {}",
+                    address, oo.order_count(),
+                    oo.native_coin_free, oo.native_coin_total,
+                    oo.native_pc_free, oo.native_pc_total,
+                    lamports,
+                );
+            }
+        }
+        Command::Settle => {
+            for (address, oo, _) in open_orders.iter().filter(|(_, oo, _)| oo.has_free_funds()) {
+                println!("settling {} (market {})", address, oo.market);
+                let ixs = settle_ix(&program, &connection, &owner.pubkey(), address, oo);
+                send(&connection, &owner, ixs);
+            }
+        }
+        Command::Close => {
+            let mut reclaimed = 0;
+            for (address, oo, lamports) in open_orders.iter() {
+                if markets.contains(&oo.market) {
+                    continue;
+                }
+                // resting orders keep funds locked -- can't settle those
+                if oo.order_count() > 0 {
+                    println!("skipping {} (market {}): {} resting orders", address, oo.market, oo.order_count());
+                    continue;
+                }
+
+                println!("closing {} (market {})", address, oo.market);
+                let mut ixs = vec![];
+                if oo.has_free_funds() {
+                    ixs.extend(settle_ix(&program, &connection, &owner.pubkey(), address, oo));
+                }
+                ixs.extend(close_ix(&program, &owner.pubkey(), address, oo));
+                send(&connection, &owner, ixs);
+                reclaimed += lamports;
+            }
+            println!("reclaimed rent: {} lamports", reclaimed);
+        }
+        Command::Write { out } => {
+            let mut market_to_open_orders = HashMap::new();
+            for (address, oo, _) in open_orders.iter() {
+                if !markets.contains(&oo.market) {
+                    continue;
+                }
+                // keep the first account of a market, the others can be closed by hand
+                if let Some(kept) = market_to_open_orders.get(&oo.market.to_string()) {
+                    println!("market {} has more than one open orders: keeping {}, ignoring {}", oo.market, kept, address);
+                    continue;
+                }
+                market_to_open_orders.insert(oo.market.to_string(), address.to_string());
+            }
+
+            for market in markets.iter() {
+                if !market_to_open_orders.contains_key(&market.to_string()) {
+                    println!("market {} has no open orders (run setup_open_orders)", market);
+                }
+            }
+
+            write_open_orders(&out, &market_to_open_orders);
+            println!("wrote {} markets to {}", market_to_open_orders.len(), out);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{Signer};
+use anchor_client::solana_sdk::signature::read_keypair_file;
+
+use anchor_client::{Client, Cluster};
+
+use solana_sdk::transaction::Transaction;
+
+use std::rc::Rc;
+use std::str::FromStr;
+
+use clap::Parser;
+
+use tmp::accounts as tmp_accounts;
+use tmp::instruction as tmp_instructions;
+
+use client::utils::{derive_mint_stats_address};
+use client::constants::*;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(short, long)]
+    pub cluster: String,
+    /// operator wallet which owns the swap state
+    #[clap(short, long)]
+    pub owner: String,
+    /// start mint of the routes
+    #[clap(short, long)]
+    pub mint: String,
+    /// token account (of `mint`) which receives swept profit
+    #[clap(short, long)]
+    pub treasury: String,
+    /// balance (scaled) kept in the operator wallet when sweeping
+    #[clap(short, long, default_value_t = 0)]
+    pub working_balance: u64,
+}
+
+fn main() {
+    let args = Args::parse();
+    let cluster = match args.cluster.as_str() {
+        "localnet" => Cluster::Localnet,
+        "mainnet" => Cluster::Mainnet,
+        _ => panic!("invalid cluster type"),
+    };
+    let mint = Pubkey::from_str(&args.mint).unwrap();
+    let treasury = Pubkey::from_str(&args.treasury).unwrap();
+
+    env_logger::init();
+
+    // ** setup RPC connection
+    let connection = RpcClient::new_with_commitment(
+        cluster.url(),
+        CommitmentConfig::confirmed()
+    );
+
+    let provider = Client::new_with_options(
+        cluster,
+        Rc::new(read_keypair_file(&args.owner).unwrap()),
+        CommitmentConfig::confirmed()
+    );
+    let program = provider.program(*ARB_PROGRAM_ID);
+    let owner = read_keypair_file(&args.owner).unwrap();
+
+    // one stats account per operator wallet and start mint -- re-running updates the sweep config
+    let mint_stats = derive_mint_stats_address(&program.id(), &owner.pubkey(), &mint);
+    let ixs = if connection.get_account(&mint_stats).is_ok() {
+        program.request()
+            .accounts(tmp_accounts::SetSweepConfig {
+                mint_stats,
+                treasury,
+                authority: owner.pubkey(),
+            })
+            .args(tmp_instructions::SetSweepConfig { working_balance: args.working_balance })
+            .instructions()
+            .unwrap()
+    } else {
+        program.request()
+            .accounts(tmp_accounts::InitMintStats {
+                mint_stats,
+                mint,
+                treasury,
+                authority: owner.pubkey(),
+                payer: owner.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            })
+            .args(tmp_instructions::InitMintStats { working_balance: args.working_balance })
+            .instructions()
+            .unwrap()
+    };
+
+    let recent_hash = connection.get_latest_blockhash().unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_hash,
+    );
+    let signature = connection.send_and_confirm_transaction(&tx).unwrap();
+    println!("mint stats {} for {} (treasury {}): {}", mint_stats, mint, treasury, signature);
+}
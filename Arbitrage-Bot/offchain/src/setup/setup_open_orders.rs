@@ -8,6 +8,7 @@ use anchor_client::solana_sdk::signature::read_keypair_file;
 use anchor_client::{Client, Cluster};
 
 use client::pools::SerumPool;
+use client::pools::serum::{write_open_orders, OPEN_ORDERS_PATH};
 
 use solana_sdk::transaction::Transaction;
 
@@ -147,7 +148,6 @@ fn main() {
     }
 
     // save open orders accounts as .JSON 
-    let json_market_oo = serde_json::to_string(&market_to_open_orders).unwrap();
-    std::fs::write("./serum_open_orders.json", json_market_oo).unwrap();
+    write_open_orders(OPEN_ORDERS_PATH, &market_to_open_orders);
 
 }
\ No newline at end of file
@@ -0,0 +1,83 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+
+use anchor_client::solana_sdk::signature::{Signer};
+use anchor_client::solana_sdk::signature::read_keypair_file;
+
+use anchor_client::{Client, Cluster};
+
+use solana_sdk::transaction::Transaction;
+
+use std::rc::Rc;
+
+use clap::Parser;
+
+use tmp::accounts as tmp_accounts;
+use tmp::instruction as tmp_instructions;
+
+use client::utils::{derive_swap_state_address};
+use client::constants::*;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(short, long)]
+    pub cluster: String,
+    /// operator wallet which will own the swap state
+    #[clap(short, long)]
+    pub owner: String,
+}
+
+fn main() {
+    let args = Args::parse();
+    let cluster = match args.cluster.as_str() {
+        "localnet" => Cluster::Localnet,
+        "mainnet" => Cluster::Mainnet,
+        _ => panic!("invalid cluster type"),
+    };
+
+    env_logger::init();
+
+    // ** setup RPC connection
+    let connection = RpcClient::new_with_commitment(
+        cluster.url(),
+        CommitmentConfig::confirmed()
+    );
+
+    let provider = Client::new_with_options(
+        cluster,
+        Rc::new(read_keypair_file(&args.owner).unwrap()),
+        CommitmentConfig::confirmed()
+    );
+    let program = provider.program(*ARB_PROGRAM_ID);
+    let owner = read_keypair_file(&args.owner).unwrap();
+
+    // one swap state per operator wallet
+    let swap_state = derive_swap_state_address(&program.id(), &owner.pubkey());
+    if connection.get_account(&swap_state).is_ok() {
+        println!("swap state {} already initialized for {}", swap_state, owner.pubkey());
+        return;
+    }
+
+    let ixs = program.request()
+        .accounts(tmp_accounts::InitSwapState {
+            swap_state,
+            authority: owner.pubkey(),
+            payer: owner.pubkey(),
+            system_program: solana_sdk::system_program::id(),
+        })
+        .args(tmp_instructions::InitProgram {})
+        .instructions()
+        .unwrap();
+
+    let recent_hash = connection.get_latest_blockhash().unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_hash,
+    );
+    let signature = connection.send_and_confirm_transaction(&tx).unwrap();
+    println!("initialized swap state {} for {}: {}", swap_state, owner.pubkey(), signature);
+}
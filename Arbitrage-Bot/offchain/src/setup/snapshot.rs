@@ -0,0 +1,77 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{Signer};
+use anchor_client::solana_sdk::signature::read_keypair_file;
+
+use anchor_client::Cluster;
+
+use std::str::FromStr;
+
+use clap::Parser;
+
+use log::info;
+
+use client::pool::{PoolDir, PoolType};
+use client::router::load_pools;
+use client::snapshot::Snapshot;
+use client::utils::derive_token_address;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(short, long)]
+    pub cluster: String,
+    /// operator wallet whose start mint ATA is recorded
+    #[clap(short, long)]
+    pub owner: String,
+    #[clap(long, default_value = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")]
+    pub start_mint: String,
+    /// snapshots are written to <dir>/<slot>.json
+    #[clap(long, default_value = "snapshots")]
+    pub dir: String,
+}
+
+fn main() {
+    let args = Args::parse();
+    let cluster = match args.cluster.as_str() {
+        "localnet" => Cluster::Localnet,
+        "mainnet" => Cluster::Mainnet,
+        _ => panic!("invalid cluster type"),
+    };
+
+    env_logger::init();
+
+    let connection = RpcClient::new_with_commitment(
+        cluster.url(),
+        CommitmentConfig::confirmed()
+    );
+    let owner = read_keypair_file(&args.owner).unwrap().pubkey();
+    let start_mint = Pubkey::from_str(&args.start_mint).unwrap();
+
+    // same pool set as main
+    let pool_dirs = vec![
+        PoolDir { tipe: PoolType::OrcaPoolType, dir_path: "../pools/orca".to_string() },
+        PoolDir { tipe: PoolType::MercurialPoolType, dir_path: "../pools/mercurial".to_string() },
+        PoolDir { tipe: PoolType::SaberPoolType, dir_path: "../pools/saber/".to_string() },
+    ];
+    let pools = load_pools(&pool_dirs);
+
+    let mut update_pks: Vec<Pubkey> = pools
+        .iter()
+        .flat_map(|pool| pool.get_update_accounts())
+        .collect();
+    update_pks.push(derive_token_address(&owner, &start_mint));
+    info!("recording {} accounts of {} pools", update_pks.len(), pools.len());
+
+    let snapshot = Snapshot::fetch(&connection, &owner, &start_mint, &update_pks);
+
+    std::fs::create_dir_all(&args.dir).unwrap();
+    let path = format!("{}/{}.json", args.dir.trim_end_matches('/'), snapshot.slot);
+    snapshot.save(&path);
+
+    let missing = snapshot.accounts.values().filter(|account| account.is_none()).count();
+    println!("slot {}: {} accounts ({} missing) -> {}", snapshot.slot, snapshot.accounts.len(), missing, path);
+}
@@ -0,0 +1,80 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_sdk::account::Account;
+
+use std::collections::HashMap;
+
+use log::warn;
+
+/// The accounts the engine quotes from (vaults, orderbooks, the owner's
+/// start ATA) as of one slot -- replayed with `main --replay`
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub slot: u64,
+    pub owner: String,
+    pub start_mint: String,
+    // pubkey -> base64 account, None = the account doesn't exist
+    pub accounts: HashMap<String, Option<UiAccount>>,
+}
+
+impl Snapshot {
+    /// Fetch `pks` (in chunks of the RPC limit), the slot is the oldest
+    /// context slot of the chunks
+    pub fn fetch(connection: &RpcClient, owner: &Pubkey, start_mint: &Pubkey, pks: &[Pubkey]) -> Snapshot {
+        let mut slots = vec![];
+        let mut accounts = HashMap::new();
+        // max 100 accounts per get_multiple_accounts
+        for chunk in pks.chunks(99) {
+            let response = connection
+                .get_multiple_accounts_with_commitment(chunk, connection.commitment())
+                .unwrap();
+            slots.push(response.context.slot);
+            for (pk, account) in chunk.iter().zip(response.value) {
+                let account = account.map(|account| {
+                    UiAccount::encode(pk, &account, UiAccountEncoding::Base64, None, None)
+                });
+                accounts.insert(pk.to_string(), account);
+            }
+        }
+
+        let slot = slots.iter().copied().min().unwrap_or_default();
+        let newest = slots.iter().copied().max().unwrap_or_default();
+        if newest != slot {
+            warn!("snapshot chunks span slots {} - {}", slot, newest);
+        }
+
+        Snapshot {
+            slot,
+            owner: owner.to_string(),
+            start_mint: start_mint.to_string(),
+            accounts,
+        }
+    }
+
+    pub fn load(path: &str) -> Snapshot {
+        let json_str = std::fs::read_to_string(path).unwrap();
+        serde_json::from_str(&json_str).unwrap()
+    }
+
+    pub fn save(&self, path: &str) {
+        let json_str = serde_json::to_string(self).unwrap();
+        std::fs::write(path, json_str).unwrap();
+    }
+
+    /// Same as `RpcClient::get_multiple_accounts`, accounts which weren't
+    /// recorded are None
+    pub fn get_multiple_accounts(&self, pks: &[Pubkey]) -> Vec<Option<Account>> {
+        pks.iter()
+            .map(|pk| {
+                self.accounts
+                    .get(&pk.to_string())
+                    .and_then(|account| account.as_ref())
+                    .map(|account| account.decode().unwrap())
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,325 @@
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use solana_sdk::account::Account;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
+    SubscribeRequestFilterAccounts, SubscribeRequestFilterTransactions, SubscribeRequestPing,
+};
+
+use log::{info, warn};
+
+use crate::follow::{project_token_balances, TxTrigger};
+use crate::pipeline::spawn_get_multiple_accounts;
+use crate::snapshot::Snapshot;
+
+/// New state of an account as of `slot`, None = the account doesn't exist
+#[derive(Debug, Clone)]
+pub struct AccountUpdate {
+    pub pubkey: Pubkey,
+    pub account: Option<Account>,
+    pub slot: u64,
+}
+
+/// Where the accounts the engine quotes from come from
+pub trait AccountSource {
+    /// Updates since the last call, blocks until there is one -- None = the
+    /// source is done
+    fn next_updates(&mut self) -> Option<Vec<AccountUpdate>>;
+
+    /// The tx the last updates were projected from, if any (--follow-txs)
+    fn trigger(&self) -> Option<&TxTrigger> {
+        None
+    }
+}
+
+/// Every account on each call, the next call's fetch runs in the background
+pub struct RpcPolling {
+    runtime: Handle,
+    connection: Arc<RpcClient>,
+    pks: Vec<Pubkey>,
+    next: Option<JoinHandle<Vec<AccountUpdate>>>,
+}
+
+impl RpcPolling {
+    pub fn new(runtime: &Handle, connection: Arc<RpcClient>, pks: Vec<Pubkey>) -> Self {
+        let next = spawn_get_multiple_accounts(runtime, connection.clone(), pks.clone());
+        RpcPolling { runtime: runtime.clone(), connection, pks, next: Some(next) }
+    }
+}
+
+impl AccountSource for RpcPolling {
+    fn next_updates(&mut self) -> Option<Vec<AccountUpdate>> {
+        let fetch = self.next.take().unwrap();
+        let updates = self.runtime.block_on(fetch).unwrap();
+        // fetch the next round while this one is searched
+        self.next = Some(spawn_get_multiple_accounts(&self.runtime, self.connection.clone(), self.pks.clone()));
+        Some(updates)
+    }
+}
+
+/// The accounts of a snapshot, once
+pub struct SnapshotSource {
+    pub snapshot: Option<Snapshot>,
+    pub pks: Vec<Pubkey>,
+}
+
+impl AccountSource for SnapshotSource {
+    fn next_updates(&mut self) -> Option<Vec<AccountUpdate>> {
+        let snapshot = self.snapshot.take()?;
+        let accounts = snapshot.get_multiple_accounts(&self.pks);
+        let updates = self.pks
+            .iter()
+            .zip(accounts)
+            .map(|(pubkey, account)| AccountUpdate { pubkey: *pubkey, account, slot: snapshot.slot })
+            .collect();
+        Some(updates)
+    }
+}
+
+enum GrpcUpdate {
+    Accounts(Vec<AccountUpdate>),
+    Tx(TxTrigger),
+}
+
+/// Account updates streamed by a Yellowstone gRPC endpoint
+///
+/// Subscriptions only send changes -- on every (re)connect all the accounts
+/// are also fetched once over RPC
+///
+/// With `programs`, the txs through them are streamed too and each one
+/// which moves a vault ends a batch of updates with the vault state it
+/// leaves behind (see `trigger`)
+pub struct GrpcSource {
+    updates: mpsc::UnboundedReceiver<GrpcUpdate>,
+    latest: HashMap<Pubkey, Account>, // to project txs on
+    trigger: Option<TxTrigger>,
+}
+
+impl GrpcSource {
+    pub fn spawn(
+        runtime: &Handle,
+        endpoint: String,
+        x_token: Option<String>,
+        connection: Arc<RpcClient>,
+        pks: Vec<Pubkey>,
+        programs: Vec<Pubkey>,
+    ) -> Self {
+        let (tx, updates) = mpsc::unbounded_channel();
+        let handle = runtime.clone();
+
+        runtime.spawn(async move {
+            loop {
+                if let Err(err) = stream_accounts(&handle, &endpoint, &x_token, &connection, &pks, &programs, &tx).await {
+                    warn!("grpc stream failed: {}", err);
+                }
+                if tx.is_closed() {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await; // reconnect
+            }
+        });
+        GrpcSource { updates, latest: HashMap::new(), trigger: None }
+    }
+}
+
+async fn stream_accounts(
+    runtime: &Handle,
+    endpoint: &str,
+    x_token: &Option<String>,
+    connection: &Arc<RpcClient>,
+    pks: &[Pubkey],
+    programs: &[Pubkey],
+    tx: &mpsc::UnboundedSender<GrpcUpdate>,
+) -> anyhow::Result<()> {
+    let mut client = GeyserGrpcClient::build_from_shared(endpoint.to_string())?
+        .x_token(x_token.clone())?
+        .tls_config(ClientTlsConfig::new().with_native_roots())?
+        .connect()
+        .await?;
+
+    let mut accounts = HashMap::new();
+    accounts.insert(
+        "pools".to_string(),
+        SubscribeRequestFilterAccounts {
+            account: pks.iter().map(|pk| pk.to_string()).collect(),
+            ..SubscribeRequestFilterAccounts::default()
+        },
+    );
+    let mut transactions = HashMap::new();
+    if !programs.is_empty() {
+        transactions.insert(
+            "follow".to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(false),
+                account_include: programs.iter().map(|pk| pk.to_string()).collect(),
+                ..SubscribeRequestFilterTransactions::default()
+            },
+        );
+    }
+    let request = SubscribeRequest {
+        accounts,
+        transactions,
+        commitment: Some(CommitmentLevel::Processed as i32),
+        ..SubscribeRequest::default()
+    };
+    let (mut subscribe_tx, mut stream) = client.subscribe_with_request(Some(request)).await?;
+    info!("streaming {} accounts from {}", pks.len(), endpoint);
+
+    // the state before the first change
+    let initial = spawn_get_multiple_accounts(runtime, connection.clone(), pks.to_vec());
+    if tx.send(GrpcUpdate::Accounts(initial.await?)).is_err() {
+        return Ok(()); // receiver is gone
+    }
+
+    while let Some(message) = stream.next().await {
+        match message?.update_oneof {
+            Some(UpdateOneof::Account(update)) => {
+                let info = match update.account {
+                    Some(info) => info,
+                    None => continue,
+                };
+                let account = Account {
+                    lamports: info.lamports,
+                    data: info.data,
+                    owner: Pubkey::new(&info.owner),
+                    executable: info.executable,
+                    rent_epoch: info.rent_epoch,
+                };
+                let update = AccountUpdate {
+                    pubkey: Pubkey::new(&info.pubkey),
+                    account: Some(account),
+                    slot: update.slot,
+                };
+                if tx.send(GrpcUpdate::Accounts(vec![update])).is_err() {
+                    return Ok(());
+                }
+            }
+            Some(UpdateOneof::Transaction(update)) => {
+                if let Some(trigger) = TxTrigger::from_update(update) {
+                    if tx.send(GrpcUpdate::Tx(trigger)).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            // keep the connection alive through load balancers
+            Some(UpdateOneof::Ping(_)) => {
+                subscribe_tx
+                    .send(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: 1 }),
+                        ..SubscribeRequest::default()
+                    })
+                    .await?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl AccountSource for GrpcSource {
+    fn next_updates(&mut self) -> Option<Vec<AccountUpdate>> {
+        self.trigger = None;
+        let mut updates = vec![];
+        let mut next = Some(self.updates.blocking_recv()?);
+        // and whatever else arrived meanwhile, up to a tx which moves a vault
+        while let Some(update) = next {
+            match update {
+                GrpcUpdate::Accounts(accounts) => {
+                    for update in accounts.iter() {
+                        if let Some(account) = update.account.as_ref() {
+                            self.latest.insert(update.pubkey, account.clone());
+                        }
+                    }
+                    updates.extend(accounts);
+                }
+                GrpcUpdate::Tx(trigger) => {
+                    let projected = project_token_balances(&self.latest, &trigger);
+                    if !projected.is_empty() {
+                        updates.extend(projected);
+                        self.trigger = Some(trigger);
+                        break;
+                    }
+                }
+            }
+            next = self.updates.try_recv().ok();
+        }
+        Some(updates)
+    }
+
+    fn trigger(&self) -> Option<&TxTrigger> {
+        self.trigger.as_ref()
+    }
+}
+
+/// Latest state of the accounts of every pool
+pub struct PoolUpdater {
+    pub update_pks: Vec<Vec<Pubkey>>, // of each pool
+    pub pool_slots: Vec<u64>,         // slot of each pool's newest account, 0 = none yet
+    accounts: HashMap<Pubkey, (u64, Option<Account>)>,
+    account_pools: HashMap<Pubkey, Vec<usize>>,
+}
+
+impl PoolUpdater {
+    pub fn new(update_pks: Vec<Vec<Pubkey>>) -> Self {
+        let mut account_pools: HashMap<Pubkey, Vec<usize>> = HashMap::new();
+        for (pool_idx, pks) in update_pks.iter().enumerate() {
+            for pk in pks {
+                account_pools.entry(*pk).or_default().push(pool_idx);
+            }
+        }
+        PoolUpdater {
+            pool_slots: vec![0; update_pks.len()],
+            update_pks,
+            accounts: HashMap::new(),
+            account_pools,
+        }
+    }
+
+    /// Apply `updates` (older than what's known are dropped), returns the
+    /// pools they changed
+    pub fn apply(&mut self, updates: Vec<AccountUpdate>) -> Vec<usize> {
+        let mut updated = HashSet::new();
+        for update in updates {
+            if let Some((slot, _)) = self.accounts.get(&update.pubkey) {
+                if *slot > update.slot {
+                    continue;
+                }
+            }
+            if let Some(pools) = self.account_pools.get(&update.pubkey) {
+                for pool_idx in pools {
+                    self.pool_slots[*pool_idx] = self.pool_slots[*pool_idx].max(update.slot);
+                    updated.insert(*pool_idx);
+                }
+            }
+            self.accounts.insert(update.pubkey, (update.slot, update.account));
+        }
+        let mut updated: Vec<usize> = updated.into_iter().collect();
+        updated.sort_unstable();
+        updated
+    }
+
+    pub fn account(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get(pubkey).and_then(|(_, account)| account.as_ref())
+    }
+
+    /// The accounts to set a pool with, None until each of them exists
+    pub fn pool_accounts(&self, pool_idx: usize) -> Option<Vec<Option<Account>>> {
+        self.update_pks[pool_idx]
+            .iter()
+            .map(|pk| self.account(pk).map(|account| Some(account.clone())))
+            .collect()
+    }
+}
@@ -0,0 +1,131 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use crate::error::QuoteError;
+use crate::utils::PoolQuote;
+
+/// A hop input divided across parallel pools of one pair
+///
+/// `pools[0]` takes the largest part and is the main swap of the on-chain
+/// hop, the others are its splits
+#[derive(Debug, Clone)]
+pub struct SplitQuote {
+    pub pools: Vec<PoolQuote>,
+    pub amounts_in: Vec<u128>,
+    pub amount_out: u128,
+}
+
+impl SplitQuote {
+    pub fn amount_in(&self) -> u128 {
+        self.amounts_in.iter().sum()
+    }
+
+    /// Share of the input of every split (`pools[1..]`) as sent on-chain
+    pub fn share_bps(&self) -> Vec<u16> {
+        let amount_in = self.amount_in();
+        self.amounts_in
+            .iter()
+            .skip(1)
+            .map(|amount| (amount * 10_000 / amount_in) as u16)
+            .collect()
+    }
+
+    /// Re-quote with the amounts the program derives from the shares: each
+    /// split rounds down and the main swap takes the rest
+    pub fn requote(&self, amount_in: u128, mint_in: &Pubkey, mint_out: &Pubkey) -> Result<SplitQuote, QuoteError> {
+        let mut amounts_in: Vec<u128> = self
+            .share_bps()
+            .iter()
+            .map(|bps| amount_in * *bps as u128 / 10_000)
+            .collect();
+        let main_amount = amount_in - amounts_in.iter().sum::<u128>();
+        amounts_in.insert(0, main_amount);
+
+        let amount_out = self
+            .pools
+            .iter()
+            .zip(amounts_in.iter())
+            .filter(|(_, amount)| **amount > 0)
+            .map(|(pool, amount)| pool.0.get_quote_with_amounts_scaled(*amount, mint_in, mint_out))
+            .sum::<Result<u128, QuoteError>>()?;
+        Ok(SplitQuote {
+            pools: self.pools.clone(),
+            amounts_in,
+            amount_out,
+        })
+    }
+}
+
+/// Divide `amount_in` across the parallel `pools` of a pair
+///
+/// The input goes in `steps` chunks, each to the pool with the largest
+/// marginal output for it -- pools being concave, this ends with their
+/// marginal outputs about equal. Pools which get nothing are dropped, like
+/// the ones which can't quote a chunk -- an error if none of them can
+pub fn split_hop(
+    pools: &[PoolQuote],
+    amount_in: u128,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    steps: usize,
+) -> Result<SplitQuote, QuoteError> {
+    let steps = steps.max(1) as u128;
+    let mut amounts_in = vec![0_u128; pools.len()];
+    let mut amounts_out = vec![0_u128; pools.len()];
+
+    let chunk = amount_in / steps;
+    for step in 0..steps {
+        let chunk = if step == steps - 1 {
+            amount_in - chunk * (steps - 1) // rounding leftover
+        } else {
+            chunk
+        };
+        if chunk == 0 {
+            continue;
+        }
+
+        // (pool idx, output with the chunk)
+        let mut best: Option<(usize, u128)> = None;
+        let mut last_err = QuoteError::InsufficientLiquidity; // no pools
+        for (i, pool) in pools.iter().enumerate() {
+            let out = match pool.0.get_quote_with_amounts_scaled(amounts_in[i] + chunk, mint_in, mint_out) {
+                Ok(out) => out,
+                Err(err) => {
+                    last_err = err;
+                    continue;
+                }
+            };
+            let marginal = out.saturating_sub(amounts_out[i]);
+            let is_better = match best {
+                Some((best_i, best_out)) => marginal > best_out.saturating_sub(amounts_out[best_i]),
+                None => true,
+            };
+            if is_better {
+                best = Some((i, out));
+            }
+        }
+
+        match best {
+            Some((i, out)) => {
+                amounts_in[i] += chunk;
+                amounts_out[i] = out;
+            }
+            None => return Err(last_err),
+        }
+    }
+
+    let mut parts: Vec<(PoolQuote, u128, u128)> = pools
+        .iter()
+        .cloned()
+        .zip(amounts_in)
+        .zip(amounts_out)
+        .map(|((pool, amount_in), amount_out)| (pool, amount_in, amount_out))
+        .filter(|(_, amount_in, _)| *amount_in > 0)
+        .collect();
+    parts.sort_by(|a, b| b.1.cmp(&a.1)); // main swap first
+
+    Ok(SplitQuote {
+        pools: parts.iter().map(|(pool, _, _)| pool.clone()).collect(),
+        amounts_in: parts.iter().map(|(_, amount_in, _)| *amount_in).collect(),
+        amount_out: parts.iter().map(|(_, _, amount_out)| *amount_out).sum(),
+    })
+}
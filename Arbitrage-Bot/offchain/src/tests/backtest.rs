@@ -0,0 +1,59 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use crate::backtest::{Backtester, BacktestReport};
+use crate::tests::common::constant_product as pool;
+
+#[test]
+fn finds_cycles_and_requotes_them_later() {
+    let (usdc, a) = (Pubkey::new_unique(), Pubkey::new_unique());
+    // a is cheap on "cheap" and dear on "dear"
+    let backtester = Backtester::new(vec![
+        pool("cheap", [usdc, a], [1_000_000, 2_000_000]),
+        pool("dear", [usdc, a], [1_000_000, 1_000_000]),
+    ], 3);
+    let usdc_idx = backtester.mint2idx[&usdc];
+
+    let opportunities = backtester.find_arbitrages(1, usdc_idx, 10_000);
+    assert_eq!(opportunities.len(), 1);
+    let arb = &opportunities[0];
+    assert_eq!(arb.pools, vec![0, 1]);
+    assert_eq!(arb.amount_out, 19_416);
+    assert_eq!(backtester.quote_route(&arb.mints, &arb.pools, arb.amount_in), arb.amount_out);
+
+    // a snapshot later the prices met
+    let later = Backtester::new(vec![
+        pool("cheap", [usdc, a], [1_000_000, 1_000_000]),
+        pool("dear", [usdc, a], [1_000_000, 1_000_000]),
+    ], 3);
+    let amount_out = later.quote_route(&arb.mints, &arb.pools, arb.amount_in);
+    assert!(amount_out < arb.amount_in);
+
+    let mut report = BacktestReport::default();
+    report.record(&backtester, arb, Some(arb.amount_out));
+    report.record(&later, arb, Some(amount_out));
+    report.record(&later, arb, None);
+    assert_eq!((report.opportunities, report.captured, report.expired, report.unresolved), (2, 1, 1, 1));
+    assert_eq!(report.captured_profit, 9_416);
+    assert_eq!(report.quoted_profit, 2 * 9_416);
+    assert_eq!(report.hops[&2].found, 2);
+    assert_eq!(report.dexes["cheap"].captured, 1);
+}
+
+#[test]
+fn max_hops_bounds_the_cycles() {
+    let (usdc, a, b) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    // usdc -> a -> b -> usdc gains, every pair has one pool
+    let pools = || vec![
+        pool("ua", [usdc, a], [1_000_000, 2_000_000]),
+        pool("ab", [a, b], [1_000_000, 1_000_000]),
+        pool("bu", [b, usdc], [1_000_000, 1_000_000]),
+    ];
+
+    let backtester = Backtester::new(pools(), 2);
+    assert!(backtester.find_arbitrages(1, backtester.mint2idx[&usdc], 10_000).is_empty());
+
+    let backtester = Backtester::new(pools(), 3);
+    let opportunities = backtester.find_arbitrages(1, backtester.mint2idx[&usdc], 10_000);
+    assert_eq!(opportunities.len(), 1);
+    assert_eq!(backtester.route_label(&opportunities[0].pools).matches(" -> ").count(), 2);
+}
@@ -0,0 +1,87 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::{Cluster, Program};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use tmp::ix_data::DexKind;
+
+use std::rc::Rc;
+
+use crate::error::QuoteError;
+use crate::pool::PoolOperations;
+use crate::utils::PoolQuote;
+
+// fee-less x * y = k pool, quotes only
+#[derive(Debug)]
+pub(crate) struct ConstantProduct {
+    pub(crate) name: String,
+    pub(crate) mints: [Pubkey; 2],
+    pub(crate) reserves: [u128; 2],
+}
+
+impl ConstantProduct {
+    fn reserves(&self, mint_in: &Pubkey) -> (u128, u128) {
+        if *mint_in == self.mints[0] {
+            (self.reserves[0], self.reserves[1])
+        } else {
+            (self.reserves[1], self.reserves[0])
+        }
+    }
+}
+
+impl PoolOperations for ConstantProduct {
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    fn get_update_accounts(&self) -> Vec<Pubkey> {
+        vec![]
+    }
+    fn set_update_accounts(&mut self, _accounts: Vec<Option<Account>>, _cluster: Cluster) -> Result<(), QuoteError> {
+        Ok(())
+    }
+    // no vaults -- a mint stands for its own
+    fn mint_2_addr(&self, mint: &Pubkey) -> Result<Pubkey, QuoteError> {
+        if self.mints.contains(mint) {
+            Ok(*mint)
+        } else {
+            Err(QuoteError::UnknownMint(*mint))
+        }
+    }
+    fn get_mints(&self) -> Vec<Pubkey> {
+        self.mints.to_vec()
+    }
+    fn mint_2_scale(&self, _mint: &Pubkey) -> Result<u64, QuoteError> {
+        Ok(1)
+    }
+    fn get_quote_with_amounts_scaled(&self, amount_in: u128, mint_in: &Pubkey, _mint_out: &Pubkey) -> Result<u128, QuoteError> {
+        let (r_in, r_out) = self.reserves(mint_in);
+        Ok(r_out * amount_in / (r_in + amount_in))
+    }
+    fn get_quote_exact_out(&self, amount_out: u128, mint_in: &Pubkey, _mint_out: &Pubkey) -> Result<u128, QuoteError> {
+        let (r_in, r_out) = self.reserves(mint_in);
+        if amount_out >= r_out {
+            return Err(QuoteError::InsufficientLiquidity);
+        }
+        // smallest a with r_out * a / (r_in + a) >= amount_out
+        let left = r_out - amount_out;
+        Ok((amount_out * r_in + left - 1) / left)
+    }
+    // a stand-in ix carrying the bound the hop was given
+    fn swap_ix(&self, program: &Program, _: &Pubkey, _: &Pubkey, _: &Pubkey, min_amount_out: Option<u64>) -> Result<Vec<Instruction>, QuoteError> {
+        let data = min_amount_out.unwrap_or(0).to_le_bytes().to_vec();
+        Ok(vec![Instruction::new_with_bytes(program.id(), &data, vec![])])
+    }
+    fn route_hop(&self, _: &Pubkey, _: &Pubkey, _: &Pubkey) -> Result<(DexKind, Vec<AccountMeta>), QuoteError> {
+        Err(QuoteError::StaleState(format!("{} has no accounts to route through", self.name)))
+    }
+    fn can_trade(&self, _mint_in: &Pubkey, _mint_out: &Pubkey) -> bool {
+        true
+    }
+}
+
+pub(crate) fn constant_product(name: &str, mints: [Pubkey; 2], reserves: [u128; 2]) -> Box<dyn PoolOperations> {
+    Box::new(ConstantProduct { name: name.to_string(), mints, reserves })
+}
+
+pub(crate) fn pool(name: &str, mints: [Pubkey; 2], reserves: [u128; 2]) -> PoolQuote {
+    PoolQuote::new(Rc::new(constant_product(name, mints, reserves)))
+}
@@ -0,0 +1,144 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use anchor_client::Cluster;
+use solana_sdk::account::Account;
+
+use crate::error::QuoteError;
+use crate::pool::PoolOperations;
+use crate::pools::OrcaPool;
+
+// token-swap pool JSON of mints A (token A) and B (token B), fee-less
+fn pool_json(mint_a: &Pubkey, mint_b: &Pubkey, curve: &str) -> String {
+    let addr = Pubkey::new_unique();
+    let token = |mint: &Pubkey| format!(
+        r#""{mint}":{{"tag":"T","name":"T","mint":"{mint}","scale":6,"addr":"{addr}"}}"#,
+        mint = mint, addr = Pubkey::new_unique(),
+    );
+    format!(
+        r#"{{"address":"{addr}","nonce":255,"authority":"{addr}","poolTokenMint":"{addr}","poolTokenDecimals":6,"feeAccount":"{addr}",
+        "tokenIds":["{a}","{b}"],"tokens":{{{ta},{tb}}},{curve},
+        "feeStructure":{{"traderFee":{{"numerator":0,"denominator":10000}},"ownerFee":{{"numerator":0,"denominator":10000}}}}}}"#,
+        addr = addr, a = mint_a, b = mint_b, ta = token(mint_a), tb = token(mint_b), curve = curve,
+    )
+}
+
+pub(crate) fn pool_with_amounts(curve: &str, amount_a: u128, amount_b: u128) -> (Box<dyn PoolOperations>, Pubkey, Pubkey) {
+    // token A sorts after token B -- the direction comes from the token ids, not the mint order
+    let mut mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    mints.sort();
+    let (mint_b, mint_a) = (mints[0], mints[1]);
+
+    let json = pool_json(&mint_a, &mint_b, curve);
+    let mut pool: OrcaPool = serde_json::from_str(&json).unwrap();
+    pool.pool_amounts.insert(mint_a.to_string(), amount_a);
+    pool.pool_amounts.insert(mint_b.to_string(), amount_b);
+    let pool: Box<dyn PoolOperations> = Box::new(pool);
+    (pool, mint_a, mint_b)
+}
+
+#[test]
+fn constant_price_quotes_by_direction() {
+    let (pool, mint_a, mint_b) = pool_with_amounts(r#""curveType":1,"tokenBPrice":4"#, 1_000_000, 1_000_000);
+
+    // 1 B costs 4 A
+    assert_eq!(pool.get_quote_with_amounts_scaled(1_000, &mint_a, &mint_b), Ok(250));
+    assert_eq!(pool.get_quote_with_amounts_scaled(1_000, &mint_b, &mint_a), Ok(4_000));
+    // less than the price of 1 B buys nothing
+    assert_eq!(pool.get_quote_with_amounts_scaled(3, &mint_a, &mint_b), Ok(0));
+    // a fixed price doesn't slip -- past the reserve the swap fails
+    assert_eq!(
+        pool.get_quote_with_amounts_scaled(400_000, &mint_b, &mint_a),
+        Err(QuoteError::InsufficientLiquidity)
+    );
+}
+
+#[test]
+fn offset_curve_fakes_token_b_liquidity() {
+    let offset = r#""curveType":3,"tokenBOffset":1000000"#;
+    let (pool, mint_a, mint_b) = pool_with_amounts(offset, 1_000_000, 0);
+
+    // selling B is priced against the offset, the same as a 1:1 constant product pool
+    let (cp, cp_a, cp_b) = pool_with_amounts(r#""curveType":0"#, 1_000_000, 1_000_000);
+    let quote = pool.get_quote_with_amounts_scaled(10_000, &mint_b, &mint_a).unwrap();
+    assert!(quote > 0);
+    assert_eq!(quote, cp.get_quote_with_amounts_scaled(10_000, &cp_b, &cp_a).unwrap());
+
+    // the offset can't be paid out -- there's no B to buy
+    assert_eq!(
+        pool.get_quote_with_amounts_scaled(10_000, &mint_a, &mint_b),
+        Err(QuoteError::InsufficientLiquidity)
+    );
+}
+
+#[test]
+fn offset_curve_quotes_are_capped_by_the_real_vault() {
+    // 1M of virtual B, only 100 real
+    let offset = r#""curveType":3,"tokenBOffset":1000000"#;
+    let (pool, mint_a, mint_b) = pool_with_amounts(offset, 1_000_000, 100);
+
+    // the curve prices ~9.9k B out, the vault pays 100 at most
+    assert_eq!(
+        pool.get_quote_with_amounts_scaled(10_000, &mint_a, &mint_b),
+        Err(QuoteError::InsufficientLiquidity)
+    );
+    let small = pool.get_quote_with_amounts_scaled(100, &mint_a, &mint_b).unwrap();
+    assert!(small > 0 && small <= 100);
+}
+
+#[test]
+fn unknown_curve_types_fail_the_quote() {
+    let (pool, mint_a, mint_b) = pool_with_amounts(r#""curveType":9"#, 1_000_000, 1_000_000);
+    assert!(matches!(
+        pool.get_quote_with_amounts_scaled(1_000, &mint_a, &mint_b),
+        Err(QuoteError::StaleState(_))
+    ));
+}
+
+#[test]
+fn missing_curve_params_fail_the_quote() {
+    // a ConstantPrice pool without its price can't quote -- no panic, no 0
+    let (pool, mint_a, mint_b) = pool_with_amounts(r#""curveType":1"#, 1_000_000, 1_000_000);
+    assert_eq!(pool.get_quote_with_amounts_scaled(1_000, &mint_a, &mint_b), Err(QuoteError::MathOverflow));
+}
+
+#[test]
+fn quotes_fail_on_unset_state_and_foreign_mints() {
+    let (pool, mint_a, _) = pool_with_amounts(r#""curveType":0"#, 1_000_000, 1_000_000);
+    let other = Pubkey::new_unique();
+    assert_eq!(
+        pool.get_quote_with_amounts_scaled(1_000, &mint_a, &other),
+        Err(QuoteError::UnsupportedDirection { mint_in: mint_a, mint_out: other })
+    );
+    assert_eq!(
+        pool.get_quote_with_amounts_scaled(1_000, &mint_a, &mint_a),
+        Err(QuoteError::UnsupportedDirection { mint_in: mint_a, mint_out: mint_a })
+    );
+    assert_eq!(pool.mint_2_addr(&other), Err(QuoteError::UnknownMint(other)));
+
+    // drained on one side
+    let (empty, mint_a, mint_b) = pool_with_amounts(r#""curveType":0"#, 1_000_000, 0);
+    assert_eq!(
+        empty.get_quote_with_amounts_scaled(1_000, &mint_a, &mint_b),
+        Err(QuoteError::InsufficientLiquidity)
+    );
+
+    // accounts never set
+    let json = pool_json(&mint_a, &mint_b, r#""curveType":0"#);
+    let unset: OrcaPool = serde_json::from_str(&json).unwrap();
+    assert!(matches!(
+        unset.get_quote_with_amounts_scaled(1_000, &mint_a, &mint_b),
+        Err(QuoteError::StaleState(_))
+    ));
+}
+
+#[test]
+fn vault_updates_are_all_or_nothing() {
+    let (mut pool, mint_a, mint_b) = pool_with_amounts(r#""curveType":0"#, 1_000_000, 1_000_000);
+    let before = pool.get_quote_with_amounts_scaled(1_000, &mint_a, &mint_b);
+
+    // one vault missing, the other one not a token account
+    let short = Account { data: vec![0; 10], ..Account::default() };
+    let update = pool.set_update_accounts(vec![None, Some(short)], Cluster::Localnet);
+    assert!(matches!(update, Err(QuoteError::StaleState(_))));
+    assert_eq!(pool.get_quote_with_amounts_scaled(1_000, &mint_a, &mint_b), before);
+}
@@ -0,0 +1,62 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_lang::Event;
+
+use tmp::events::{HopExecuted, RouteCompleted, RouteStarted};
+use tmp::ix_data::DexKind;
+
+use crate::constants::*;
+use crate::events::{parse_route_events, RouteEvent, RouteReport};
+
+fn data_log(event: &impl Event) -> String {
+    format!("Program data: {}", base64::encode(event.data()))
+}
+
+fn route_logs(authority: Pubkey, mint: Pubkey) -> Vec<String> {
+    let arb = ARB_PROGRAM_ID.to_string();
+    let orca = ORCA_PROGRAM_ID.to_string();
+    vec![
+        format!("Program {} invoke [1]", arb),
+        data_log(&RouteStarted { authority, mint, swap_input: 100, flash_borrowed: 0 }),
+        // same layout logged by the DEX is not ours
+        format!("Program {} invoke [2]", orca),
+        data_log(&HopExecuted { authority, dex: DexKind::Saber, amount_in: 1, amount_out: 1 }),
+        format!("Program {} success", orca),
+        data_log(&HopExecuted { authority, dex: DexKind::Orca, amount_in: 100, amount_out: 210 }),
+        data_log(&HopExecuted { authority, dex: DexKind::Orca, amount_in: 210, amount_out: 103 }),
+        data_log(&RouteCompleted { authority, mint, profit: 3 }),
+        format!("Program {} success", arb),
+    ]
+}
+
+#[test]
+fn events_of_the_arb_program_are_decoded() {
+    let (authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let events = parse_route_events(&ARB_PROGRAM_ID, &route_logs(authority, mint));
+
+    assert_eq!(events.len(), 4);
+    assert!(matches!(&events[0], RouteEvent::Started(e) if e.mint == mint && e.swap_input == 100));
+    assert!(matches!(&events[1], RouteEvent::Hop(e) if e.dex == DexKind::Orca && e.amount_out == 210));
+    assert!(matches!(&events[3], RouteEvent::Completed(e) if e.profit == 3));
+}
+
+#[test]
+fn report_of_a_route() {
+    let (authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let events = parse_route_events(&ARB_PROGRAM_ID, &route_logs(authority, mint));
+    let reports = RouteReport::from_events(&events);
+
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].mint, Some(mint));
+    assert_eq!(reports[0].hops.len(), 2);
+    assert_eq!(reports[0].profit, Some(3));
+}
+
+#[test]
+fn failed_route_has_no_profit() {
+    let (authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut logs = route_logs(authority, mint);
+    logs.truncate(7); // reverted before the profit check
+    let reports = RouteReport::from_events(&parse_route_events(&ARB_PROGRAM_ID, &logs));
+
+    assert_eq!(reports[0].profit, None);
+}
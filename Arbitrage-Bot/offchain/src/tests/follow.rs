@@ -0,0 +1,100 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signature;
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use yellowstone_grpc_proto::prelude::{
+    Message, SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, TokenBalance, Transaction,
+    TransactionStatusMeta, UiTokenAmount,
+};
+
+use crate::follow::{project_token_balances, route_touches, TxTrigger};
+use crate::schedule::RouteCandidate;
+use crate::serialize::token::unpack_token_account;
+use crate::tests::curves::pool_with_amounts;
+use crate::utils::PoolQuote;
+
+fn token_account(mint: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; 165];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    Account { lamports: 2_039_280, data, ..Account::default() }
+}
+
+fn balance(account_index: u32, amount: &str) -> TokenBalance {
+    TokenBalance {
+        account_index,
+        ui_token_amount: Some(UiTokenAmount { amount: amount.to_string(), ..UiTokenAmount::default() }),
+        ..TokenBalance::default()
+    }
+}
+
+#[test]
+fn balances_index_static_then_loaded_keys() {
+    let (payer, vault, loaded_vault) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let update = SubscribeUpdateTransaction {
+        transaction: Some(SubscribeUpdateTransactionInfo {
+            signature: vec![7; 64],
+            transaction: Some(Transaction {
+                message: Some(Message {
+                    account_keys: vec![payer.to_bytes().to_vec(), vault.to_bytes().to_vec()],
+                    ..Message::default()
+                }),
+                ..Transaction::default()
+            }),
+            meta: Some(TransactionStatusMeta {
+                post_token_balances: vec![balance(1, "500"), balance(2, "42"), balance(3, "1")],
+                loaded_writable_addresses: vec![loaded_vault.to_bytes().to_vec()],
+                ..TransactionStatusMeta::default()
+            }),
+            ..SubscribeUpdateTransactionInfo::default()
+        }),
+        slot: 99,
+    };
+
+    let trigger = TxTrigger::from_update(update).unwrap();
+    assert_eq!(trigger.signature, Signature::new(&[7; 64]));
+    assert_eq!(trigger.slot, 99);
+    // index 3 is past the keys
+    assert_eq!(trigger.token_balances, vec![(vault, 500), (loaded_vault, 42)]);
+}
+
+#[test]
+fn vaults_are_projected_to_their_post_tx_amount() {
+    let (mint, vault, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let mut latest = HashMap::new();
+    latest.insert(vault, token_account(&mint, 1_000));
+
+    let trigger = TxTrigger {
+        signature: Signature::default(),
+        slot: 12,
+        token_balances: vec![(vault, 1_250), (other, 5)], // `other` isn't ours
+    };
+    let updates = project_token_balances(&latest, &trigger);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].pubkey, vault);
+    assert_eq!(updates[0].slot, 12);
+
+    let projected = unpack_token_account(&updates[0].account.as_ref().unwrap().data);
+    assert_eq!(projected.amount, 1_250);
+    assert_eq!(projected.mint, mint);
+}
+
+#[test]
+fn routes_are_kept_when_they_go_through_a_moved_vault() {
+    let (pool, _, _) = pool_with_amounts(r#""curveType":0"#, 1_000_000, 1_000_000);
+    let vaults = pool.get_update_accounts();
+    let route = RouteCandidate {
+        mint_idxs: vec![0, 1, 0],
+        pools: vec![PoolQuote::new(Rc::new(pool))],
+        amount_in: 1,
+        amount_out: 2,
+    };
+
+    let moved: HashSet<Pubkey> = [vaults[1]].into_iter().collect();
+    assert!(route_touches(&route, &moved));
+    let elsewhere: HashSet<Pubkey> = [Pubkey::new_unique()].into_iter().collect();
+    assert!(!route_touches(&route, &elsewhere));
+}
@@ -0,0 +1,144 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use std::time::{Duration, Instant};
+
+use tmp::events::HopExecuted;
+use tmp::ix_data::DexKind;
+
+use crate::events::RouteReport;
+use crate::health::{BreakerConfig, PoolHealth, PoolId};
+
+fn health() -> PoolHealth {
+    PoolHealth::new(BreakerConfig {
+        max_failures: 3,
+        max_hop_deviation_bps: 100,
+        backoff: Duration::from_secs(10),
+        max_backoff: Duration::from_secs(25),
+    })
+}
+
+fn pool(label: &str) -> (PoolId, String) {
+    (PoolId(vec![Pubkey::new_unique(), Pubkey::new_unique()]), label.to_string())
+}
+
+#[test]
+fn consecutive_failures_disable_a_pool() {
+    let mut health = health();
+    let (a, b) = (pool("a"), pool("b"));
+    let now = Instant::now();
+
+    health.record_failure(&[a.clone(), b.clone()], now);
+    health.record_failure(&[a.clone(), b.clone()], now);
+    // a success through b clears its streak
+    health.record_success(&[b.clone()]);
+    health.record_failure(&[a.clone(), b.clone()], now);
+
+    assert!(!health.is_enabled(&a.0, now));
+    assert!(health.is_enabled(&b.0, now));
+    assert!(health.is_enabled(&a.0, now + Duration::from_secs(10)));
+
+    let summary = health.summary(now);
+    assert_eq!(summary.disabled, vec![("a".to_string(), Duration::from_secs(10))]);
+    assert_eq!(summary.failing, 1); // b
+}
+
+#[test]
+fn backoff_doubles_on_probation() {
+    let mut health = health();
+    let a = pool("a");
+    let mut now = Instant::now();
+    for _ in 0..3 {
+        health.record_failure(&[a.clone()], now);
+    }
+    assert!(!health.is_enabled(&a.0, now + Duration::from_secs(9)));
+
+    // one failure after it's back is enough, for twice as long
+    now += Duration::from_secs(10);
+    health.record_failure(&[a.clone()], now);
+    assert!(!health.is_enabled(&a.0, now + Duration::from_secs(19)));
+    assert!(health.is_enabled(&a.0, now + Duration::from_secs(20)));
+
+    // capped
+    now += Duration::from_secs(20);
+    health.record_failure(&[a.clone()], now);
+    assert!(health.is_enabled(&a.0, now + Duration::from_secs(25)));
+
+    // a success clears the probation
+    now += Duration::from_secs(25);
+    health.record_success(&[a.clone()]);
+    health.record_failure(&[a.clone()], now);
+    assert!(health.is_enabled(&a.0, now));
+}
+
+#[test]
+fn hops_off_their_quote_disable_their_pools() {
+    let mut health = health();
+    let (a, b) = (pool("a"), pool("b"));
+    let now = Instant::now();
+
+    assert!(!health.record_hop(&[a.clone()], 1_000_000, 990_000, now)); // 100 bps
+    assert!(health.is_enabled(&a.0, now));
+
+    // paying more than quoted is fine
+    assert!(!health.record_hop(&[a.clone()], 1_000_000, 1_020_000, now));
+    assert!(health.is_enabled(&a.0, now));
+
+    assert!(health.record_hop(&[a.clone(), b.clone()], 1_000_000, 980_000, now));
+    assert!(!health.is_enabled(&a.0, now));
+    assert!(!health.is_enabled(&b.0, now));
+}
+
+#[test]
+fn stats_count_failures_and_disables() {
+    let mut health = health();
+    let (a, b) = (pool("a"), pool("b"));
+    let now = Instant::now();
+
+    for _ in 0..3 {
+        health.record_failure(&[a.clone()], now);
+    }
+    health.record_hop(&[b.clone()], 1_000, 900, now);
+
+    let stats = health.stats(now);
+    assert_eq!(stats.len(), 2);
+    assert_eq!((stats[0].label.as_str(), stats[0].total_failures, stats[0].disables), ("a", 3, 1));
+    assert_eq!(stats[0].failures, 0); // reset by the disable
+    assert_eq!(stats[0].disabled_secs, Some(10));
+    assert_eq!((stats[1].label.as_str(), stats[1].short_hops, stats[1].disables), ("b", 1, 1));
+
+    // back on, the counters stay
+    let later = health.stats(now + Duration::from_secs(10));
+    assert_eq!(later[0].disabled_secs, None);
+    assert_eq!(later[0].total_failures, 3);
+}
+
+#[test]
+fn split_hops_are_checked_on_the_sum_of_their_swaps() {
+    let mut health = health();
+    let (a, b, c, d, e) = (pool("a"), pool("b"), pool("c"), pool("d"), pool("e"));
+    let now = Instant::now();
+
+    // hop 1 is split across b, c + e -- e's share rounded to 0 so it has no event
+    let swap = |amount_in, amount_out| HopExecuted { authority: Pubkey::default(), dex: DexKind::Orca, amount_in, amount_out };
+    let report = RouteReport {
+        swap_input: 1_000,
+        hops: vec![swap(1_000, 2_000), swap(1_500, 1_480), swap(500, 495), swap(1_975, 1_010)],
+        profit: Some(10),
+        ..RouteReport::default()
+    };
+    let hops = vec![(vec![a.clone()], 2_000), (vec![b.clone(), c.clone(), e.clone()], 1_975), (vec![d.clone()], 1_010)];
+    let hop_pools: Vec<usize> = hops.iter().map(|(pools, _)| pools.len()).collect();
+
+    let outputs = report.hop_outputs(&hop_pools);
+    assert_eq!(outputs, vec![2_000, 1_975, 1_010]);
+    for ((pools, quote), amount_out) in hops.iter().zip(outputs) {
+        assert!(!health.record_hop(pools, *quote, amount_out as u128, now));
+    }
+    for pool in [a, b, c, d, e] {
+        assert!(health.is_enabled(&pool.0, now));
+    }
+
+    // reverted in the split hop: only the hop before is reported
+    let reverted = RouteReport { hops: report.hops[..2].to_vec(), profit: None, ..report };
+    assert_eq!(reverted.hop_outputs(&hop_pools), vec![2_000]);
+}
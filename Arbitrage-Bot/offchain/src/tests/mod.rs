@@ -1 +1,15 @@
-pub mod quotes;
\ No newline at end of file
+pub mod common; // mock pools shared by the suites
+pub mod quotes;
+pub mod events;
+pub mod open_orders;
+pub mod split;
+pub mod curves;
+pub mod backtest;
+pub mod source;
+pub mod registry;
+pub mod health;
+pub mod schedule;
+pub mod follow;
+pub mod router;
+#[cfg(test)] // proptest is a dev-dependency
+pub mod properties;
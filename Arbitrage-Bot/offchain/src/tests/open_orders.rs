@@ -0,0 +1,37 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use crate::serialize::open_orders::{unpack_open_orders, OPEN_ORDERS_OWNER_OFFSET, OPEN_ORDERS_SPAN};
+
+fn open_orders_data(market: &Pubkey, owner: &Pubkey, coin_free: u64, coin_total: u64, free_slot_bits: u128) -> Vec<u8> {
+    let mut data = vec![0; OPEN_ORDERS_SPAN];
+    data[13..45].copy_from_slice(market.as_ref());
+    data[OPEN_ORDERS_OWNER_OFFSET..77].copy_from_slice(owner.as_ref());
+    data[77..85].copy_from_slice(&coin_free.to_le_bytes());
+    data[85..93].copy_from_slice(&coin_total.to_le_bytes());
+    data[109..125].copy_from_slice(&free_slot_bits.to_le_bytes());
+    data
+}
+
+#[test]
+fn unpack_open_orders_layout() {
+    let (market, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let oo = unpack_open_orders(&open_orders_data(&market, &owner, 5, 7, !0 << 2));
+
+    assert_eq!(oo.market, market);
+    assert_eq!(oo.owner, owner);
+    assert_eq!(oo.native_coin_free, 5);
+    assert_eq!(oo.native_coin_total, 7);
+    assert_eq!(oo.order_count(), 2);
+    assert!(oo.has_free_funds());
+    assert!(!oo.can_close());
+}
+
+#[test]
+fn empty_open_orders_can_close() {
+    let (market, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let oo = unpack_open_orders(&open_orders_data(&market, &owner, 0, 0, u128::MAX));
+
+    assert_eq!(oo.order_count(), 0);
+    assert!(!oo.has_free_funds());
+    assert!(oo.can_close());
+}
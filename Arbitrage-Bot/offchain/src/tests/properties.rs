@@ -0,0 +1,393 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use proptest::prelude::*;
+
+use std::collections::HashMap;
+
+use crate::pool::PoolOperations;
+use crate::pools::{AldrinPool, MercurialPool, SaberPool};
+use crate::pool_utils::serum::{
+    ask_quote, ask_quote_exact_out, bid_quote, bid_quote_exact_out, BookOrder, FeeTier,
+};
+use crate::serialize::pool::{Fraction, JSONFeeStructure};
+use crate::serialize::token::{Token, WrappedPubkey};
+use crate::tests::curves::pool_with_amounts;
+
+// every quoter: more in never gets less out, the output fits in the
+// destination reserve, and swapping the output back on the same state
+// never returns more than went in -- a failed quote is a skipped edge, only
+// the ones which succeed are checked
+
+fn check_pool(pool: &dyn PoolOperations, mint_a: &Pubkey, mint_b: &Pubkey, reserve_b: u128, amount: u128, more: u128) {
+    let out = match pool.get_quote_with_amounts_scaled(amount, mint_a, mint_b) {
+        Ok(out) => out,
+        Err(_) => return,
+    };
+    assert!(out <= reserve_b, "quote {} > reserve {}", out, reserve_b);
+    if let Ok(out_more) = pool.get_quote_with_amounts_scaled(amount.saturating_add(more), mint_a, mint_b) {
+        assert!(out <= out_more, "not monotone: {} > {}", out, out_more);
+        assert!(out_more <= reserve_b, "quote {} > reserve {}", out_more, reserve_b);
+    }
+
+    if let Ok(back) = pool.get_quote_with_amounts_scaled(out, mint_b, mint_a) {
+        assert!(back <= amount, "round trip gained: {} -> {} -> {}", amount, out, back);
+    }
+}
+
+// exact out is checked against the forward quote: the input buys at least
+// `amount_out` and one unit less doesn't -- Ok(amount in) of the pool
+fn check_exact_out(pool: &dyn PoolOperations, mint_in: &Pubkey, mint_out: &Pubkey, amount_out: u128) -> Option<u128> {
+    let amount_in = pool.get_quote_exact_out(amount_out, mint_in, mint_out).ok()?;
+    let buys = |amount_in| pool
+        .get_quote_with_amounts_scaled(amount_in, mint_in, mint_out)
+        .map_or(false, |out| out >= amount_out);
+    assert!(buys(amount_in), "{} in doesn't buy {}", amount_in, amount_out);
+    assert!(amount_in == 0 || !buys(amount_in - 1), "{} in isn't the least for {}", amount_in, amount_out);
+    Some(amount_in)
+}
+
+fn token(mint: &Pubkey) -> Token {
+    Token {
+        tag: "T".to_string(),
+        name: "T".to_string(),
+        mint: WrappedPubkey(*mint),
+        scale: 6,
+        addr: WrappedPubkey(Pubkey::new_unique()),
+    }
+}
+
+fn key() -> WrappedPubkey {
+    WrappedPubkey(Pubkey::new_unique())
+}
+
+fn mints() -> (Pubkey, Pubkey) {
+    (Pubkey::new_unique(), Pubkey::new_unique())
+}
+
+fn saber_pool(amp: u64, fee_numerator: u64, reserves: [u128; 2]) -> (SaberPool, Pubkey, Pubkey) {
+    let (mint_a, mint_b) = mints();
+    let pool = SaberPool {
+        pool_account: key(),
+        authority: key(),
+        pool_token_mint: key(),
+        token_ids: vec![mint_a.to_string(), mint_b.to_string()],
+        tokens: HashMap::from([(mint_a.to_string(), token(&mint_a)), (mint_b.to_string(), token(&mint_b))]),
+        target_amp: amp,
+        fee_numerator,
+        fee_denominator: 10_000,
+        fee_accounts: HashMap::new(),
+        pool_amounts: HashMap::from([(mint_a.to_string(), reserves[0]), (mint_b.to_string(), reserves[1])]),
+    };
+    (pool, mint_a, mint_b)
+}
+
+fn mercurial_pool(amp: u64, multipliers: [u64; 2], reserves: [u128; 2]) -> (MercurialPool, Pubkey, Pubkey) {
+    let (mint_a, mint_b) = mints();
+    let pool = MercurialPool {
+        pool_account: key(),
+        pool_token_mint: key(),
+        authority: key(),
+        token_ids: vec![mint_a.to_string(), mint_b.to_string()],
+        tokens: HashMap::from([(mint_a.to_string(), token(&mint_a)), (mint_b.to_string(), token(&mint_b))]),
+        amp,
+        fee_numerator: 4_000_000, // 4 bps of 1e10
+        admin_numerator: 0,
+        precision_factor: 1,
+        precision_multiplier: multipliers.to_vec(),
+        pool_amounts: HashMap::from([(mint_a.to_string(), reserves[0]), (mint_b.to_string(), reserves[1])]),
+    };
+    (pool, mint_a, mint_b)
+}
+
+fn aldrin_pool(curve_type: u8, reserves: [u128; 2]) -> (AldrinPool, Pubkey, Pubkey) {
+    let (mint_a, mint_b) = mints();
+    let pool = AldrinPool {
+        lp_token_freeze_vault: key(),
+        pool_mint: key(),
+        pool_signer: key(),
+        pool_signer_nonce: 0,
+        authority: key(),
+        initializer_account: key(),
+        fee_base_account: key(),
+        fee_quote_account: key(),
+        fee_pool_token_account: key(),
+        token_ids: vec![mint_a.to_string(), mint_b.to_string()],
+        tokens: HashMap::from([(mint_a.to_string(), token(&mint_a)), (mint_b.to_string(), token(&mint_b))]),
+        fees: JSONFeeStructure {
+            trader_fee: Fraction { numerator: 25, denominator: 10_000 },
+            owner_fee: Fraction { numerator: 5, denominator: 10_000 },
+        },
+        curve_type,
+        curve: key(),
+        pool_public_key: key(),
+        pool_version: 2,
+        pool_amounts: HashMap::from([(mint_a.to_string(), reserves[0]), (mint_b.to_string(), reserves[1])]),
+    };
+    (pool, mint_a, mint_b)
+}
+
+// asks ascending from `mid` + 1, bids descending from `mid` -- never crossed
+fn book(mid: u64, asks: &[(u64, u64)], bids: &[(u64, u64)]) -> (Vec<BookOrder>, Vec<BookOrder>) {
+    let mut ask_price = mid;
+    let asks = asks.iter().map(|&(step, quantity)| {
+        ask_price = ask_price.saturating_add(step + 1);
+        BookOrder { price: ask_price, quantity }
+    }).collect();
+    let mut bid_price = mid + 1;
+    let bids = bids.iter().map(|&(step, quantity)| {
+        bid_price = bid_price.saturating_sub(step + 1);
+        BookOrder { price: bid_price, quantity }
+    }).filter(|order| order.price > 0).collect();
+    (asks, bids)
+}
+
+proptest! {
+    #[test]
+    fn orca_constant_product(
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount in 0..u64::MAX,
+        more in 0..u64::MAX,
+    ) {
+        let (pool, mint_a, mint_b) = pool_with_amounts(r#""curveType":0"#, reserve_a as u128, reserve_b as u128);
+        check_pool(pool.as_ref(), &mint_a, &mint_b, reserve_b as u128, amount as u128, more as u128);
+    }
+
+    #[test]
+    fn orca_stable(
+        amp in 1..10_000u64,
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount in 0..u64::MAX,
+        more in 0..u64::MAX,
+    ) {
+        let curve = format!(r#""curveType":2,"amp":{}"#, amp);
+        let (pool, mint_a, mint_b) = pool_with_amounts(&curve, reserve_a as u128, reserve_b as u128);
+        check_pool(pool.as_ref(), &mint_a, &mint_b, reserve_b as u128, amount as u128, more as u128);
+    }
+
+    #[test]
+    fn orca_constant_price(
+        price in 1..u32::MAX,
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount in 0..u64::MAX,
+        more in 0..u64::MAX,
+    ) {
+        let curve = format!(r#""curveType":1,"tokenBPrice":{}"#, price);
+        let (pool, mint_a, mint_b) = pool_with_amounts(&curve, reserve_a as u128, reserve_b as u128);
+        // a fixed price has no slippage -- past the reserve the swap fails
+        check_pool(pool.as_ref(), &mint_a, &mint_b, reserve_b as u128, amount as u128, more as u128);
+    }
+
+    #[test]
+    fn orca_offset(
+        offset in 1..u64::MAX,
+        reserve_a in 1..u64::MAX,
+        reserve_b in 0..u64::MAX,
+        amount in 0..u64::MAX,
+        more in 0..u64::MAX,
+    ) {
+        let curve = format!(r#""curveType":3,"tokenBOffset":{}"#, offset);
+        let (pool, mint_a, mint_b) = pool_with_amounts(&curve, reserve_a as u128, reserve_b as u128);
+        // the offset is priced but can't be paid out -- quotes past the real reserve fail
+        check_pool(pool.as_ref(), &mint_a, &mint_b, reserve_b as u128, amount as u128, more as u128);
+    }
+
+    #[test]
+    fn aldrin(
+        stable in any::<bool>(),
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount in 0..u64::MAX,
+        more in 0..u64::MAX,
+    ) {
+        let (pool, mint_a, mint_b) = aldrin_pool(stable as u8, [reserve_a as u128, reserve_b as u128]);
+        check_pool(&pool, &mint_a, &mint_b, reserve_b as u128, amount as u128, more as u128);
+    }
+
+    #[test]
+    fn saber(
+        amp in 1..10_000u64,
+        fee_numerator in 0..10_000u64,
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount in 0..u64::MAX,
+        more in 0..u64::MAX,
+    ) {
+        let (pool, mint_a, mint_b) = saber_pool(amp, fee_numerator, [reserve_a as u128, reserve_b as u128]);
+        check_pool(&pool, &mint_a, &mint_b, reserve_b as u128, amount as u128, more as u128);
+    }
+
+    #[test]
+    fn mercurial(
+        amp in 1..10_000u64,
+        multiplier_a in 1..1_000_000u64,
+        multiplier_b in 1..1_000_000u64,
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount in 0..u64::MAX,
+        more in 0..u64::MAX,
+    ) {
+        let (pool, mint_a, mint_b) = mercurial_pool(amp, [multiplier_a, multiplier_b], [reserve_a as u128, reserve_b as u128]);
+        check_pool(&pool, &mint_a, &mint_b, reserve_b as u128, amount as u128, more as u128);
+    }
+
+    #[test]
+    fn stable_quotes_never_panic(
+        amp in any::<u64>(),
+        fee_numerator in any::<u64>(),
+        multiplier_a in any::<u64>(),
+        multiplier_b in any::<u64>(),
+        reserve_a in any::<u64>(),
+        reserve_b in any::<u64>(),
+        amount in any::<u64>(),
+    ) {
+        let reserves = [reserve_a as u128, reserve_b as u128];
+        // an error at most
+        let (pool, mint_a, mint_b) = saber_pool(amp, fee_numerator, reserves);
+        let _ = pool.get_quote_with_amounts_scaled(amount as u128, &mint_a, &mint_b);
+
+        let (pool, mint_a, mint_b) = mercurial_pool(amp, [multiplier_a, multiplier_b], reserves);
+        let _ = pool.get_quote_with_amounts_scaled(amount as u128, &mint_a, &mint_b);
+    }
+
+    #[test]
+    fn serum_orderbook(
+        mid in 1..1_000_000u64,
+        asks in prop::collection::vec((0..1_000u64, 1..1_000_000u64), 0..20),
+        bids in prop::collection::vec((0..1_000u64, 1..1_000_000u64), 0..20),
+        coin_lot_size in 1..1_000_000u64,
+        pc_lot_size in 1..1_000u64,
+        amount in 0..u64::MAX,
+        more in 0..u64::MAX,
+    ) {
+        let (asks, bids) = book(mid, &asks, &bids);
+        let fee_tier = FeeTier::Base;
+
+        // buying base is bounded by the depth of the asks
+        let depth = asks.iter().fold(0u64, |depth, ask| depth.saturating_add(ask.quantity.saturating_mul(coin_lot_size)));
+        let out = bid_quote(asks.clone(), coin_lot_size, pc_lot_size, fee_tier, amount);
+        let out_more = bid_quote(asks.clone(), coin_lot_size, pc_lot_size, fee_tier, amount.saturating_add(more));
+        prop_assert!(out <= out_more);
+        prop_assert!(out_more <= depth);
+
+        // selling it straight back hits the lower bids
+        let back = ask_quote(bids.clone(), coin_lot_size, pc_lot_size, fee_tier, out);
+        prop_assert!(back <= amount);
+
+        let sold = ask_quote(bids.clone(), coin_lot_size, pc_lot_size, fee_tier, amount);
+        let sold_more = ask_quote(bids, coin_lot_size, pc_lot_size, fee_tier, amount.saturating_add(more));
+        prop_assert!(sold <= sold_more);
+    }
+
+    #[test]
+    fn serum_orderbook_never_panics(
+        orders in prop::collection::vec((any::<u64>(), any::<u64>()), 0..20),
+        coin_lot_size in any::<u64>(),
+        pc_lot_size in any::<u64>(),
+        amount in any::<u64>(),
+    ) {
+        let orders: Vec<BookOrder> = orders
+            .into_iter()
+            .map(|(price, quantity)| BookOrder { price, quantity })
+            .collect();
+        bid_quote(orders.clone(), coin_lot_size, pc_lot_size, FeeTier::Base, amount);
+        ask_quote(orders, coin_lot_size, pc_lot_size, FeeTier::Base, amount);
+    }
+
+    #[test]
+    fn orca_exact_out(
+        curve_type in 0..4u8,
+        param in 1..u32::MAX,
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount_out in 1..u64::MAX,
+        a_to_b in any::<bool>(),
+    ) {
+        let curve = match curve_type {
+            0 => r#""curveType":0"#.to_string(),
+            1 => format!(r#""curveType":1,"tokenBPrice":{}"#, param),
+            2 => format!(r#""curveType":2,"amp":{}"#, param % 10_000 + 1),
+            _ => format!(r#""curveType":3,"tokenBOffset":{}"#, param),
+        };
+        let (pool, mint_a, mint_b) = pool_with_amounts(&curve, reserve_a as u128, reserve_b as u128);
+        let (mint_in, mint_out, reserve_out) = if a_to_b {
+            (mint_a, mint_b, reserve_b as u128)
+        } else {
+            (mint_b, mint_a, reserve_a as u128)
+        };
+        let amount_in = check_exact_out(pool.as_ref(), &mint_in, &mint_out, amount_out as u128);
+        // x * y = k can always sell half its reserve
+        if curve_type == 0 && amount_out as u128 <= reserve_out / 2 {
+            prop_assert!(amount_in.is_some());
+        }
+    }
+
+    #[test]
+    fn aldrin_exact_out(
+        stable in any::<bool>(),
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount_out in 1..u64::MAX,
+    ) {
+        let (pool, mint_a, mint_b) = aldrin_pool(stable as u8, [reserve_a as u128, reserve_b as u128]);
+        check_exact_out(&pool, &mint_a, &mint_b, amount_out as u128);
+    }
+
+    #[test]
+    fn saber_exact_out(
+        amp in 1..10_000u64,
+        fee_numerator in 0..10_000u64,
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount_out in 1..u64::MAX,
+    ) {
+        let (pool, mint_a, mint_b) = saber_pool(amp, fee_numerator, [reserve_a as u128, reserve_b as u128]);
+        check_exact_out(&pool, &mint_a, &mint_b, amount_out as u128);
+    }
+
+    #[test]
+    fn mercurial_exact_out(
+        amp in 1..10_000u64,
+        multiplier_a in 1..1_000_000u64,
+        multiplier_b in 1..1_000_000u64,
+        reserve_a in 1..u64::MAX,
+        reserve_b in 1..u64::MAX,
+        amount_out in 1..u64::MAX,
+    ) {
+        let (pool, mint_a, mint_b) = mercurial_pool(amp, [multiplier_a, multiplier_b], [reserve_a as u128, reserve_b as u128]);
+        check_exact_out(&pool, &mint_a, &mint_b, amount_out as u128);
+    }
+
+    #[test]
+    fn serum_orderbook_exact_out(
+        mid in 1..1_000_000u64,
+        asks in prop::collection::vec((0..1_000u64, 1..1_000_000u64), 0..20),
+        bids in prop::collection::vec((0..1_000u64, 1..1_000_000u64), 0..20),
+        coin_lot_size in 1..1_000_000u64,
+        pc_lot_size in 1..1_000u64,
+        amount_out in 0..u64::MAX,
+    ) {
+        let (asks, bids) = book(mid, &asks, &bids);
+        let fee_tier = FeeTier::Base;
+
+        // buying base: any amount up to the depth of the asks, whole lots
+        let depth = asks.iter().fold(0u64, |depth, ask| depth.saturating_add(ask.quantity.saturating_mul(coin_lot_size)));
+        let base_out = amount_out % depth.saturating_add(1);
+        let cost = bid_quote_exact_out(asks.clone(), coin_lot_size, pc_lot_size, fee_tier, base_out);
+        prop_assert!(cost.is_some());
+        prop_assert!(bid_quote(asks, coin_lot_size, pc_lot_size, fee_tier, cost.unwrap()) >= base_out);
+
+        // selling it: the fewest lots, for as much as the bids pay
+        let lots: u64 = bids.iter().map(|bid| bid.quantity).sum();
+        let sold_all = ask_quote(bids.clone(), coin_lot_size, pc_lot_size, fee_tier, lots * coin_lot_size);
+        let sell = ask_quote_exact_out(bids.clone(), coin_lot_size, pc_lot_size, fee_tier, amount_out);
+        prop_assert_eq!(sell.is_some(), amount_out <= sold_all);
+        if let Some(sell) = sell {
+            prop_assert!(ask_quote(bids.clone(), coin_lot_size, pc_lot_size, fee_tier, sell) >= amount_out);
+            if sell > 0 {
+                prop_assert!(ask_quote(bids, coin_lot_size, pc_lot_size, fee_tier, sell - 1) < amount_out);
+            }
+        }
+    }
+}
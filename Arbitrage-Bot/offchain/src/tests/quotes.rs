@@ -14,7 +14,7 @@ use std::vec;
 use tmp::accounts as tmp_accounts;
 use tmp::instruction as tmp_ix;
 
-use crate::utils::{derive_token_address, read_json_dir};
+use crate::utils::{derive_token_address, derive_swap_state_address, derive_mint_stats_address, read_json_dir};
 use crate::pool::{PoolType, PoolOperations, pool_factory};
 use crate::constants::*;
 
@@ -113,13 +113,17 @@ fn test_pool_quote(
     let accounts = connection
             .get_multiple_accounts(&update_accounts)
             .unwrap();
-    pool.set_update_accounts(accounts, Cluster::Localnet);
+    if let Err(err) = pool.set_update_accounts(accounts, Cluster::Localnet) {
+        println!("pool path: {}", pool_path);
+        println!("update error: {}", err);
+        return 1;
+    }
 
     // get a quote 
     let pool_mints = pool.get_mints(); 
     let mint_in = &pool_mints[0];
     let mint_out = &pool_mints[1];
-    let src_scale = pool.mint_2_scale(mint_in);
+    let src_scale = pool.mint_2_scale(mint_in).unwrap();
 
     let src_ata = derive_token_address(&owner.pubkey(), mint_in);
     let _dst_ata = derive_token_address(&owner.pubkey(), mint_out);
@@ -147,11 +151,18 @@ fn test_pool_quote(
     let mut loop_count = 0; 
     let mut quote_out_amount;
     loop {
-        quote_out_amount = pool.get_quote_with_amounts_scaled(
+        quote_out_amount = match pool.get_quote_with_amounts_scaled(
             amount_in, 
             mint_in, 
             mint_out
-        );
+        ) {
+            Ok(quote_out_amount) => quote_out_amount,
+            Err(err) => {
+                println!("pool path: {}", pool_path);
+                println!("quote error: {}", err);
+                return 1;
+            }
+        };
         // println!("quote: {}", quote_out_amount);
 
         if quote_out_amount == 0 {
@@ -213,8 +224,8 @@ fn test_pool_quote(
 
     }
 
-    let (swap_state_pda, _) = Pubkey::find_program_address(
-        &[b"swap_state"], &program.id());
+    let swap_state_pda = derive_swap_state_address(&program.id(), &owner.pubkey());
+    let mint_stats_pda = derive_mint_stats_address(&program.id(), &owner.pubkey(), mint_in);
 
     // initialize swap 
     let ix = program
@@ -222,9 +233,12 @@ fn test_pool_quote(
         .accounts(tmp_accounts::TokenAndSwapState {
             swap_state: swap_state_pda,
             src: src_ata,
+            mint_stats: mint_stats_pda,
+            authority: owner.pubkey(),
         })
         .args(tmp_ix::StartSwap {
-            swap_input: amount_in as u64
+            swap_input: amount_in as u64,
+            flash_loan: None,
         }).instructions().unwrap();
     ixs.push(ix);
 
@@ -233,8 +247,9 @@ fn test_pool_quote(
         program, 
         &owner.pubkey(), 
         mint_in, 
-        mint_out
-    );
+        mint_out,
+        None,
+    ).unwrap();
     ixs.push(swap_ix);
     
     let ixs = ixs.concat();
@@ -0,0 +1,50 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use crate::pool::PoolOperations;
+use crate::registry::TokenRegistry;
+use crate::tests::common::constant_product;
+
+fn registry(mints: &[(Pubkey, &str, u8)]) -> TokenRegistry {
+    let mut registry = TokenRegistry::default();
+    for (mint, symbol, decimals) in mints {
+        registry.insert(*mint, symbol, symbol, *decimals);
+    }
+    registry
+}
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() / b < 1e-3, "{} != {}", a, b);
+}
+
+#[test]
+fn amounts_are_shown_in_whole_tokens() {
+    let (usdc, unknown) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let registry = registry(&[(usdc, "USDC", 6)]);
+
+    assert_eq!(registry.fmt_amount(&usdc, 12_500_000), "12.5 USDC");
+    // raw amount + short address when the mint isn't known
+    let short = unknown.to_string()[..4].to_string();
+    assert_eq!(registry.fmt_amount(&unknown, 42), format!("42 {}", short));
+    assert_eq!(registry.fmt_path(&[usdc, unknown, usdc]), format!("USDC -> {} -> USDC", short));
+}
+
+#[test]
+fn prices_propagate_from_usdc_pools() {
+    let (usdc, sol, ray, orphan) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let mut registry = registry(&[(usdc, "USDC", 6), (sol, "SOL", 9), (ray, "RAY", 6), (orphan, "ORPH", 6)]);
+
+    let sol_usdc = constant_product("sol_usdc", [sol, usdc], [1_000 * 10_u128.pow(9), 100_000 * 10_u128.pow(6)]); // $100
+    let ray_sol = constant_product("ray_sol", [ray, sol], [1_000 * 10_u128.pow(6), 10 * 10_u128.pow(9)]); // 0.01 SOL
+    let orphan_ray = constant_product("orphan_ray", [orphan, Pubkey::new_unique()], [10_u128.pow(6), 10_u128.pow(6)]);
+    let pools: Vec<&dyn PoolOperations> = vec![&*ray_sol, &*sol_usdc, &*orphan_ray];
+    registry.update_prices(&pools, &usdc);
+
+    assert_eq!(registry.usd_prices[&usdc], 1.0);
+    assert_close(registry.usd_prices[&sol], 100.0);
+    assert_close(registry.usd_prices[&ray], 1.0);
+    assert!(registry.usd_value(&orphan, 10_u128.pow(6)).is_none());
+
+    assert_close(registry.usd_value(&sol, 2 * 10_u128.pow(9)).unwrap(), 200.0);
+    assert_eq!(registry.fmt_usd(&usdc, 1_234_567), "$1.23");
+    assert_eq!(registry.fmt_usd(&orphan, 1), "$?");
+}
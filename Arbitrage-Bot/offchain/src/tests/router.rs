@@ -0,0 +1,96 @@
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::{Client, Cluster};
+
+use std::rc::Rc;
+
+use crate::constants::*;
+use crate::router::PoolRouter;
+use crate::tests::common::constant_product as cp;
+use crate::utils::apply_slippage;
+
+// a -> c directly through a shallow pool, or through b with deep pools
+fn triangle() -> (PoolRouter, [Pubkey; 3]) {
+    let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let router = PoolRouter::new(vec![
+        cp("ac", [a, c], [1_000, 1_000]),
+        cp("ab", [a, b], [1_000_000, 1_000_000]),
+        cp("bc", [b, c], [1_000_000, 1_000_000]),
+    ]);
+    (router, [a, b, c])
+}
+
+#[test]
+fn best_route_takes_the_deeper_hops() {
+    let (router, [a, b, c]) = triangle();
+
+    let route = router.best_route(&a, &c, 1_000, 3).unwrap();
+    assert_eq!(route.mints, vec![a, b, c]);
+    let names: Vec<String> = route.pools.iter().map(|pool| pool.0.get_name()).collect();
+    assert_eq!(names, vec!["ab", "bc"]);
+    assert_eq!(route.amounts, vec![1_000, 999, 998]);
+    assert_eq!(route.amount_out(), 998);
+
+    // the direct pool pays half
+    let direct = router.best_route(&a, &c, 1_000, 1).unwrap();
+    assert_eq!(direct.mints, vec![a, c]);
+    assert_eq!(direct.amounts, vec![1_000, 500]);
+}
+
+#[test]
+fn best_route_is_bounded_by_max_hops() {
+    let mints: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    // a chain, mints[0] -> mints[3] takes 3 hops
+    let router = PoolRouter::new(
+        mints.windows(2).map(|pair| cp("chain", [pair[0], pair[1]], [1_000_000, 1_000_000])).collect(),
+    );
+
+    assert!(router.best_route(&mints[0], &mints[3], 1_000, 2).is_none());
+    let route = router.best_route(&mints[0], &mints[3], 1_000, 3).unwrap();
+    assert_eq!(route.pools.len(), 3);
+    assert_eq!(route.mints, mints);
+}
+
+#[test]
+fn no_route_between_unconnected_or_unknown_mints() {
+    let (router, [a, _, c]) = triangle();
+    let (d, e) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let islands = PoolRouter::new(vec![
+        cp("ac", [a, c], [1_000_000, 1_000_000]),
+        cp("de", [d, e], [1_000_000, 1_000_000]),
+    ]);
+
+    assert!(islands.best_route(&a, &d, 1_000, 4).is_none());
+    assert!(router.best_route(&a, &Pubkey::new_unique(), 1_000, 4).is_none());
+    assert!(router.best_route(&a, &c, 0, 4).is_none());
+}
+
+#[test]
+fn no_route_to_the_input_mint() {
+    let (router, [a, _, _]) = triangle();
+    assert!(router.best_route(&a, &a, 1_000, 4).is_none());
+}
+
+#[test]
+fn swap_instructions_bound_each_hop_by_its_quote() {
+    let (router, [a, _, c]) = triangle();
+    let route = router.best_route(&a, &c, 1_000, 3).unwrap();
+
+    // ixs are only built, nothing is sent
+    let provider = Client::new_with_options(Cluster::Localnet, Rc::new(Keypair::new()), CommitmentConfig::confirmed());
+    let program = provider.program(*ARB_PROGRAM_ID);
+    let owner = Pubkey::new_unique();
+    let ixs = route.swap_instructions(&program, &owner, 100).unwrap();
+
+    // start_dust_sweep, a swap per hop, end_dust_sweep
+    assert_eq!(ixs.len(), route.pools.len() + 2);
+    for (i, ix) in ixs[1..ixs.len() - 1].iter().enumerate() {
+        let min_amount_out = u64::from_le_bytes(ix.data[..].try_into().unwrap());
+        assert_eq!(min_amount_out, apply_slippage(route.amounts[i + 1], 100));
+    }
+    for ix in [&ixs[0], &ixs[ixs.len() - 1]] {
+        assert_eq!(ix.program_id, *ARB_PROGRAM_ID);
+        assert!(ix.accounts.iter().any(|meta| meta.pubkey == owner && meta.is_signer));
+    }
+}
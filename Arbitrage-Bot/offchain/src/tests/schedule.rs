@@ -0,0 +1,66 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use std::collections::HashSet;
+
+use crate::schedule::{assign_wallets, pick_batch, ExecutedSwaps, RouteCandidate};
+use crate::tests::common::{constant_product, pool};
+
+fn route(amount_in: u128, profit: u128) -> RouteCandidate {
+    RouteCandidate { mint_idxs: vec![], pools: vec![], amount_in, amount_out: amount_in + profit }
+}
+
+fn accounts(pks: &[Pubkey]) -> HashSet<Pubkey> {
+    pks.iter().copied().collect()
+}
+
+#[test]
+fn batch_skips_conflicts_for_more_total_profit() {
+    let (x, y, z) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    // the best route conflicts with both others, which together pay more
+    let routes = vec![route(100, 10), route(100, 7), route(100, 6)];
+    let write_accounts = vec![accounts(&[x, y]), accounts(&[x]), accounts(&[y, z])];
+
+    let batch = pick_batch(&routes, &write_accounts, &[100, 100]);
+    let picked: HashSet<usize> = batch.iter().map(|(route, _)| *route).collect();
+    assert_eq!(picked, [1, 2].into_iter().collect());
+    let wallets: HashSet<usize> = batch.iter().map(|(_, wallet)| *wallet).collect();
+    assert_eq!(wallets.len(), 2);
+
+    // one wallet = one route
+    assert_eq!(pick_batch(&routes, &write_accounts, &[100]), vec![(0, 0)]);
+}
+
+#[test]
+fn routes_need_a_wallet_with_their_balance() {
+    assert_eq!(assign_wallets(&[50, 200], &[100, 300]), Some(vec![0, 1]));
+    assert_eq!(assign_wallets(&[200, 200], &[100, 300]), None);
+    assert_eq!(assign_wallets(&[10, 10, 10], &[100, 100]), None);
+
+    // the large route doesn't fit, the small one goes to the small wallet
+    let routes = vec![route(1_000, 50), route(10, 1)];
+    let write_accounts = vec![accounts(&[Pubkey::new_unique()]), accounts(&[Pubkey::new_unique()])];
+    assert_eq!(pick_batch(&routes, &write_accounts, &[20, 500]), vec![(1, 1)]);
+}
+
+#[test]
+fn executed_swaps_continue_the_curve() {
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let pool = pool("cp", mints, [1_000_000, 1_000_000]);
+    let mut executed = ExecutedSwaps::default();
+    let before = executed.quote(&pool, 100_000, &mints[0], &mints[1]).unwrap();
+
+    executed.record(&pool, 100_000, &mints[0], &mints[1]);
+    assert!(executed.touches(&pool));
+
+    // the same as quoting on the reserves after the first swap
+    let after = constant_product("cp", mints, [1_100_000, 1_000_000 - before]);
+    let expected = after.get_quote_with_amounts_scaled(100_000, &mints[0], &mints[1]).unwrap();
+    let requoted = executed.quote(&pool, 100_000, &mints[0], &mints[1]).unwrap();
+    assert!(requoted < before);
+    assert!(expected.abs_diff(requoted) <= 1);
+
+    // the other way is quoted on the state before, under what it'd really get
+    let back = executed.quote(&pool, 50_000, &mints[1], &mints[0]).unwrap();
+    assert_eq!(back, pool.0.get_quote_with_amounts_scaled(50_000, &mints[1], &mints[0]).unwrap());
+    assert!(back <= after.get_quote_with_amounts_scaled(50_000, &mints[1], &mints[0]).unwrap());
+}
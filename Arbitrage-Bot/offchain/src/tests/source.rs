@@ -0,0 +1,70 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use solana_sdk::account::Account;
+
+use std::rc::Rc;
+
+use crate::source::{AccountUpdate, PoolUpdater};
+use crate::tests::common::constant_product;
+use crate::utils::{slot_spread, PoolQuote};
+
+fn update(pubkey: Pubkey, lamports: u64, slot: u64) -> AccountUpdate {
+    AccountUpdate {
+        pubkey,
+        account: Some(Account { lamports, ..Account::default() }),
+        slot,
+    }
+}
+
+#[test]
+fn updates_go_to_the_pools_of_the_account() {
+    let (vault_a, vault_b, vault_c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    // pools 0 and 1 share vault_b
+    let mut updater = PoolUpdater::new(vec![vec![vault_a, vault_b], vec![vault_b, vault_c]]);
+
+    assert_eq!(updater.apply(vec![update(vault_a, 1, 10)]), vec![0]);
+    assert!(updater.pool_accounts(0).is_none()); // vault_b not seen yet
+
+    assert_eq!(updater.apply(vec![update(vault_b, 2, 11)]), vec![0, 1]);
+    let accounts = updater.pool_accounts(0).unwrap();
+    assert_eq!(accounts[1].as_ref().unwrap().lamports, 2);
+    assert!(updater.pool_accounts(1).is_none());
+    assert_eq!(updater.pool_slots, vec![11, 11]);
+}
+
+#[test]
+fn stale_updates_are_dropped() {
+    let vault = Pubkey::new_unique();
+    let mut updater = PoolUpdater::new(vec![vec![vault]]);
+
+    updater.apply(vec![update(vault, 1, 20)]);
+    // an RPC fetch older than the streamed state
+    assert!(updater.apply(vec![update(vault, 2, 19)]).is_empty());
+    assert_eq!(updater.account(&vault).unwrap().lamports, 1);
+    assert_eq!(updater.pool_slots, vec![20]);
+}
+
+#[test]
+fn a_missing_account_only_disables_its_pool() {
+    let (vault_a, vault_b, vault_c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let mut updater = PoolUpdater::new(vec![vec![vault_a], vec![vault_b], vec![vault_c]]);
+    updater.apply(vec![update(vault_a, 1, 10), update(vault_b, 2, 10), update(vault_c, 3, 10)]);
+
+    // vault_b was closed
+    let updated = updater.apply(vec![AccountUpdate { pubkey: vault_b, account: None, slot: 11 }]);
+    assert_eq!(updated, vec![1]);
+    assert!(updater.pool_accounts(1).is_none());
+    // the pools after it keep their own accounts
+    assert_eq!(updater.pool_accounts(2).unwrap()[0].as_ref().unwrap().lamports, 3);
+}
+
+#[test]
+fn slot_spread_of_a_cycle() {
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let pool = |slot: u64| {
+        PoolQuote::at_slot(Rc::new(constant_product("cp", mints, [1, 1])), slot)
+    };
+
+    assert_eq!(slot_spread(&[]), 0);
+    assert_eq!(slot_spread(&[pool(100)]), 0);
+    assert_eq!(slot_spread(&[pool(100), pool(103), pool(101)]), 3);
+}
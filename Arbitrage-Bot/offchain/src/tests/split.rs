@@ -0,0 +1,56 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+use crate::split::split_hop;
+use crate::tests::common::pool;
+
+#[test]
+fn equal_pools_get_equal_parts() {
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let pools = vec![pool("a", mints, [1_000_000, 1_000_000]), pool("b", mints, [1_000_000, 1_000_000])];
+
+    let split = split_hop(&pools, 100_000, &mints[0], &mints[1], 10).unwrap();
+    assert_eq!(split.amounts_in, vec![50_000, 50_000]);
+    assert_eq!(split.amount_in(), 100_000);
+
+    // beats either pool alone
+    let single = pools[0].0.get_quote_with_amounts_scaled(100_000, &mints[0], &mints[1]).unwrap();
+    assert!(split.amount_out > single);
+}
+
+#[test]
+fn deeper_pool_takes_the_larger_part() {
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let pools = vec![pool("shallow", mints, [1_000_000, 1_000_000]), pool("deep", mints, [3_000_000, 3_000_000])];
+
+    let split = split_hop(&pools, 400_000, &mints[0], &mints[1], 20).unwrap();
+    assert_eq!(split.pools[0].0.get_name(), "deep"); // main swap first
+    assert_eq!(split.amounts_in, vec![300_000, 100_000]);
+    assert_eq!(split.share_bps(), vec![2_500]);
+}
+
+#[test]
+fn small_input_stays_on_one_pool() {
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    // b prices the output much lower, a small input never reaches it
+    let pools = vec![pool("a", mints, [1_000_000, 2_000_000]), pool("b", mints, [1_000_000, 1_000_000])];
+
+    let split = split_hop(&pools, 1_000, &mints[1], &mints[0], 10).unwrap();
+    assert_eq!(split.pools.len(), 1);
+    assert_eq!(split.pools[0].0.get_name(), "b");
+    assert!(split.share_bps().is_empty());
+}
+
+#[test]
+fn requote_matches_the_onchain_amounts() {
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let pools = vec![pool("a", mints, [1_000_000, 1_000_000]), pool("b", mints, [2_000_000, 2_000_000])];
+
+    let split = split_hop(&pools, 90_001, &mints[0], &mints[1], 9).unwrap();
+    let shares = split.share_bps();
+    let requoted = split.requote(90_001, &mints[0], &mints[1]).unwrap();
+
+    // splits round down, the main swap takes the rest
+    let split_amount = 90_001 * shares[0] as u128 / 10_000;
+    assert_eq!(requoted.amounts_in, vec![90_001 - split_amount, split_amount]);
+    assert_eq!(requoted.amount_in(), 90_001);
+}
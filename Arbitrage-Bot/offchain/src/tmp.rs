@@ -28,7 +28,7 @@ use sha2::{Digest};
 
 use anchor_lang::prelude::*;
 
-use client::utils::{derive_token_address};
+use client::utils::{derive_token_address, derive_swap_state_address, derive_mint_stats_address};
 use client::pool::{ PoolType};
 use client::constants::*;
 use client::pool_utils::serum::*;
@@ -338,10 +338,8 @@ fn main() {
     let payer_acc = if side == "buy" { quote_ata } else { base_ata };
     let _side = if side == "buy" { tmp::Side::Bid } else { tmp::Side::Ask };
 
-    let (swap_state, _) = Pubkey::find_program_address(
-        &[b"swap_state"], 
-        &program.id()
-    );
+    let swap_state = derive_swap_state_address(&program.id(), &owner.pubkey());
+    let mint_stats = derive_mint_stats_address(&program.id(), &owner.pubkey(), &pool.base_mint);
 
     // initialize swap 
     let ix = program
@@ -349,9 +347,12 @@ fn main() {
         .accounts(tmp_accounts::TokenAndSwapState {
             swap_state,
             src: base_ata,
+            mint_stats,
+            authority: owner.pubkey(),
         })
         .args(tmp_instructions::StartSwap {
-            swap_input: amount_in_u
+            swap_input: amount_in_u,
+            flash_loan: None,
         }).instructions().unwrap();
 
     let swap_ix = program.request()
@@ -376,7 +377,7 @@ fn main() {
             rent: solana_sdk::sysvar::rent::id(),
             swap_state,
         })
-        .args(tmp_instructions::SerumSwap { side: _side })
+        .args(tmp_instructions::SerumSwap { side: _side, min_amount_out: None })
         .instructions().unwrap(); 
 
     let ixs = vec![
@@ -1,6 +1,13 @@
 use crate::constants::*;
 use crate::pool::PoolOperations;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use anchor_client::solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
 use anchor_client::solana_sdk::pubkey::Pubkey;
+use solana_account_decoder::UiAccountEncoding;
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::system_program;
 use std::collections::HashMap;
 use std::fs;
 use std::rc::Rc;
@@ -41,12 +48,92 @@ pub fn derive_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
     pda
 }
 
+/// Create the ATA of `owner` for `mint` -- a no-op if it already exists
+pub fn create_ata_idempotent_ix(payer: &Pubkey, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(derive_token_address(owner, mint), false),
+        AccountMeta::new_readonly(*owner, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*TOKEN_PROGRAM_ID, false),
+    ];
+    Instruction {
+        program_id: *ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts,
+        data: vec![1], // CreateIdempotent
+    }
+}
+
+pub fn derive_swap_state_address(program_id: &Pubkey, authority: &Pubkey) -> Pubkey {
+    let (pda, _) =
+        Pubkey::find_program_address(&[b"swap_state", &authority.to_bytes()], program_id);
+    pda
+}
+
+pub fn derive_mint_stats_address(program_id: &Pubkey, authority: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let (pda, _) = Pubkey::find_program_address(
+        &[b"mint_stats", &authority.to_bytes(), &mint.to_bytes()],
+        program_id,
+    );
+    pda
+}
+
+/// Accounts of `program_id` of `data_size` bytes which store `owner` at `owner_offset`
+pub fn get_owned_program_accounts(
+    connection: &RpcClient,
+    program_id: &Pubkey,
+    data_size: usize,
+    owner_offset: usize,
+    owner: &Pubkey,
+) -> Vec<(Pubkey, Account)> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(data_size as u64),
+            RpcFilterType::Memcmp(Memcmp {
+                offset: owner_offset,
+                bytes: MemcmpEncodedBytes::Base58(owner.to_string()),
+                encoding: None,
+            }),
+        ]),
+        // base58 only works for small accounts
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    connection
+        .get_program_accounts_with_config(program_id, config)
+        .unwrap()
+}
+
+/// quote * (1 - bps) -- min amount out of a hop
+pub fn apply_slippage(quote_amount: u128, bps: u64) -> u64 {
+    let bps = bps.min(10_000) as u128;
+    (quote_amount * (10_000 - bps) / 10_000) as u64
+}
+
+/// A pool + the slot of the state it quotes from (0 = unknown)
 #[derive(Debug, Clone)]
-pub struct PoolQuote(pub Rc<Box<dyn PoolOperations>>);
+pub struct PoolQuote(pub Rc<Box<dyn PoolOperations>>, pub u64);
 
 impl PoolQuote {
     pub fn new(quote: Rc<Box<dyn PoolOperations>>) -> Self {
-        Self(quote)
+        Self(quote, 0)
+    }
+
+    pub fn at_slot(quote: Rc<Box<dyn PoolOperations>>, slot: u64) -> Self {
+        Self(quote, slot)
+    }
+}
+
+/// Slots between the oldest and newest state of `pools`
+pub fn slot_spread(pools: &[PoolQuote]) -> u64 {
+    let slots = pools.iter().map(|pool| pool.1);
+    match (slots.clone().min(), slots.max()) {
+        (Some(min), Some(max)) => max - min,
+        _ => 0,
     }
 }
 
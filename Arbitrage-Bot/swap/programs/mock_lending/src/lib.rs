@@ -0,0 +1,111 @@
+//! Minimal flash lender used to test flash-loan funded routes
+//!
+//! Lends out of a token vault owned by a PDA of this program. It does not
+//! enforce repayment itself -- the `tmp` program has to.
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+solana_program::declare_id!("2yDLtGSvHpG7ThxjSJ4pjo4Sm9xyACnwGTuhVdJ9UYsv");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+/// Seed of the PDA which owns the lending vault
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault_authority";
+
+pub fn vault_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED], &id())
+}
+
+pub enum LendingInstruction {
+    /// Accounts: [vault (w), dst (w), vault_authority, token_program]
+    Borrow { amount: u64 },
+    /// Accounts: [src (w), vault (w), src_authority (s), token_program]
+    Repay { amount: u64 },
+}
+
+impl LendingInstruction {
+    pub fn pack(&self) -> Vec<u8> {
+        let (tag, amount) = match self {
+            LendingInstruction::Borrow { amount } => (0_u8, amount),
+            LendingInstruction::Repay { amount } => (1_u8, amount),
+        };
+        let mut data = vec![tag];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let (tag, rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+        let amount = rest
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        match tag {
+            0 => Ok(LendingInstruction::Borrow { amount }),
+            1 => Ok(LendingInstruction::Repay { amount }),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    match LendingInstruction::unpack(data)? {
+        LendingInstruction::Borrow { amount } => {
+            let vault = next_account_info(accounts_iter)?;
+            let dst = next_account_info(accounts_iter)?;
+            let authority = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+
+            let (authority_pda, bump) = vault_authority();
+            if *authority.key != authority_pda {
+                return Err(ProgramError::InvalidSeeds);
+            }
+
+            let ix = spl_token::instruction::transfer(
+                token_program.key,
+                vault.key,
+                dst.key,
+                authority.key,
+                &[],
+                amount,
+            )?;
+            invoke_signed(
+                &ix,
+                &[vault.clone(), dst.clone(), authority.clone(), token_program.clone()],
+                &[&[VAULT_AUTHORITY_SEED, &[bump]]],
+            )
+        }
+        LendingInstruction::Repay { amount } => {
+            let src = next_account_info(accounts_iter)?;
+            let vault = next_account_info(accounts_iter)?;
+            let authority = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+
+            let ix = spl_token::instruction::transfer(
+                token_program.key,
+                src.key,
+                vault.key,
+                authority.key,
+                &[],
+                amount,
+            )?;
+            invoke(
+                &ix,
+                &[src.clone(), vault.clone(), authority.clone(), token_program.clone()],
+            )
+        }
+    }
+}
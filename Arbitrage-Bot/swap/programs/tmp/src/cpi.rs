@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program;
+
+/// Invoke `program` with `accounts` passed on as is
+///
+/// Signer and writable flags are the ones of the outer instruction, so the
+/// client decides them when building the remaining accounts
+pub fn invoke_forwarded<'info>(
+    program: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+) -> Result<()> {
+    let ix_accounts = accounts
+        .iter()
+        .map(|acc| AccountMeta {
+            pubkey: *acc.key,
+            is_signer: acc.is_signer,
+            is_writable: acc.is_writable,
+        })
+        .collect();
+
+    let instruction = Instruction {
+        program_id: *program.key,
+        accounts: ix_accounts,
+        data,
+    };
+
+    let mut account_infos = accounts.to_vec();
+    account_infos.push(program.clone());
+
+    solana_program::program::invoke(
+        &instruction, 
+        &account_infos, 
+    )?;
+
+    Ok(())
+}
@@ -18,4 +18,12 @@ pub enum ErrorCode {
     InvalidPoolConfig,
     #[msg("Slippage tolerance exceeded.")]
     SlippageExceeded,
+    #[msg("Signer is not the authority of the swap state.")]
+    InvalidAuthority,
+    #[msg("Flash loan borrow or repayment is invalid.")]
+    InvalidFlashLoan,
+    #[msg("Treasury does not match the mint stats.")]
+    InvalidTreasury,
+    #[msg("Route hop accounts or encoding are invalid.")]
+    InvalidRoute,
 }
\ No newline at end of file
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::ix_data::DexKind;
+
+// Typed outcome of a route, decoded offchain from the `Program data:` logs
+
+/// A route started with `swap_input` of `mint`
+#[event]
+#[derive(Clone, Debug)]
+pub struct RouteStarted {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub swap_input: u64,
+    pub flash_borrowed: u64, // 0 = funded from the operator wallet
+}
+
+/// One swap of a route, a split hop emits one per pool
+#[event]
+#[derive(Clone, Debug)]
+pub struct HopExecuted {
+    pub authority: Pubkey,
+    pub dex: DexKind,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+/// A route ended with `profit` in its start `mint`
+#[event]
+#[derive(Clone, Debug)]
+pub struct RouteCompleted {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub profit: u64,
+}
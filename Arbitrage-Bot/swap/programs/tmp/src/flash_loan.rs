@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+use crate::cpi::invoke_forwarded;
+use crate::error::ErrorCode;
+
+/// Invoke a lending program for a flash borrow or repayment
+///
+/// The first remaining account is the lending program, the rest are passed
+/// to it as is, so any lender can be plugged in by the client
+pub fn lending_cpi<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    data: &[u8],
+) -> Result<()> {
+    let (lending_program, accounts) = remaining_accounts
+        .split_first()
+        .ok_or(ErrorCode::InvalidFlashLoan)?;
+    require!(lending_program.executable, ErrorCode::InvalidFlashLoan);
+
+    invoke_forwarded(lending_program, accounts, data.to_vec())
+}
@@ -1,8 +1,51 @@
 use anchor_lang::prelude::*;
 
+use crate::swaps::Side;
+
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct SwapData {
     pub instruction: u8, 
     pub amount_in: u64, 
     pub minimum_amount_out: u64, 
 }
+
+/// Lending program CPI used to wrap a route in a flash loan
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FlashLoan {
+    pub amount: u64,    // Amount borrowed (start_swap) or repaid (profit_or_revert)
+    pub data: Vec<u8>,  // Lending program instruction data
+}
+
+/// DEX of a hop in a `route`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DexKind {
+    Orca,
+    Saber,
+    Mercurial,
+    AldrinV1 { is_inverted: bool },
+    AldrinV2 { is_inverted: bool },
+    Serum { side: Side },
+}
+
+/// One hop of a `route`, run over `remaining_accounts[accounts_start..accounts_end]`
+///
+/// With `splits` the hop input is divided across parallel pools of the same
+/// pair: each split swaps its share and this swap takes the remainder, the
+/// hop output is the sum of them all
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RouteHop {
+    pub dex: DexKind,
+    pub accounts_start: u8,
+    pub accounts_end: u8,
+    pub min_amount_out: Option<u64>, // None = no bound on this hop (bounds the summed output)
+    pub splits: Vec<HopSplit>,
+}
+
+/// A sub-swap of a `RouteHop` on another pool of the same pair
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct HopSplit {
+    pub dex: DexKind,
+    pub accounts_start: u8,
+    pub accounts_end: u8,
+    pub share_bps: u16, // Share of the hop input, the splits of a hop sum under 10_000
+}
@@ -1,17 +1,25 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token::{TokenAccount, Mint}
+    token::{self, TokenAccount, Mint, Token, Transfer}
 };
 use anchor_lang::Accounts;
 
 declare_id!("CRQXfRGq3wTkjt7JkqhojPLiKLYLjHPGLebnfiiQB46T");
 
-use state::SwapState;
+use state::{SwapState, MintStats};
 use error::ErrorCode; 
+use ix_data::{DexKind, FlashLoan, RouteHop};
+use events::{RouteStarted, HopExecuted, RouteCompleted};
+use flash_loan::lending_cpi;
+use route::execute_hop;
 
 pub mod error; 
 pub mod state; 
 pub mod ix_data;
+pub mod events;
+pub mod cpi;
+pub mod flash_loan;
+pub mod route;
 pub mod swaps; 
 
 pub use swaps::*; 
@@ -20,84 +28,256 @@ pub use swaps::*;
 pub mod tmp {
     use super::*;
 
-    /// Initialize the swap program state
+    /// Initialize the swap state of an operator wallet
     pub fn init_program(ctx: Context<InitSwapState>) -> Result<()> {
         let swap_state = &mut ctx.accounts.swap_state;
+        swap_state.authority = ctx.accounts.authority.key();
+        swap_state.bump = ctx.bumps.swap_state;
         swap_state.swap_input = 0;
         swap_state.is_valid = false;
         swap_state.total_swaps = 0;
         swap_state.total_volume = 0;
         swap_state.created_at = Clock::get()?.unix_timestamp;
-        msg!("Swap program initialized successfully");
+        msg!("Swap state initialized for authority: {}", swap_state.authority);
+        Ok(())
+    }
+
+    /// Initialize the statistics of a start mint of an operator wallet
+    pub fn init_mint_stats(ctx: Context<InitMintStats>, working_balance: u64) -> Result<()> {
+        let mint_stats = &mut ctx.accounts.mint_stats;
+        mint_stats.authority = ctx.accounts.authority.key();
+        mint_stats.mint = ctx.accounts.mint.key();
+        mint_stats.bump = ctx.bumps.mint_stats;
+        mint_stats.treasury = ctx.accounts.treasury.key();
+        mint_stats.working_balance = working_balance;
+        msg!("Mint stats initialized for mint: {}", mint_stats.mint);
+        Ok(())
+    }
+
+    /// Update the treasury and working balance used by `sweep_profit`
+    pub fn set_sweep_config(ctx: Context<SetSweepConfig>, working_balance: u64) -> Result<()> {
+        let mint_stats = &mut ctx.accounts.mint_stats;
+        mint_stats.treasury = ctx.accounts.treasury.key();
+        mint_stats.working_balance = working_balance;
         Ok(())
     }
     
     /// Start a new swap sequence
-    pub fn start_swap(ctx: Context<TokenAndSwapState>, swap_input: u64) -> Result<()> {
+    ///
+    /// With `flash_loan` the route is funded by a flash borrow through the
+    /// lending program given in the remaining accounts
+    pub fn start_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, TokenAndSwapState<'info>>, 
+        swap_input: u64, 
+        flash_loan: Option<FlashLoan>,
+    ) -> Result<()> {
         require!(swap_input > 0, ErrorCode::InvalidAmount);
+        // a second start would overwrite the baseline of the open one
+        require!(!ctx.accounts.swap_state.is_valid, ErrorCode::InvalidState);
         
+        // Balance before any borrowed funds
+        let start_balance = ctx.accounts.src.amount;
+
+        let mut flash_borrowed = 0;
+        if let Some(flash_loan) = flash_loan {
+            lending_cpi(ctx.remaining_accounts, &flash_loan.data)?;
+
+            let src = &mut ctx.accounts.src;
+            src.reload()?;
+            flash_borrowed = src.amount.checked_sub(start_balance)
+                .ok_or(ErrorCode::Overflow)?;
+            require!(flash_borrowed >= flash_loan.amount, ErrorCode::InvalidFlashLoan);
+
+            msg!("Flash borrowed: {}", flash_borrowed);
+        }
+
         let swap_state = &mut ctx.accounts.swap_state;
-        swap_state.start_balance = ctx.accounts.src.amount;
+        swap_state.start_balance = start_balance;
         swap_state.swap_input = swap_input;
+        swap_state.flash_borrowed = flash_borrowed;
         swap_state.is_valid = true;
-        swap_state.current_swap_start = Clock::get()?.unix_timestamp;
-        swap_state.total_swaps += 1;
-        swap_state.total_volume = swap_state.total_volume.checked_add(swap_input)
-            .ok_or(ErrorCode::Overflow)?;
+        record_swap_start(swap_state, &mut ctx.accounts.mint_stats, swap_input)?;
         
         msg!("Swap started with input amount: {}", swap_input);
         Ok(())
     }
 
     /// Verify profit and complete swap sequence
-    pub fn profit_or_revert(ctx: Context<TokenAndSwapState>) -> Result<()> {
+    ///
+    /// A flash loan taken in `start_swap` must be repaid with `flash_repay`
+    /// before the profit check
+    pub fn profit_or_revert<'info>(
+        ctx: Context<'_, '_, '_, 'info, TokenAndSwapState<'info>>, 
+        flash_repay: Option<FlashLoan>,
+    ) -> Result<()> {
+        let flash_borrowed = ctx.accounts.swap_state.flash_borrowed;
+        match flash_repay {
+            Some(flash_repay) => {
+                require!(flash_borrowed > 0, ErrorCode::InvalidFlashLoan);
+                require!(flash_repay.amount >= flash_borrowed, ErrorCode::InvalidFlashLoan);
+
+                let src = &mut ctx.accounts.src;
+                let pre_repay_balance = src.amount;
+                lending_cpi(ctx.remaining_accounts, &flash_repay.data)?;
+                src.reload()?;
+
+                let repaid = pre_repay_balance.checked_sub(src.amount)
+                    .ok_or(ErrorCode::InvalidFlashLoan)?;
+                require!(repaid >= flash_repay.amount, ErrorCode::InvalidFlashLoan);
+
+                msg!("Flash repaid: {}", repaid);
+            }
+            None => require!(flash_borrowed == 0, ErrorCode::InvalidFlashLoan),
+        }
+
         let swap_state = &mut ctx.accounts.swap_state; 
         swap_state.is_valid = false;
+        swap_state.flash_borrowed = 0;
 
         let init_balance = swap_state.start_balance;
         let final_balance = ctx.accounts.src.amount;
-        let profit = final_balance.checked_sub(init_balance)
-            .ok_or(ErrorCode::Overflow)?;
-        
-        msg!("Initial balance: {}, Final balance: {}, Profit: {}", 
-             init_balance, final_balance, profit);
-        
-        // Ensure profit or revert
-        require!(profit > 0, ErrorCode::NoProfit);
-        
-        // Update total profit
-        swap_state.total_profit = swap_state.total_profit.checked_add(profit)
-            .ok_or(ErrorCode::Overflow)?;
+        let profit = record_profit(
+            swap_state, 
+            &mut ctx.accounts.mint_stats, 
+            init_balance, 
+            final_balance,
+        )?;
         
         msg!("Swap completed successfully with profit: {}", profit);
         Ok(())
     }
 
+    /// Execute a whole route in one instruction
+    ///
+    /// Each hop runs over its slice of the remaining accounts and its output
+    /// is the input of the next hop, the route must end with a profit in `src`
+    pub fn route<'info>(
+        ctx: Context<'_, '_, '_, 'info, TokenAndSwapState<'info>>, 
+        swap_input: u64, 
+        hops: Vec<RouteHop>,
+    ) -> Result<()> {
+        require!(swap_input > 0, ErrorCode::InvalidAmount);
+        require!(!hops.is_empty(), ErrorCode::InvalidRoute);
+        // not inside a start_swap .. profit_or_revert sequence
+        require!(!ctx.accounts.swap_state.is_valid, ErrorCode::InvalidState);
+
+        let start_balance = ctx.accounts.src.amount;
+        record_swap_start(&mut ctx.accounts.swap_state, &mut ctx.accounts.mint_stats, swap_input)?;
+
+        // Amount moves between hops in memory, no swap state round trip
+        let authority = ctx.accounts.authority.key();
+        let mut amount = swap_input;
+        for hop in hops.iter() {
+            amount = execute_hop(authority, hop, ctx.remaining_accounts, amount)?;
+        }
+
+        let src = &mut ctx.accounts.src;
+        src.reload()?;
+        let profit = record_profit(
+            &mut ctx.accounts.swap_state, 
+            &mut ctx.accounts.mint_stats, 
+            start_balance, 
+            src.amount,
+        )?;
+
+        msg!("Route of {} hops completed with profit: {}", hops.len(), profit);
+        Ok(())
+    }
+
+    /// Start swapping leftover `src` tokens back to a base mint
+    ///
+    /// Unlike `start_swap` the route doesn't come back to `src`, so there is
+    /// no profit check and no stats -- each hop should bound its output
+    pub fn start_dust_sweep(ctx: Context<StartDustSweep>, swap_input: u64) -> Result<()> {
+        require!(swap_input > 0, ErrorCode::InvalidAmount);
+        require!(swap_input <= ctx.accounts.src.amount, ErrorCode::NotEnoughFunds);
+
+        let swap_state = &mut ctx.accounts.swap_state;
+        require!(!swap_state.is_valid, ErrorCode::InvalidState);
+        swap_state.swap_input = swap_input;
+        swap_state.is_valid = true;
+
+        msg!("Dust sweep started with input amount: {}", swap_input);
+        Ok(())
+    }
+
+    /// Close the swap sequence opened by `start_dust_sweep`
+    pub fn end_dust_sweep(ctx: Context<EndDustSweep>) -> Result<()> {
+        let swap_state = &mut ctx.accounts.swap_state;
+        require!(swap_state.is_valid, ErrorCode::InvalidState);
+        swap_state.is_valid = false;
+
+        msg!("Dust sweep completed with amount out: {}", swap_state.swap_input);
+        Ok(())
+    }
+
+    /// Move the balance above the working balance into the treasury
+    pub fn sweep_profit(ctx: Context<SweepProfit>) -> Result<()> {
+        // Never in the middle of a route
+        require!(!ctx.accounts.swap_state.is_valid, ErrorCode::InvalidState);
+
+        let working_balance = ctx.accounts.mint_stats.working_balance;
+        let amount = ctx.accounts.src.amount.saturating_sub(working_balance);
+        if amount == 0 {
+            msg!("Nothing to sweep above working balance: {}", working_balance);
+            return Ok(());
+        }
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.src.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let mint_stats = &mut ctx.accounts.mint_stats;
+        mint_stats.total_swept = mint_stats.total_swept.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        msg!("Swept to treasury: {}", amount);
+        Ok(())
+    }
+
     /// Initialize an open orders account on Serum DEX
     pub fn init_open_order(ctx: Context<InitOpenOrder>) -> Result<()> {
         _init_open_order(ctx)
     }
+
+    /// Settle the free funds of an open orders account on Serum DEX
+    pub fn settle_open_orders(ctx: Context<SettleOpenOrders>) -> Result<()> {
+        _settle_open_orders(ctx)
+    }
+
+    /// Close an open orders account on Serum DEX, reclaiming its rent
+    pub fn close_open_orders(ctx: Context<CloseAccount>) -> Result<()> {
+        _close_open_orders(ctx)
+    }
     
     /// Execute swap on Orca DEX
-    pub fn orca_swap<'info>(ctx: Context<'_, '_, '_, 'info, OrcaSwap<'info>>) -> Result<()> {
-        basic_pool_swap!(_orca_swap, OrcaSwap<'info>)(ctx)
+    pub fn orca_swap<'info>(ctx: Context<'_, '_, '_, 'info, OrcaSwap<'info>>, min_amount_out: Option<u64>) -> Result<()> {
+        basic_pool_swap!(_orca_swap, DexKind::Orca, OrcaSwap<'info>)(ctx, min_amount_out)
     }
 
     /// Execute swap on Mercurial DEX
-    pub fn mercurial_swap<'info>(ctx: Context<'_, '_, '_, 'info, MercurialSwap<'info>>) -> Result<()> {
-        basic_pool_swap!(_mercurial_swap, MercurialSwap<'info>)(ctx)
+    pub fn mercurial_swap<'info>(ctx: Context<'_, '_, '_, 'info, MercurialSwap<'info>>, min_amount_out: Option<u64>) -> Result<()> {
+        basic_pool_swap!(_mercurial_swap, DexKind::Mercurial, MercurialSwap<'info>)(ctx, min_amount_out)
     }
 
     /// Execute swap on Saber DEX
-    pub fn saber_swap<'info>(ctx: Context<'_, '_, '_, 'info, SaberSwap<'info>>) -> Result<()> {
-        basic_pool_swap!(_saber_swap, SaberSwap<'info>)(ctx)
+    pub fn saber_swap<'info>(ctx: Context<'_, '_, '_, 'info, SaberSwap<'info>>, min_amount_out: Option<u64>) -> Result<()> {
+        basic_pool_swap!(_saber_swap, DexKind::Saber, SaberSwap<'info>)(ctx, min_amount_out)
     }
 
     /// Execute swap on Aldrin V2 DEX
-    pub fn aldrin_swap_v2<'info>(ctx: Context<'_, '_, '_, 'info, AldrinSwapV2<'info>>, is_inverted: bool) -> Result<()> {
+    pub fn aldrin_swap_v2<'info>(ctx: Context<'_, '_, '_, 'info, AldrinSwapV2<'info>>, is_inverted: bool, min_amount_out: Option<u64>) -> Result<()> {
         let amount_in = prepare_swap(&ctx.accounts.swap_state)?;
 
-        _aldrin_swap_v2(&ctx, amount_in, is_inverted)?;
+        _aldrin_swap_v2(&ctx, amount_in, is_inverted, min_amount_out)?;
 
         // End swap 
         let user_dst = match is_inverted {
@@ -105,16 +285,16 @@ pub mod tmp {
             false => &mut ctx.accounts.user_base_ata 
         };
         let swap_state = &mut ctx.accounts.swap_state;
-        end_swap(swap_state, user_dst)?;
+        end_swap(swap_state, user_dst, DexKind::AldrinV2 { is_inverted }, min_amount_out)?;
 
         Ok(())
     }
     
     /// Execute swap on Aldrin V1 DEX
-    pub fn aldrin_swap_v1<'info>(ctx: Context<'_, '_, '_, 'info, AldrinSwapV1<'info>>, is_inverted: bool) -> Result<()> {
+    pub fn aldrin_swap_v1<'info>(ctx: Context<'_, '_, '_, 'info, AldrinSwapV1<'info>>, is_inverted: bool, min_amount_out: Option<u64>) -> Result<()> {
         let amount_in = prepare_swap(&ctx.accounts.swap_state)?;
 
-        _aldrin_swap_v1(&ctx, amount_in, is_inverted)?;
+        _aldrin_swap_v1(&ctx, amount_in, is_inverted, min_amount_out)?;
 
         // End swap 
         let user_dst = match is_inverted {
@@ -122,13 +302,13 @@ pub mod tmp {
             false => &mut ctx.accounts.user_base_ata 
         };
         let swap_state = &mut ctx.accounts.swap_state;
-        end_swap(swap_state, user_dst)?;
+        end_swap(swap_state, user_dst, DexKind::AldrinV1 { is_inverted }, min_amount_out)?;
 
         Ok(())
     }
     
     /// Execute swap on Serum DEX
-    pub fn serum_swap<'info>(ctx: Context<'_, '_, '_, 'info, SerumSwap<'info>>, side: Side) -> Result<()> {
+    pub fn serum_swap<'info>(ctx: Context<'_, '_, '_, 'info, SerumSwap<'info>>, side: Side, min_amount_out: Option<u64>) -> Result<()> {
         let amount_in = prepare_swap(&ctx.accounts.swap_state)?;
         let is_bid = match side {
             Side::Bid => true,
@@ -143,7 +323,7 @@ pub mod tmp {
             false => &mut ctx.accounts.pc_wallet,
         };
         let swap_state = &mut ctx.accounts.swap_state;
-        end_swap(swap_state, user_dst)?;
+        end_swap(swap_state, user_dst, DexKind::Serum { side }, min_amount_out)?;
 
         Ok(())
     }
@@ -151,28 +331,32 @@ pub mod tmp {
 
 #[macro_export]
 macro_rules! basic_pool_swap {
-    ($swap_fcn:expr, $typ:ident < $tipe:tt > ) => {{
-        |ctx: Context<'_, '_, '_, 'info, $typ<$tipe>> | -> Result<()> {
+    ($swap_fcn:expr, $dex:expr, $typ:ident < $tipe:tt > ) => {{
+        |ctx: Context<'_, '_, '_, 'info, $typ<$tipe>>, min_amount_out: Option<u64>| -> Result<()> {
             // Save the amount of input swap
             let amount_in = prepare_swap(&ctx.accounts.swap_state)?;
 
             // Execute swap 
-            $swap_fcn(&ctx, amount_in)?;
+            $swap_fcn(&ctx, amount_in, min_amount_out)?;
 
             // Update the swap output amount (to be used as input to next swap)
             let swap_state = &mut ctx.accounts.swap_state;
             let user_dst = &mut ctx.accounts.user_dst;
-            end_swap(swap_state, user_dst)?;
+            end_swap(swap_state, user_dst, $dex, min_amount_out)?;
 
             Ok(())
         }
     }};
 }
 
-/// Complete a swap and update the swap state
+/// Complete a swap of `dex` and update the swap state
+///
+/// Fails with `SlippageExceeded` if the hop produced less than `min_amount_out`
 pub fn end_swap(
     swap_state: &mut Account<SwapState>,
-    user_dst: &mut Account<TokenAccount>
+    user_dst: &mut Account<TokenAccount>,
+    dex: DexKind,
+    min_amount_out: Option<u64>,
 ) -> Result<()> {
     // Derive the output of the swap 
     let dst_start_balance = user_dst.amount; // Pre-swap balance 
@@ -183,6 +367,18 @@ pub fn end_swap(
     
     msg!("Swap amount out: {}", swap_amount_out);
 
+    // Fail at this hop instead of running the rest of the route 
+    if let Some(min_amount_out) = min_amount_out {
+        require!(swap_amount_out >= min_amount_out, ErrorCode::SlippageExceeded);
+    }
+
+    emit!(HopExecuted {
+        authority: swap_state.authority,
+        dex,
+        amount_in: swap_state.swap_input,
+        amount_out: swap_amount_out,
+    });
+
     // Will be input amount into the next swap instruction
     swap_state.swap_input = swap_amount_out; 
     swap_state.last_swap_time = Clock::get()?.unix_timestamp;
@@ -190,6 +386,65 @@ pub fn end_swap(
     Ok(())
 }
 
+/// Count a new route in the swap state and the start mint stats
+pub fn record_swap_start(
+    swap_state: &mut Account<SwapState>,
+    mint_stats: &mut Account<MintStats>,
+    swap_input: u64,
+) -> Result<()> {
+    swap_state.current_swap_start = Clock::get()?.unix_timestamp;
+    swap_state.total_swaps += 1;
+    swap_state.total_volume = swap_state.total_volume.checked_add(swap_input)
+        .ok_or(ErrorCode::Overflow)?;
+
+    mint_stats.total_swaps = mint_stats.total_swaps.checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    mint_stats.total_volume = mint_stats.total_volume.checked_add(swap_input)
+        .ok_or(ErrorCode::Overflow)?;
+
+    emit!(RouteStarted {
+        authority: swap_state.authority,
+        mint: mint_stats.mint,
+        swap_input,
+        flash_borrowed: swap_state.flash_borrowed,
+    });
+
+    Ok(())
+}
+
+/// Check the route made a profit and add it to the stats
+///
+/// Fails with `NoProfit` unless `final_balance` is above `init_balance`
+pub fn record_profit(
+    swap_state: &mut Account<SwapState>,
+    mint_stats: &mut Account<MintStats>,
+    init_balance: u64,
+    final_balance: u64,
+) -> Result<u64> {
+    msg!("Initial balance: {}, Final balance: {}", init_balance, final_balance);
+    
+    // Ensure profit or revert -- a loss is no profit too
+    require!(final_balance > init_balance, ErrorCode::NoProfit);
+    let profit = final_balance - init_balance;
+    
+    // Update total profit
+    swap_state.total_profit = swap_state.total_profit.checked_add(profit)
+        .ok_or(ErrorCode::Overflow)?;
+
+    // Per start mint, as profits in different mints can't be summed
+    mint_stats.total_profit = mint_stats.total_profit.checked_add(profit)
+        .ok_or(ErrorCode::Overflow)?;
+    mint_stats.last_swap_time = Clock::get()?.unix_timestamp;
+
+    emit!(RouteCompleted {
+        authority: swap_state.authority,
+        mint: mint_stats.mint,
+        profit,
+    });
+
+    Ok(profit)
+}
+
 /// Prepare for a swap by validating the swap state
 pub fn prepare_swap(
     swap_state: &Account<SwapState>,
@@ -211,10 +466,11 @@ pub struct InitSwapState<'info> {
         init, 
         payer = payer,
         space = 8 + SwapState::INIT_SPACE,
-        seeds = [b"swap_state"], 
+        seeds = [b"swap_state", authority.key().as_ref()], 
         bump, 
     )] 
     pub swap_state: Account<'info, SwapState>,
+    pub authority: Signer<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -222,8 +478,103 @@ pub struct InitSwapState<'info> {
 
 #[derive(Accounts)]
 pub struct TokenAndSwapState<'info> {
+    #[account(mut, constraint = src.owner == authority.key() @ ErrorCode::InvalidAuthority)]
+    pub src: Account<'info, TokenAccount>,
+    #[account(
+        mut, 
+        seeds = [b"swap_state", authority.key().as_ref()], 
+        bump = swap_state.bump,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )] 
+    pub swap_state: Account<'info, SwapState>,
+    #[account(
+        mut,
+        seeds = [b"mint_stats", authority.key().as_ref(), src.mint.as_ref()],
+        bump = mint_stats.bump,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitMintStats<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MintStats::INIT_SPACE,
+        seeds = [b"mint_stats", authority.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+    pub mint: Account<'info, Mint>,
+    #[account(constraint = treasury.mint == mint.key() @ ErrorCode::InvalidTreasury)]
+    pub treasury: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
     #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSweepConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"mint_stats", authority.key().as_ref(), mint_stats.mint.as_ref()],
+        bump = mint_stats.bump,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+    #[account(constraint = treasury.mint == mint_stats.mint @ ErrorCode::InvalidTreasury)]
+    pub treasury: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StartDustSweep<'info> {
+    #[account(constraint = src.owner == authority.key() @ ErrorCode::InvalidAuthority)]
+    pub src: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"swap_state", authority.key().as_ref()],
+        bump = swap_state.bump,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub swap_state: Account<'info, SwapState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EndDustSweep<'info> {
+    #[account(
+        mut,
+        seeds = [b"swap_state", authority.key().as_ref()],
+        bump = swap_state.bump,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub swap_state: Account<'info, SwapState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepProfit<'info> {
+    #[account(mut, constraint = src.owner == authority.key() @ ErrorCode::InvalidAuthority)]
     pub src: Account<'info, TokenAccount>,
-    #[account(mut, seeds = [b"swap_state"], bump)] 
+    #[account(mut, address = mint_stats.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [b"swap_state", authority.key().as_ref()],
+        bump = swap_state.bump,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
     pub swap_state: Account<'info, SwapState>,
+    #[account(
+        mut,
+        seeds = [b"mint_stats", authority.key().as_ref(), src.mint.as_ref()],
+        bump = mint_stats.bump,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
\ No newline at end of file
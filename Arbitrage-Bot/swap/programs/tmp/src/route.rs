@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::cpi::invoke_forwarded;
+use crate::error::ErrorCode;
+use crate::events::HopExecuted;
+use crate::ix_data::{DexKind, RouteHop};
+use crate::swaps::*;
+
+// Hop accounts of the AMMs are the accounts of the DEX swap instruction, in
+// the order the DEX expects them, followed by the DEX program. Serum hops use
+// the `SerumSwap` accounts without the `swap_state`.
+impl DexKind {
+    /// Number of accounts of a hop
+    pub fn accounts_len(&self) -> usize {
+        match self {
+            DexKind::Orca => 11,
+            DexKind::Saber => 10,
+            DexKind::Mercurial => 9,
+            DexKind::AldrinV1 { .. } => 11,
+            DexKind::AldrinV2 { .. } => 12,
+            DexKind::Serum { .. } => 16,
+        }
+    }
+
+    /// Index of the user's destination token account in the hop accounts
+    pub fn user_dst_index(&self) -> usize {
+        match self {
+            DexKind::Orca => 6,
+            DexKind::Saber => 6,
+            DexKind::Mercurial => 7,
+            // user_base_ata = 7, user_quote_ata = 8
+            DexKind::AldrinV1 { is_inverted } | DexKind::AldrinV2 { is_inverted } => {
+                if *is_inverted { 8 } else { 7 }
+            }
+            // coin_wallet = 10, pc_wallet = 12
+            DexKind::Serum { side } => match side {
+                Side::Bid => 10,
+                Side::Ask => 12,
+            },
+        }
+    }
+}
+
+/// Execute one hop of a route of `authority` and return its output amount
+///
+/// Fails with `SlippageExceeded` if the hop produced less than its `min_amount_out`
+pub fn execute_hop<'info>(
+    authority: Pubkey,
+    hop: &RouteHop,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount_in: u64,
+) -> Result<u64> {
+    require!(amount_in > 0, ErrorCode::InvalidAmount);
+
+    let total_share_bps: u32 = hop.splits.iter().map(|split| split.share_bps as u32).sum();
+    require!(total_share_bps < 10_000, ErrorCode::InvalidRoute);
+
+    // The main swap takes what the splits leave, so it never gets 0
+    let split_amounts: Vec<u64> = hop
+        .splits
+        .iter()
+        .map(|split| (amount_in as u128 * split.share_bps as u128 / 10_000) as u64)
+        .collect();
+    let main_amount = amount_in - split_amounts.iter().sum::<u64>();
+
+    // A split hop is bounded on its summed output only
+    let dex_min_amount_out = if hop.splits.is_empty() { hop.min_amount_out } else { None };
+    let main_accounts = hop_accounts(remaining_accounts, hop.accounts_start, hop.accounts_end)?;
+    let mut amount_out = execute_swap(authority, hop.dex, main_accounts, main_amount, dex_min_amount_out)?;
+
+    for (split, amount) in hop.splits.iter().zip(split_amounts) {
+        if amount == 0 {
+            continue; // Share of a tiny input
+        }
+        let accounts = hop_accounts(remaining_accounts, split.accounts_start, split.accounts_end)?;
+        let split_out = execute_swap(authority, split.dex, accounts, amount, None)?;
+        amount_out = amount_out.checked_add(split_out).ok_or(ErrorCode::Overflow)?;
+    }
+
+    // Fail at this hop instead of running the rest of the route
+    if let Some(min_amount_out) = hop.min_amount_out {
+        require!(amount_out >= min_amount_out, ErrorCode::SlippageExceeded);
+    }
+
+    Ok(amount_out)
+}
+
+fn hop_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    accounts_start: u8,
+    accounts_end: u8,
+) -> Result<&'a [AccountInfo<'info>]> {
+    let accounts = remaining_accounts
+        .get(accounts_start as usize..accounts_end as usize)
+        .ok_or(ErrorCode::InvalidRoute)?;
+    Ok(accounts)
+}
+
+// One swap on one pool, reported by a `HopExecuted` event
+fn execute_swap<'info>(
+    authority: Pubkey,
+    dex: DexKind,
+    accounts: &[AccountInfo<'info>],
+    amount_in: u64,
+    min_amount_out: Option<u64>,
+) -> Result<u64> {
+    require!(accounts.len() == dex.accounts_len(), ErrorCode::InvalidRoute);
+
+    let user_dst = &accounts[dex.user_dst_index()];
+    let dst_start_balance = token_balance(user_dst)?; // Pre-swap balance
+
+    match dex {
+        DexKind::Orca => {
+            let data = orca_swap_data(amount_in, min_amount_out);
+            invoke_amm(accounts, data.try_to_vec()?)?;
+        }
+        DexKind::Saber => {
+            let data = saber_swap_data(amount_in, min_amount_out);
+            invoke_amm(accounts, data.try_to_vec()?)?;
+        }
+        DexKind::Mercurial => {
+            let data = mercurial_swap_data(amount_in, min_amount_out);
+            invoke_amm(accounts, data.try_to_vec()?)?;
+        }
+        DexKind::AldrinV1 { is_inverted } | DexKind::AldrinV2 { is_inverted } => {
+            let data = aldrin_swap_data(amount_in, is_inverted, min_amount_out)?;
+            invoke_amm(accounts, data)?;
+        }
+        DexKind::Serum { side } => serum_hop(accounts, amount_in, side)?,
+    }
+
+    let swap_amount_out = token_balance(user_dst)?.checked_sub(dst_start_balance)
+        .ok_or(ErrorCode::Overflow)?;
+
+    msg!("{:?} hop amount in: {}, amount out: {}", dex, amount_in, swap_amount_out);
+
+    emit!(HopExecuted {
+        authority,
+        dex,
+        amount_in,
+        amount_out: swap_amount_out,
+    });
+
+    Ok(swap_amount_out)
+}
+
+// The DEX program is the last hop account
+fn invoke_amm<'info>(accounts: &[AccountInfo<'info>], data: Vec<u8>) -> Result<()> {
+    let (program, ix_accounts) = accounts.split_last().ok_or(ErrorCode::InvalidRoute)?;
+    invoke_forwarded(program, ix_accounts, data)
+}
+
+fn token_balance(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    let token_account = TokenAccount::try_deserialize(&mut &data[..])?;
+    Ok(token_account.amount)
+}
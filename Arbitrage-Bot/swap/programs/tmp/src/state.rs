@@ -3,8 +3,11 @@ use anchor_lang::prelude::*;
 #[account]
 #[derive(Default)]
 pub struct SwapState { 
+    pub authority: Pubkey,         // Operator wallet which owns this state
+    pub bump: u8,                  // PDA bump
     pub start_balance: u64,        // Start of swap balance
     pub swap_input: u64,           // Output of swap (input for next swap)
+    pub flash_borrowed: u64,       // Flash loan to repay before the profit check
     pub is_valid: bool,            // Safety flag to prevent invalid swaps
     pub total_swaps: u64,          // Total number of swaps executed
     pub total_volume: u64,         // Total volume processed
@@ -15,5 +18,25 @@ pub struct SwapState {
 }
 
 impl SwapState {
-    pub const INIT_SPACE: usize = 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 8 + 8;
+    pub const INIT_SPACE: usize = 32 + 1 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 8 + 8;
+}
+
+/// Swap statistics of one start mint of an operator wallet
+#[account]
+#[derive(Default)]
+pub struct MintStats {
+    pub authority: Pubkey,         // Operator wallet which owns these stats
+    pub mint: Pubkey,              // Start mint of the routes
+    pub bump: u8,                  // PDA bump
+    pub treasury: Pubkey,          // Token account which receives swept profit
+    pub working_balance: u64,      // Balance kept in the operator wallet by a sweep
+    pub total_swaps: u64,          // Routes started with this mint
+    pub total_volume: u64,         // Total input of those routes
+    pub total_profit: u64,         // Total profit in this mint
+    pub total_swept: u64,          // Total moved to the treasury
+    pub last_swap_time: i64,       // Timestamp of last profitable route
+}
+
+impl MintStats {
+    pub const INIT_SPACE: usize = 32 + 32 + 1 + 32 + 8 + 8 + 8 + 8 + 8 + 8;
 }
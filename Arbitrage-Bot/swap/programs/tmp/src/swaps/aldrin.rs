@@ -9,14 +9,12 @@ use sha2::{Digest, Sha256};
 
 use crate::state::SwapState;
 
-/// Execute a swap on Aldrin V1 DEX
-pub fn _aldrin_swap_v1<'info>(
-    ctx: &Context<'_, '_, '_, 'info, AldrinSwapV1<'info>>, 
+/// Instruction data of an Aldrin (V1 or V2) swap
+pub fn aldrin_swap_data(
     amount_in: u64,
     is_inverted: bool,
-) -> Result<()> {
-    require!(amount_in > 0, crate::error::ErrorCode::InvalidAmount);
-
+    min_amount_out: Option<u64>,
+) -> Result<Vec<u8>> {
     // Generate Anchor method discriminator 
     let key = "global:swap".to_string();
     let mut hasher = Sha256::new(); 
@@ -25,7 +23,7 @@ pub fn _aldrin_swap_v1<'info>(
     let fcn_name = &result.as_slice()[..8];
 
     let amount_in_bytes = &amount_in.try_to_vec()?[..];
-    let amount_out_bytes = &(0 as u64).try_to_vec()?[..];
+    let amount_out_bytes = &min_amount_out.unwrap_or(0).try_to_vec()?[..];
     let bid_ask_flag = if is_inverted { 1 } else { 0 }; // 0 = bid, 1 = ask 
     let bid_ask = &[bid_ask_flag];
     let data = [
@@ -35,6 +33,20 @@ pub fn _aldrin_swap_v1<'info>(
         bid_ask, 
     ].concat();
 
+    Ok(data)
+}
+
+/// Execute a swap on Aldrin V1 DEX
+pub fn _aldrin_swap_v1<'info>(
+    ctx: &Context<'_, '_, '_, 'info, AldrinSwapV1<'info>>, 
+    amount_in: u64,
+    is_inverted: bool,
+    min_amount_out: Option<u64>,
+) -> Result<()> {
+    require!(amount_in > 0, crate::error::ErrorCode::InvalidAmount);
+
+    let data = aldrin_swap_data(amount_in, is_inverted, min_amount_out)?;
+
     let ix_accounts = vec![
         AccountMeta::new_readonly(*ctx.accounts.pool_public_key.key, false),
         AccountMeta::new_readonly(*ctx.accounts.pool_signer.key, false),
@@ -85,26 +97,11 @@ pub fn _aldrin_swap_v2<'info>(
     ctx: &Context<'_, '_, '_, 'info, AldrinSwapV2<'info>>, 
     amount_in: u64,
     is_inverted: bool,
+    min_amount_out: Option<u64>,
 ) -> Result<()> {
     require!(amount_in > 0, crate::error::ErrorCode::InvalidAmount);
 
-    // Generate Anchor method discriminator 
-    let key = "global:swap".to_string();
-    let mut hasher = Sha256::new(); 
-    hasher.update(key);
-    let result = hasher.finalize();
-    let fcn_name = &result.as_slice()[..8];
-
-    let amount_in_bytes = &amount_in.try_to_vec()?[..];
-    let amount_out_bytes = &(0 as u64).try_to_vec()?[..];
-    let bid_ask_flag = if is_inverted { 1 } else { 0 }; // 0 = bid, 1 = ask 
-    let bid_ask = &[bid_ask_flag];
-    let data = [
-        fcn_name, 
-        amount_in_bytes, 
-        amount_out_bytes, 
-        bid_ask, 
-    ].concat();
+    let data = aldrin_swap_data(amount_in, is_inverted, min_amount_out)?;
 
     let ix_accounts = vec![
         AccountMeta::new_readonly(*ctx.accounts.pool_public_key.key, false),
@@ -172,7 +169,12 @@ pub struct AldrinSwapV1<'info> {
     pub user_quote_ata: Account<'info, TokenAccount>,
     pub aldrin_v1_program: AccountInfo<'info>,
     pub token_program: AccountInfo<'info>,
-    #[account(mut, seeds = [b"swap_state"], bump)] 
+    #[account(
+        mut, 
+        seeds = [b"swap_state", user_transfer_authority.key().as_ref()], 
+        bump = swap_state.bump,
+        constraint = swap_state.authority == user_transfer_authority.key() @ crate::error::ErrorCode::InvalidAuthority,
+    )] 
     pub swap_state: Account<'info, SwapState>,
 }
 
@@ -196,6 +198,11 @@ pub struct AldrinSwapV2<'info> {
     pub aldrin_v2_program: AccountInfo<'info>,
     pub curve: AccountInfo<'info>, // V2 difference! 
     pub token_program: AccountInfo<'info>,
-    #[account(mut, seeds = [b"swap_state"], bump)] 
+    #[account(
+        mut, 
+        seeds = [b"swap_state", user_transfer_authority.key().as_ref()], 
+        bump = swap_state.bump,
+        constraint = swap_state.authority == user_transfer_authority.key() @ crate::error::ErrorCode::InvalidAuthority,
+    )] 
     pub swap_state: Account<'info, SwapState>,
 }
@@ -8,18 +8,24 @@ use anchor_lang::{Accounts};
 use crate::ix_data::SwapData;
 use crate::state::SwapState;
 
+/// Instruction data of a Orca swap
+pub fn orca_swap_data(amount_in: u64, min_amount_out: Option<u64>) -> SwapData {
+    SwapData {
+        instruction: 1, // Swap instruction 
+        amount_in: amount_in,
+        minimum_amount_out: min_amount_out.unwrap_or(0), // 0 = no safety check
+    }
+}
+
 /// Execute a swap on Orca DEX
 pub fn _orca_swap<'info>(
     ctx: &Context<'_, '_, '_, 'info, OrcaSwap<'info>>, 
-    amount_in: u64
+    amount_in: u64,
+    min_amount_out: Option<u64>,
 ) -> Result<()> {
     require!(amount_in > 0, crate::error::ErrorCode::InvalidAmount);
     
-    let data = SwapData {
-        instruction: 1, // Swap instruction 
-        amount_in: amount_in,
-        minimum_amount_out: 0, // No safety check for now
-    };
+    let data = orca_swap_data(amount_in, min_amount_out);
 
     let ix_accounts = vec![
         AccountMeta::new_readonly(*ctx.accounts.token_swap.key, false),
@@ -85,7 +91,12 @@ pub struct OrcaSwap<'info> {
     pub fee_account: AccountInfo<'info>,
     pub token_program: AccountInfo<'info>,
     pub token_swap_program: AccountInfo<'info>,
-    #[account(mut, seeds = [b"swap_state"], bump)] 
+    #[account(
+        mut, 
+        seeds = [b"swap_state", user_transfer_authority.key().as_ref()], 
+        bump = swap_state.bump,
+        constraint = swap_state.authority == user_transfer_authority.key() @ crate::error::ErrorCode::InvalidAuthority,
+    )] 
     pub swap_state: Account<'info, SwapState>,
 }
 
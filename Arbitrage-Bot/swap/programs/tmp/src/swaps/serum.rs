@@ -18,6 +18,22 @@ pub fn _init_open_order<'info>(ctx: Context<InitOpenOrder>) -> Result<()> {
     Ok(())
 }
 
+/// Move the free funds of an open orders account back to the owner's wallets
+pub fn _settle_open_orders<'info>(ctx: Context<SettleOpenOrders>) -> Result<()> {
+    let ctx = CpiContext::new(ctx.accounts.dex_program.clone(), ctx.accounts.into());
+    dex::settle_funds(ctx)?;
+    msg!("Open orders account settled successfully");
+    Ok(())
+}
+
+/// Close an open orders account and send its rent to `destination`
+pub fn _close_open_orders<'info>(ctx: Context<CloseAccount>) -> Result<()> {
+    let ctx = CpiContext::new(ctx.accounts.dex_program.clone(), ctx.accounts.into());
+    dex::close_open_orders(ctx)?;
+    msg!("Open orders account closed successfully");
+    Ok(())
+}
+
 /// Execute a swap on Serum DEX
 pub fn _serum_swap<'info>(
     ctx: &Context<'_, '_, '_, 'info, SerumSwap<'info>>,
@@ -41,6 +57,54 @@ pub fn _serum_swap<'info>(
     Ok(())
 }
 
+/// Execute a swap on Serum DEX over the accounts of a route hop
+///
+/// The accounts are laid out like `SerumSwap` without the `swap_state`
+pub fn serum_hop<'info>(
+    accounts: &[AccountInfo<'info>],
+    amount_in: u64,
+    side: Side,
+) -> Result<()> {
+    require!(amount_in > 0, crate::error::ErrorCode::InvalidAmount);
+
+    let [
+        market, open_orders, request_queue, event_queue, bids, asks,
+        order_payer_token_account, coin_vault, pc_vault, vault_signer, coin_wallet,
+        authority, pc_wallet, dex_program, token_program, rent,
+    ] = accounts else {
+        return err!(crate::error::ErrorCode::InvalidRoute);
+    };
+
+    let orderbook = OrderbookClient {
+        market: MarketAccounts {
+            market: market.clone(),
+            open_orders: open_orders.clone(),
+            request_queue: request_queue.clone(),
+            event_queue: event_queue.clone(),
+            bids: bids.clone(),
+            asks: asks.clone(),
+            order_payer_token_account: order_payer_token_account.clone(),
+            coin_vault: coin_vault.clone(),
+            pc_vault: pc_vault.clone(),
+            vault_signer: vault_signer.clone(),
+            coin_wallet: Account::try_from(coin_wallet)?,
+        },
+        authority: authority.clone(),
+        pc_wallet: pc_wallet.clone(),
+        dex_program: dex_program.clone(),
+        token_program: token_program.clone(),
+        rent: rent.clone(),
+    };
+    match side {
+        Side::Bid => orderbook.buy(amount_in, None)?,
+        Side::Ask => orderbook.sell(amount_in, None)?,
+    };
+    orderbook.settle(None)?; // Instant settle 
+
+    msg!("Serum hop executed successfully with amount: {}, side: {:?}", amount_in, side);
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct InitOpenOrder<'info> {
     #[account(mut)]
@@ -63,16 +127,53 @@ impl<'info> From<&mut InitOpenOrder<'info>> for dex::InitOpenOrders<'info> {
     }
 }
 
+#[derive(Accounts)]
+pub struct SettleOpenOrders<'info> {
+    #[account(mut)]
+    pub market: AccountInfo<'info>,
+    #[account(mut)]
+    pub open_orders: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub coin_vault: AccountInfo<'info>,
+    #[account(mut)]
+    pub pc_vault: AccountInfo<'info>,
+    #[account(mut)]
+    pub coin_wallet: AccountInfo<'info>,
+    #[account(mut)]
+    pub pc_wallet: AccountInfo<'info>,
+    pub vault_signer: AccountInfo<'info>,
+    pub dex_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> From<&mut SettleOpenOrders<'info>> for dex::SettleFunds<'info> {
+    fn from(accs: &mut SettleOpenOrders<'info>) -> dex::SettleFunds<'info> {
+        dex::SettleFunds {
+            market: accs.market.clone(),
+            open_orders: accs.open_orders.clone(),
+            open_orders_authority: accs.authority.clone(),
+            coin_vault: accs.coin_vault.clone(),
+            pc_vault: accs.pc_vault.clone(),
+            coin_wallet: accs.coin_wallet.clone(),
+            pc_wallet: accs.pc_wallet.clone(),
+            vault_signer: accs.vault_signer.clone(),
+            token_program: accs.token_program.clone(),
+        }
+    }
+}
+
 #[derive(Accounts)]
 pub struct CloseAccount<'info> {
     #[account(mut)]
-    open_orders: AccountInfo<'info>,
+    pub open_orders: AccountInfo<'info>,
     #[account(signer)]
-    authority: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
     #[account(mut)]
-    destination: AccountInfo<'info>,
-    market: AccountInfo<'info>,
-    dex_program: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+    pub market: AccountInfo<'info>,
+    pub dex_program: AccountInfo<'info>,
 }
 
 impl<'info> From<&mut CloseAccount<'info>> for dex::CloseOpenOrders<'info> {
@@ -101,7 +202,12 @@ pub struct SerumSwap<'info> {
     pub token_program: AccountInfo<'info>,
     // Sysvars
     pub rent: AccountInfo<'info>,
-    #[account(mut, seeds = [b"swap_state"], bump)] 
+    #[account(
+        mut, 
+        seeds = [b"swap_state", authority.key().as_ref()], 
+        bump = swap_state.bump,
+        constraint = swap_state.authority == authority.key() @ crate::error::ErrorCode::InvalidAuthority,
+    )] 
     pub swap_state: Account<'info, SwapState>,
 }
 
@@ -299,7 +405,7 @@ pub struct MarketAccounts<'info> {
     pub coin_wallet: Account<'info, TokenAccount>,  // Base currency wallet
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
 pub enum Side {
     Bid,
     Ask,
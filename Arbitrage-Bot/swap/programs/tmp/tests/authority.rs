@@ -0,0 +1,119 @@
+mod common;
+
+use common::*;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+use tmp::error::ErrorCode;
+
+const SRC_BALANCE: u64 = 1_000;
+
+struct Setup {
+    context: ProgramTestContext,
+    authority: Keypair,
+    intruder: Keypair,
+    mint: Pubkey,
+    src: Pubkey,          // the authority's
+    intruder_src: Pubkey, // same mint, owned by the intruder
+}
+
+// the authority's swap state and mint stats, and a second wallet holding
+// the same mint without any state of its own
+async fn setup() -> Setup {
+    let mut program_test = program_test();
+    let authority = Keypair::new();
+    let intruder = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let src = Pubkey::new_unique();
+    let intruder_src = Pubkey::new_unique();
+
+    add_mint(&mut program_test, &mint, &Pubkey::new_unique());
+    add_token_account(&mut program_test, &src, &mint, &authority.pubkey(), SRC_BALANCE);
+    add_token_account(&mut program_test, &intruder_src, &mint, &intruder.pubkey(), SRC_BALANCE);
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let ixs = [
+        init_program_ix(&authority.pubkey(), &payer),
+        init_mint_stats_ix(&authority.pubkey(), &payer, &mint, &src, 0),
+    ];
+    process(&mut context, &ixs, &[&authority]).await.unwrap();
+
+    Setup { context, authority, intruder, mint, src, intruder_src }
+}
+
+// point an ix built for `signer` at the swap state and mint stats of `authority`
+fn against(mut ix: Instruction, signer: &Pubkey, authority: &Pubkey, mint: &Pubkey) -> Instruction {
+    for meta in ix.accounts.iter_mut() {
+        if meta.pubkey == swap_state_address(signer) {
+            meta.pubkey = swap_state_address(authority);
+        } else if meta.pubkey == mint_stats_address(signer, mint) {
+            meta.pubkey = mint_stats_address(authority, mint);
+        }
+    }
+    ix
+}
+
+// each ix built by the intruder against the authority's state, with the
+// intruder's own tokens or the authority's
+fn intruder_ixs(setup: &Setup) -> Vec<Instruction> {
+    let intruder = setup.intruder.pubkey();
+    let authority = setup.authority.pubkey();
+    let mut ixs = vec![];
+    for src in [setup.intruder_src, setup.src] {
+        ixs.push(start_swap_ix(&intruder, &src, &setup.mint, 100, None));
+        ixs.push(route_ix(&intruder, &src, &setup.mint, 100, vec![], vec![]));
+        ixs.push(profit_or_revert_ix(&intruder, &src, &setup.mint, None));
+    }
+    ixs.into_iter()
+        .map(|ix| against(ix, &intruder, &authority, &setup.mint))
+        .collect()
+}
+
+fn is_foreign_state_error(err: &TransactionError) -> bool {
+    let seeds: u32 = anchor_lang::error::ErrorCode::ConstraintSeeds.into();
+    *err == custom_error(0, ErrorCode::InvalidAuthority)
+        || *err
+            == TransactionError::InstructionError(
+                0,
+                solana_program::instruction::InstructionError::Custom(seeds),
+            )
+}
+
+#[tokio::test]
+async fn another_wallet_cant_use_the_swap_state() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+
+    for ix in intruder_ixs(&setup) {
+        let err = process(&mut setup.context, &[ix], &[&setup.intruder])
+            .await
+            .unwrap_err();
+        assert!(is_foreign_state_error(&err), "{:?}", err);
+    }
+
+    let state = swap_state(&mut setup.context, &authority).await;
+    assert!(!state.is_valid);
+    assert_eq!(state.total_swaps, 0);
+    assert_eq!(token_balance(&mut setup.context, &setup.src).await, SRC_BALANCE);
+}
+
+#[tokio::test]
+async fn another_wallet_cant_end_the_authoritys_swap() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+
+    // the authority's swap is open, the intruder tries to close it
+    let ixs = [start_swap_ix(&authority, &setup.src, &setup.mint, 100, None)];
+    process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap();
+
+    let intruder = setup.intruder.pubkey();
+    let ix = profit_or_revert_ix(&intruder, &setup.intruder_src, &setup.mint, None);
+    let ix = against(ix, &intruder, &authority, &setup.mint);
+    let err = process(&mut setup.context, &[ix], &[&setup.intruder])
+        .await
+        .unwrap_err();
+    assert!(is_foreign_state_error(&err), "{:?}", err);
+    assert!(swap_state(&mut setup.context, &authority).await.is_valid);
+}
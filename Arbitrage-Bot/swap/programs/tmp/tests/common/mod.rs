@@ -0,0 +1,350 @@
+#![allow(dead_code)]
+
+pub mod token_swap;
+
+use anchor_lang::{
+    AccountDeserialize, AccountSerialize, AnchorDeserialize, Discriminator, Event, InstructionData,
+    ToAccountMetas,
+};
+use base64::Engine;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use tmp::ix_data::{FlashLoan, RouteHop};
+use tmp::state::{MintStats, SwapState};
+
+// anchor's entrypoint wants the accounts to live for 'info
+fn tmp_processor(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    tmp::entry(program_id, accounts, data)
+}
+
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("tmp", tmp::id(), processor!(tmp_processor));
+    program_test.add_program(
+        "mock_lending",
+        mock_lending::id(),
+        processor!(mock_lending::process_instruction),
+    );
+    program_test.add_program(
+        "spl_token_swap",
+        spl_token_swap::id(),
+        processor!(spl_token_swap::processor::Processor::process),
+    );
+    program_test
+}
+
+pub fn add_mint(program_test: &mut ProgramTest, mint: &Pubkey, authority: &Pubkey) {
+    program_test.add_packable_account(
+        *mint,
+        u32::MAX as u64,
+        &spl_token::state::Mint {
+            mint_authority: COption::Some(*authority),
+            supply: u64::MAX / 2,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &spl_token::id(),
+    );
+}
+
+pub fn add_token_account(
+    program_test: &mut ProgramTest,
+    address: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) {
+    program_test.add_packable_account(
+        *address,
+        u32::MAX as u64,
+        &spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        },
+        &spl_token::id(),
+    );
+}
+
+pub fn swap_state_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"swap_state", authority.as_ref()], &tmp::id()).0
+}
+
+/// Add the swap state of `authority` as if `init_program` already ran
+pub fn add_swap_state(program_test: &mut ProgramTest, authority: &Pubkey, mut swap_state: SwapState) {
+    let (address, bump) =
+        Pubkey::find_program_address(&[b"swap_state", authority.as_ref()], &tmp::id());
+    swap_state.authority = *authority;
+    swap_state.bump = bump;
+
+    let mut data = vec![];
+    swap_state.try_serialize(&mut data).unwrap();
+    data.resize(8 + SwapState::INIT_SPACE, 0);
+    program_test.add_account(
+        address,
+        Account {
+            lamports: u32::MAX as u64,
+            data,
+            owner: tmp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+pub fn mint_stats_address(authority: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"mint_stats", authority.as_ref(), mint.as_ref()],
+        &tmp::id(),
+    )
+    .0
+}
+
+pub fn init_program_ix(authority: &Pubkey, payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: tmp::id(),
+        accounts: tmp::accounts::InitSwapState {
+            swap_state: swap_state_address(authority),
+            authority: *authority,
+            payer: *payer,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: tmp::instruction::InitProgram {}.data(),
+    }
+}
+
+pub fn init_mint_stats_ix(
+    authority: &Pubkey,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    treasury: &Pubkey,
+    working_balance: u64,
+) -> Instruction {
+    Instruction {
+        program_id: tmp::id(),
+        accounts: tmp::accounts::InitMintStats {
+            mint_stats: mint_stats_address(authority, mint),
+            mint: *mint,
+            treasury: *treasury,
+            authority: *authority,
+            payer: *payer,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: tmp::instruction::InitMintStats { working_balance }.data(),
+    }
+}
+
+pub fn sweep_profit_ix(authority: &Pubkey, src: &Pubkey, mint: &Pubkey, treasury: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: tmp::id(),
+        accounts: tmp::accounts::SweepProfit {
+            src: *src,
+            treasury: *treasury,
+            swap_state: swap_state_address(authority),
+            mint_stats: mint_stats_address(authority, mint),
+            authority: *authority,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: tmp::instruction::SweepProfit {}.data(),
+    }
+}
+
+pub fn start_dust_sweep_ix(authority: &Pubkey, src: &Pubkey, swap_input: u64) -> Instruction {
+    Instruction {
+        program_id: tmp::id(),
+        accounts: tmp::accounts::StartDustSweep {
+            src: *src,
+            swap_state: swap_state_address(authority),
+            authority: *authority,
+        }
+        .to_account_metas(None),
+        data: tmp::instruction::StartDustSweep { swap_input }.data(),
+    }
+}
+
+pub fn end_dust_sweep_ix(authority: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: tmp::id(),
+        accounts: tmp::accounts::EndDustSweep {
+            swap_state: swap_state_address(authority),
+            authority: *authority,
+        }
+        .to_account_metas(None),
+        data: tmp::instruction::EndDustSweep {}.data(),
+    }
+}
+
+fn token_and_swap_state(authority: &Pubkey, src: &Pubkey, mint: &Pubkey) -> Vec<AccountMeta> {
+    tmp::accounts::TokenAndSwapState {
+        src: *src,
+        swap_state: swap_state_address(authority),
+        mint_stats: mint_stats_address(authority, mint),
+        authority: *authority,
+    }
+    .to_account_metas(None)
+}
+
+pub fn start_swap_ix(
+    authority: &Pubkey,
+    src: &Pubkey,
+    mint: &Pubkey,
+    swap_input: u64,
+    flash_loan: Option<(FlashLoan, Vec<AccountMeta>)>,
+) -> Instruction {
+    let mut accounts = token_and_swap_state(authority, src, mint);
+    let flash_loan = flash_loan.map(|(flash_loan, lending_accounts)| {
+        accounts.extend(lending_accounts);
+        flash_loan
+    });
+    Instruction {
+        program_id: tmp::id(),
+        accounts,
+        data: tmp::instruction::StartSwap { swap_input, flash_loan }.data(),
+    }
+}
+
+pub fn profit_or_revert_ix(
+    authority: &Pubkey,
+    src: &Pubkey,
+    mint: &Pubkey,
+    flash_repay: Option<(FlashLoan, Vec<AccountMeta>)>,
+) -> Instruction {
+    let mut accounts = token_and_swap_state(authority, src, mint);
+    let flash_repay = flash_repay.map(|(flash_repay, lending_accounts)| {
+        accounts.extend(lending_accounts);
+        flash_repay
+    });
+    Instruction {
+        program_id: tmp::id(),
+        accounts,
+        data: tmp::instruction::ProfitOrRevert { flash_repay }.data(),
+    }
+}
+
+pub fn route_ix(
+    authority: &Pubkey,
+    src: &Pubkey,
+    mint: &Pubkey,
+    swap_input: u64,
+    hops: Vec<RouteHop>,
+    hop_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut accounts = token_and_swap_state(authority, src, mint);
+    accounts.extend(hop_accounts);
+    Instruction {
+        program_id: tmp::id(),
+        accounts,
+        data: tmp::instruction::Route { swap_input, hops }.data(),
+    }
+}
+
+pub async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+/// Process a transaction which must succeed and return its logs
+pub async fn process_logs(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signers: &[&Keypair],
+) -> Vec<String> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    let res = context
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    res.result.unwrap();
+    res.metadata.unwrap().log_messages
+}
+
+/// Events of type `T` in the logs, in emit order
+pub fn events<T: Event + AnchorDeserialize>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .filter(|data| data.starts_with(&T::discriminator()))
+        .map(|data| T::deserialize(&mut &data[8..]).unwrap())
+        .collect()
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+pub async fn swap_state(context: &mut ProgramTestContext, authority: &Pubkey) -> SwapState {
+    let account = context
+        .banks_client
+        .get_account(swap_state_address(authority))
+        .await
+        .unwrap()
+        .unwrap();
+    SwapState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn mint_stats(
+    context: &mut ProgramTestContext,
+    authority: &Pubkey,
+    mint: &Pubkey,
+) -> MintStats {
+    let account = context
+        .banks_client
+        .get_account(mint_stats_address(authority, mint))
+        .await
+        .unwrap()
+        .unwrap();
+    MintStats::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub fn custom_error(ix_idx: u8, error: tmp::error::ErrorCode) -> TransactionError {
+    TransactionError::InstructionError(
+        ix_idx,
+        solana_program::instruction::InstructionError::Custom(error.into()),
+    )
+}
@@ -0,0 +1,186 @@
+//! Constant product pool of the SPL token-swap program, which speaks the
+//! same swap instruction as Orca
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_option::COption,
+    pubkey::Pubkey,
+};
+use solana_program_test::*;
+use solana_sdk::account::Account;
+use spl_token_swap::curve::{
+    base::{CurveType, SwapCurve},
+    constant_product::ConstantProductCurve,
+    fees::Fees,
+};
+use spl_token_swap::state::SwapVersion;
+
+use super::{add_token_account, swap_state_address};
+
+pub struct TokenSwapPool {
+    pub swap: Pubkey,
+    pub authority: Pubkey,
+    pub nonce: u8,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub pool_mint: Pubkey,
+    pub fee_account: Pubkey,
+    pub destination: Pubkey,
+}
+
+impl TokenSwapPool {
+    /// Add the accounts of a pool holding `reserve_a` and `reserve_b`, it
+    /// still has to be initialized with `initialize_ix`
+    pub fn add(
+        program_test: &mut ProgramTest,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        reserve_a: u64,
+        reserve_b: u64,
+    ) -> Self {
+        let swap = Pubkey::new_unique();
+        let (authority, nonce) =
+            Pubkey::find_program_address(&[swap.as_ref()], &spl_token_swap::id());
+        let pool = TokenSwapPool {
+            swap,
+            authority,
+            nonce,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            token_a: Pubkey::new_unique(),
+            token_b: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            fee_account: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+        };
+
+        program_test.add_account(
+            swap,
+            Account {
+                lamports: u32::MAX as u64,
+                data: vec![0; SwapVersion::LATEST_LEN],
+                owner: spl_token_swap::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        // the pool mint must start without supply
+        program_test.add_packable_account(
+            pool.pool_mint,
+            u32::MAX as u64,
+            &spl_token::state::Mint {
+                mint_authority: COption::Some(authority),
+                supply: 0,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            &spl_token::id(),
+        );
+        add_token_account(program_test, &pool.token_a, mint_a, &authority, reserve_a);
+        add_token_account(program_test, &pool.token_b, mint_b, &authority, reserve_b);
+        let fee_owner = Pubkey::new_unique();
+        add_token_account(program_test, &pool.fee_account, &pool.pool_mint, &fee_owner, 0);
+        add_token_account(program_test, &pool.destination, &pool.pool_mint, &fee_owner, 0);
+
+        pool
+    }
+
+    pub fn initialize_ix(&self) -> Instruction {
+        let fees = Fees {
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            owner_trade_fee_numerator: 5,
+            owner_trade_fee_denominator: 10_000,
+            owner_withdraw_fee_numerator: 0,
+            owner_withdraw_fee_denominator: 0,
+            host_fee_numerator: 0,
+            host_fee_denominator: 0,
+        };
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Box::new(ConstantProductCurve {}),
+        };
+        spl_token_swap::instruction::initialize(
+            &spl_token_swap::id(),
+            &spl_token::id(),
+            &self.swap,
+            &self.authority,
+            &self.token_a,
+            &self.token_b,
+            &self.pool_mint,
+            &self.fee_account,
+            &self.destination,
+            self.nonce,
+            fees,
+            swap_curve,
+        )
+        .unwrap()
+    }
+
+    // (pool_src, pool_dst) of a swap selling `mint_in`
+    fn vaults(&self, mint_in: &Pubkey) -> (Pubkey, Pubkey) {
+        if *mint_in == self.mint_a {
+            (self.token_a, self.token_b)
+        } else {
+            (self.token_b, self.token_a)
+        }
+    }
+
+    /// `orca_swap` hop of `authority` selling `mint_in` from `user_src`
+    pub fn orca_swap_ix(
+        &self,
+        authority: &Pubkey,
+        user_src: &Pubkey,
+        user_dst: &Pubkey,
+        mint_in: &Pubkey,
+        min_amount_out: Option<u64>,
+    ) -> Instruction {
+        let (pool_src, pool_dst) = self.vaults(mint_in);
+        Instruction {
+            program_id: tmp::id(),
+            accounts: tmp::accounts::OrcaSwap {
+                token_swap: self.swap,
+                authority: self.authority,
+                user_transfer_authority: *authority,
+                user_src: *user_src,
+                pool_src,
+                pool_dst,
+                user_dst: *user_dst,
+                pool_mint: self.pool_mint,
+                fee_account: self.fee_account,
+                token_program: spl_token::id(),
+                token_swap_program: spl_token_swap::id(),
+                swap_state: swap_state_address(authority),
+            }
+            .to_account_metas(None),
+            data: tmp::instruction::OrcaSwap { min_amount_out }.data(),
+        }
+    }
+
+    /// Accounts of an Orca hop of a `route` (swap instruction order, then the program)
+    pub fn route_hop_accounts(
+        &self,
+        authority: &Pubkey,
+        user_src: &Pubkey,
+        user_dst: &Pubkey,
+        mint_in: &Pubkey,
+    ) -> Vec<AccountMeta> {
+        let (pool_src, pool_dst) = self.vaults(mint_in);
+        vec![
+            AccountMeta::new_readonly(self.swap, false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*user_src, false),
+            AccountMeta::new(pool_src, false),
+            AccountMeta::new(pool_dst, false),
+            AccountMeta::new(*user_dst, false),
+            AccountMeta::new(self.pool_mint, false),
+            AccountMeta::new(self.fee_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_token_swap::id(), false),
+        ]
+    }
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::token_swap::TokenSwapPool;
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use tmp::error::ErrorCode;
+
+const DUST: u64 = 1_000;
+
+struct Setup {
+    context: ProgramTestContext,
+    authority: Keypair,
+    dust: Pubkey, // ATA of the intermediate mint
+    base: Pubkey, // ATA of the base mint
+    pool: TokenSwapPool,
+}
+
+// dust of mint A to sell back to the base mint B -- no mint stats needed
+async fn setup() -> Setup {
+    let mut program_test = program_test();
+    let authority = Keypair::new();
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+    let dust = Pubkey::new_unique();
+    let base = Pubkey::new_unique();
+
+    add_mint(&mut program_test, &mint_a, &Pubkey::new_unique());
+    add_mint(&mut program_test, &mint_b, &Pubkey::new_unique());
+    add_token_account(&mut program_test, &dust, &mint_a, &authority.pubkey(), DUST);
+    add_token_account(&mut program_test, &base, &mint_b, &authority.pubkey(), 0);
+    let pool = TokenSwapPool::add(&mut program_test, &mint_a, &mint_b, 1_000_000, 1_000_000);
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let ixs = [pool.initialize_ix(), init_program_ix(&authority.pubkey(), &payer)];
+    process(&mut context, &ixs, &[&authority]).await.unwrap();
+
+    Setup { context, authority, dust, base, pool }
+}
+
+#[tokio::test]
+async fn dust_is_swept_to_the_base_mint() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+    let mint_a = setup.pool.mint_a;
+
+    let ixs = [
+        start_dust_sweep_ix(&authority, &setup.dust, DUST),
+        setup.pool.orca_swap_ix(&authority, &setup.dust, &setup.base, &mint_a, Some(1)),
+        end_dust_sweep_ix(&authority),
+    ];
+    process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut setup.context, &setup.dust).await, 0);
+    let swept = token_balance(&mut setup.context, &setup.base).await;
+    assert!(swept > 0);
+
+    // no profit or stats for a sweep
+    let state = swap_state(&mut setup.context, &authority).await;
+    assert!(!state.is_valid);
+    assert_eq!(state.swap_input, swept);
+    assert_eq!(state.total_swaps, 0);
+    assert_eq!(state.total_profit, 0);
+}
+
+#[tokio::test]
+async fn sweep_above_the_balance_is_rejected() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+
+    let ixs = [start_dust_sweep_ix(&authority, &setup.dust, DUST + 1)];
+    let err = process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::NotEnoughFunds));
+}
+
+#[tokio::test]
+async fn sweep_hop_below_its_bound_is_rejected() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+    let mint_a = setup.pool.mint_a;
+
+    let ixs = [
+        start_dust_sweep_ix(&authority, &setup.dust, DUST),
+        setup.pool.orca_swap_ix(&authority, &setup.dust, &setup.base, &mint_a, Some(DUST * 2)),
+        end_dust_sweep_ix(&authority),
+    ];
+    let err = process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap_err();
+    assert!(matches!(
+        err,
+        solana_sdk::transaction::TransactionError::InstructionError(1, _)
+    ));
+}
+
+#[tokio::test]
+async fn end_without_start_is_rejected() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+
+    let ixs = [end_dust_sweep_ix(&authority)];
+    let err = process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::InvalidState));
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use common::*;
+use mock_lending::LendingInstruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use tmp::error::ErrorCode;
+use tmp::ix_data::FlashLoan;
+
+const VAULT_BALANCE: u64 = 1_000_000;
+const SRC_BALANCE: u64 = 100;
+const BORROW: u64 = 10_000;
+const FEE: u64 = 10;
+
+struct Setup {
+    context: ProgramTestContext,
+    authority: Keypair,
+    mint: Pubkey,
+    src: Pubkey,
+    vault: Pubkey,
+    donor: Pubkey,
+}
+
+// the route is faked by a transfer from `donor`, so only the flash loan
+// wrapping is under test here
+async fn setup() -> Setup {
+    let mut program_test = program_test();
+    let authority = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let src = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let donor = Pubkey::new_unique();
+    let (vault_authority, _) = mock_lending::vault_authority();
+
+    add_mint(&mut program_test, &mint, &Pubkey::new_unique());
+    add_token_account(&mut program_test, &src, &mint, &authority.pubkey(), SRC_BALANCE);
+    add_token_account(&mut program_test, &donor, &mint, &authority.pubkey(), 1_000);
+    add_token_account(&mut program_test, &vault, &mint, &vault_authority, VAULT_BALANCE);
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let ixs = [
+        init_program_ix(&authority.pubkey(), &payer),
+        init_mint_stats_ix(&authority.pubkey(), &payer, &mint, &donor, 0),
+    ];
+    process(&mut context, &ixs, &[&authority]).await.unwrap();
+
+    Setup { context, authority, mint, src, vault, donor }
+}
+
+fn borrow(setup: &Setup, amount: u64) -> (FlashLoan, Vec<AccountMeta>) {
+    let (vault_authority, _) = mock_lending::vault_authority();
+    let flash_loan = FlashLoan {
+        amount,
+        data: LendingInstruction::Borrow { amount }.pack(),
+    };
+    let accounts = vec![
+        AccountMeta::new_readonly(mock_lending::id(), false),
+        AccountMeta::new(setup.vault, false),
+        AccountMeta::new(setup.src, false),
+        AccountMeta::new_readonly(vault_authority, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    (flash_loan, accounts)
+}
+
+fn repay(setup: &Setup, amount: u64) -> (FlashLoan, Vec<AccountMeta>) {
+    let flash_repay = FlashLoan {
+        amount,
+        data: LendingInstruction::Repay { amount }.pack(),
+    };
+    let accounts = vec![
+        AccountMeta::new_readonly(mock_lending::id(), false),
+        AccountMeta::new(setup.src, false),
+        AccountMeta::new(setup.vault, false),
+        AccountMeta::new_readonly(setup.authority.pubkey(), true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    (flash_repay, accounts)
+}
+
+async fn run_route(
+    setup: &mut Setup,
+    route_gain: u64,
+    flash_repay: Option<(FlashLoan, Vec<AccountMeta>)>,
+) -> Result<(), solana_sdk::transaction::TransactionError> {
+    let authority = setup.authority.pubkey();
+    let route_ix = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &setup.donor,
+        &setup.src,
+        &authority,
+        &[],
+        route_gain,
+    )
+    .unwrap();
+    let ixs = [
+        start_swap_ix(
+            &authority,
+            &setup.src,
+            &setup.mint,
+            SRC_BALANCE + BORROW,
+            Some(borrow(setup, BORROW)),
+        ),
+        route_ix,
+        profit_or_revert_ix(&authority, &setup.src, &setup.mint, flash_repay),
+    ];
+    process(&mut setup.context, &ixs, &[&setup.authority]).await
+}
+
+#[tokio::test]
+async fn flash_loan_route_repays_and_keeps_profit() {
+    let mut setup = setup().await;
+    let repay = repay(&setup, BORROW + FEE);
+
+    run_route(&mut setup, 50, Some(repay)).await.unwrap();
+
+    let src = setup.src;
+    let vault = setup.vault;
+    assert_eq!(token_balance(&mut setup.context, &src).await, SRC_BALANCE + 50 - FEE);
+    assert_eq!(token_balance(&mut setup.context, &vault).await, VAULT_BALANCE + FEE);
+
+    let authority = setup.authority.pubkey();
+    let state = swap_state(&mut setup.context, &authority).await;
+    assert_eq!(state.total_profit, 50 - FEE);
+    assert_eq!(state.flash_borrowed, 0);
+    assert!(!state.is_valid);
+}
+
+#[tokio::test]
+async fn flash_loan_fee_counts_against_profit() {
+    let mut setup = setup().await;
+    let repay = repay(&setup, BORROW + FEE);
+
+    // gains exactly the fee -- no profit once the loan is repaid
+    let err = run_route(&mut setup, FEE, Some(repay)).await.unwrap_err();
+    assert_eq!(err, custom_error(2, ErrorCode::NoProfit));
+}
+
+#[tokio::test]
+async fn flash_loan_must_be_repaid() {
+    let mut setup = setup().await;
+
+    let err = run_route(&mut setup, 50, None).await.unwrap_err();
+    assert_eq!(err, custom_error(2, ErrorCode::InvalidFlashLoan));
+}
+
+#[tokio::test]
+async fn flash_loan_repayment_below_borrow_is_rejected() {
+    let mut setup = setup().await;
+    let repay = repay(&setup, BORROW - 1);
+
+    let err = run_route(&mut setup, 50, Some(repay)).await.unwrap_err();
+    assert_eq!(err, custom_error(2, ErrorCode::InvalidFlashLoan));
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use tmp::error::ErrorCode;
+
+const SRC_BALANCE: u64 = 1_000;
+const WORKING_BALANCE: u64 = 600;
+
+struct Setup {
+    context: ProgramTestContext,
+    authority: Keypair,
+    mints: [Pubkey; 2],
+    srcs: [Pubkey; 2],
+    treasury: Pubkey,
+    donor: Pubkey,
+}
+
+// two start mints, each with its own stats and treasury
+async fn setup() -> Setup {
+    let mut program_test = program_test();
+    let authority = Keypair::new();
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let srcs = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let treasuries = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let donor = Pubkey::new_unique();
+
+    let treasury_owner = Pubkey::new_unique();
+    for ((mint, src), treasury) in mints.iter().zip(&srcs).zip(&treasuries) {
+        add_mint(&mut program_test, mint, &Pubkey::new_unique());
+        add_token_account(&mut program_test, src, mint, &authority.pubkey(), SRC_BALANCE);
+        add_token_account(&mut program_test, treasury, mint, &treasury_owner, 0);
+    }
+    add_token_account(&mut program_test, &donor, &mints[0], &authority.pubkey(), 1_000);
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let ixs = [
+        init_program_ix(&authority.pubkey(), &payer),
+        init_mint_stats_ix(&authority.pubkey(), &payer, &mints[0], &treasuries[0], WORKING_BALANCE),
+        init_mint_stats_ix(&authority.pubkey(), &payer, &mints[1], &treasuries[1], WORKING_BALANCE),
+    ];
+    process(&mut context, &ixs, &[&authority]).await.unwrap();
+
+    Setup { context, authority, mints, srcs, treasury: treasuries[0], donor }
+}
+
+#[tokio::test]
+async fn profit_is_recorded_for_the_start_mint() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+    let [mint, other_mint] = setup.mints;
+
+    // the route is faked by a transfer from `donor`
+    let route_ix = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &setup.donor,
+        &setup.srcs[0],
+        &authority,
+        &[],
+        25,
+    )
+    .unwrap();
+    let ixs = [
+        start_swap_ix(&authority, &setup.srcs[0], &mint, 500, None),
+        route_ix,
+        profit_or_revert_ix(&authority, &setup.srcs[0], &mint, None),
+    ];
+    process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap();
+
+    let stats = mint_stats(&mut setup.context, &authority, &mint).await;
+    assert_eq!(stats.total_swaps, 1);
+    assert_eq!(stats.total_volume, 500);
+    assert_eq!(stats.total_profit, 25);
+
+    let other_stats = mint_stats(&mut setup.context, &authority, &other_mint).await;
+    assert_eq!(other_stats.total_swaps, 0);
+    assert_eq!(other_stats.total_profit, 0);
+}
+
+#[tokio::test]
+async fn sweep_moves_balance_above_working_balance() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+    let (src, mint, treasury) = (setup.srcs[0], setup.mints[0], setup.treasury);
+
+    let ixs = [sweep_profit_ix(&authority, &src, &mint, &treasury)];
+    process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut setup.context, &src).await, WORKING_BALANCE);
+    assert_eq!(token_balance(&mut setup.context, &treasury).await, SRC_BALANCE - WORKING_BALANCE);
+    let stats = mint_stats(&mut setup.context, &authority, &mint).await;
+    assert_eq!(stats.total_swept, SRC_BALANCE - WORKING_BALANCE);
+}
+
+#[tokio::test]
+async fn sweep_only_to_configured_treasury() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+    let (src, mint) = (setup.srcs[0], setup.mints[0]);
+
+    // an account of the operator instead of the treasury
+    let ixs = [sweep_profit_ix(&authority, &src, &mint, &setup.donor)];
+    let err = process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::InvalidTreasury));
+}
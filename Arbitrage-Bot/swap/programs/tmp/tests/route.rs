@@ -0,0 +1,121 @@
+mod common;
+
+use common::*;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use tmp::error::ErrorCode;
+use tmp::ix_data::{DexKind, HopSplit, RouteHop};
+
+struct Setup {
+    context: ProgramTestContext,
+    authority: Keypair,
+    mint: Pubkey,
+    src: Pubkey,
+}
+
+async fn setup() -> Setup {
+    let mut program_test = program_test();
+    let authority = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let src = Pubkey::new_unique();
+
+    add_mint(&mut program_test, &mint, &Pubkey::new_unique());
+    add_token_account(&mut program_test, &src, &mint, &authority.pubkey(), 1_000);
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let ixs = [
+        init_program_ix(&authority.pubkey(), &payer),
+        init_mint_stats_ix(&authority.pubkey(), &payer, &mint, &src, 0),
+    ];
+    process(&mut context, &ixs, &[&authority]).await.unwrap();
+
+    Setup { context, authority, mint, src }
+}
+
+async fn run_route(
+    setup: &mut Setup,
+    hops: Vec<RouteHop>,
+    hop_accounts: Vec<AccountMeta>,
+) -> Result<(), solana_sdk::transaction::TransactionError> {
+    let authority = setup.authority.pubkey();
+    let ixs = [route_ix(&authority, &setup.src, &setup.mint, 100, hops, hop_accounts)];
+    process(&mut setup.context, &ixs, &[&setup.authority]).await
+}
+
+fn orca_hop(accounts_start: u8, accounts_end: u8) -> RouteHop {
+    RouteHop {
+        dex: DexKind::Orca,
+        accounts_start,
+        accounts_end,
+        min_amount_out: None,
+        splits: vec![],
+    }
+}
+
+fn dummy_accounts(n: usize) -> Vec<AccountMeta> {
+    (0..n)
+        .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+        .collect()
+}
+
+#[tokio::test]
+async fn route_without_hops_is_rejected() {
+    let mut setup = setup().await;
+
+    let err = run_route(&mut setup, vec![], vec![]).await.unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::InvalidRoute));
+}
+
+#[tokio::test]
+async fn route_inside_an_open_swap_is_rejected() {
+    let mut setup = setup().await;
+    let authority = setup.authority.pubkey();
+
+    let ixs = [
+        start_swap_ix(&authority, &setup.src, &setup.mint, 100, None),
+        route_ix(&authority, &setup.src, &setup.mint, 100, vec![orca_hop(0, 11)], dummy_accounts(11)),
+    ];
+    let err = process(&mut setup.context, &ixs, &[&setup.authority]).await.unwrap_err();
+    assert_eq!(err, custom_error(1, ErrorCode::InvalidState));
+}
+
+#[tokio::test]
+async fn hop_outside_remaining_accounts_is_rejected() {
+    let mut setup = setup().await;
+
+    let err = run_route(&mut setup, vec![orca_hop(0, 11)], dummy_accounts(5))
+        .await
+        .unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::InvalidRoute));
+}
+
+#[tokio::test]
+async fn hop_with_wrong_account_count_is_rejected() {
+    let mut setup = setup().await;
+
+    // an orca hop takes 11 accounts
+    let err = run_route(&mut setup, vec![orca_hop(0, 10)], dummy_accounts(10))
+        .await
+        .unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::InvalidRoute));
+}
+
+#[tokio::test]
+async fn splits_of_the_whole_input_are_rejected() {
+    let mut setup = setup().await;
+
+    // the main swap must keep a share of the input
+    let mut hop = orca_hop(0, 11);
+    hop.splits.push(HopSplit {
+        dex: DexKind::Orca,
+        accounts_start: 11,
+        accounts_end: 22,
+        share_bps: 10_000,
+    });
+    let err = run_route(&mut setup, vec![hop], dummy_accounts(22))
+        .await
+        .unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::InvalidRoute));
+}
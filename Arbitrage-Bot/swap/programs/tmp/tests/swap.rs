@@ -0,0 +1,337 @@
+mod common;
+
+use common::token_swap::TokenSwapPool;
+use common::*;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+use tmp::error::ErrorCode;
+use tmp::events::{HopExecuted, RouteCompleted, RouteStarted};
+use tmp::ix_data::{DexKind, HopSplit, RouteHop};
+use tmp::state::SwapState;
+
+const USER_BALANCE: u64 = 100_000;
+const SWAP_INPUT: u64 = 10_000;
+
+struct Setup {
+    context: ProgramTestContext,
+    authority: Keypair,
+    mint_a: Pubkey,
+    user_a: Pubkey,
+    user_b: Pubkey,
+    dear_a: TokenSwapPool,  // reserves A:B = 1:2, A sells for ~2 B
+    cheap_a: TokenSwapPool, // reserves A:B = 2:1, B buys ~2 A
+}
+
+// two token-swap pools pricing A differently, so A -> B -> A across them is
+// an arbitrage and the same trip through one pool only pays fees
+async fn setup(swap_state: Option<SwapState>) -> Setup {
+    let mut program_test = program_test();
+    let authority = Keypair::new();
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+    let user_a = Pubkey::new_unique();
+    let user_b = Pubkey::new_unique();
+
+    add_mint(&mut program_test, &mint_a, &Pubkey::new_unique());
+    add_mint(&mut program_test, &mint_b, &Pubkey::new_unique());
+    add_token_account(&mut program_test, &user_a, &mint_a, &authority.pubkey(), USER_BALANCE);
+    add_token_account(&mut program_test, &user_b, &mint_b, &authority.pubkey(), 0);
+
+    let dear_a = TokenSwapPool::add(&mut program_test, &mint_a, &mint_b, 1_000_000, 2_000_000);
+    let cheap_a = TokenSwapPool::add(&mut program_test, &mint_a, &mint_b, 2_000_000, 1_000_000);
+
+    let preloaded = swap_state.is_some();
+    if let Some(swap_state) = swap_state {
+        add_swap_state(&mut program_test, &authority.pubkey(), swap_state);
+    }
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let mut ixs = vec![dear_a.initialize_ix(), cheap_a.initialize_ix()];
+    if !preloaded {
+        ixs.push(init_program_ix(&authority.pubkey(), &payer));
+    }
+    ixs.push(init_mint_stats_ix(&authority.pubkey(), &payer, &mint_a, &user_a, 0));
+    process(&mut context, &ixs, &[&authority]).await.unwrap();
+
+    Setup { context, authority, mint_a, user_a, user_b, dear_a, cheap_a }
+}
+
+impl Setup {
+    fn start_swap_ix(&self, swap_input: u64) -> Instruction {
+        start_swap_ix(&self.authority.pubkey(), &self.user_a, &self.mint_a, swap_input, None)
+    }
+
+    fn profit_or_revert_ix(&self) -> Instruction {
+        profit_or_revert_ix(&self.authority.pubkey(), &self.user_a, &self.mint_a, None)
+    }
+
+    fn sell_a_ix(&self, pool: &TokenSwapPool) -> Instruction {
+        let authority = self.authority.pubkey();
+        pool.orca_swap_ix(&authority, &self.user_a, &self.user_b, &self.mint_a, None)
+    }
+
+    fn buy_a_ix(&self, pool: &TokenSwapPool) -> Instruction {
+        let authority = self.authority.pubkey();
+        pool.orca_swap_ix(&authority, &self.user_b, &self.user_a, &pool.mint_b, None)
+    }
+
+    async fn process(&mut self, ixs: &[Instruction]) -> Result<(), TransactionError> {
+        process(&mut self.context, ixs, &[&self.authority]).await
+    }
+
+    async fn process_logs(&mut self, ixs: &[Instruction]) -> Vec<String> {
+        process_logs(&mut self.context, ixs, &[&self.authority]).await
+    }
+
+    async fn balances(&mut self) -> (u64, u64) {
+        let (user_a, user_b) = (self.user_a, self.user_b);
+        (
+            token_balance(&mut self.context, &user_a).await,
+            token_balance(&mut self.context, &user_b).await,
+        )
+    }
+
+    async fn swap_state(&mut self) -> SwapState {
+        let authority = self.authority.pubkey();
+        swap_state(&mut self.context, &authority).await
+    }
+}
+
+#[tokio::test]
+async fn arbitrage_across_pools_keeps_profit() {
+    let mut setup = setup(None).await;
+
+    let ixs = [
+        setup.start_swap_ix(SWAP_INPUT),
+        setup.sell_a_ix(&setup.dear_a),
+        setup.buy_a_ix(&setup.cheap_a),
+        setup.profit_or_revert_ix(),
+    ];
+    setup.process(&ixs).await.unwrap();
+
+    // every B bought is sold again
+    let (balance_a, balance_b) = setup.balances().await;
+    assert!(balance_a > USER_BALANCE);
+    assert_eq!(balance_b, 0);
+
+    let profit = balance_a - USER_BALANCE;
+    let state = setup.swap_state().await;
+    assert!(!state.is_valid);
+    assert_eq!(state.total_swaps, 1);
+    assert_eq!(state.total_volume, SWAP_INPUT);
+    assert_eq!(state.total_profit, profit);
+
+    let authority = setup.authority.pubkey();
+    let mint_a = setup.mint_a;
+    let stats = mint_stats(&mut setup.context, &authority, &mint_a).await;
+    assert_eq!(stats.total_profit, profit);
+}
+
+#[tokio::test]
+async fn hop_output_is_input_of_next_hop() {
+    let mut setup = setup(None).await;
+
+    let ixs = [setup.start_swap_ix(SWAP_INPUT), setup.sell_a_ix(&setup.dear_a)];
+    setup.process(&ixs).await.unwrap();
+
+    let (balance_a, balance_b) = setup.balances().await;
+    assert_eq!(balance_a, USER_BALANCE - SWAP_INPUT);
+    assert!(balance_b > 0);
+
+    let state = setup.swap_state().await;
+    assert!(state.is_valid);
+    assert_eq!(state.start_balance, USER_BALANCE);
+    assert_eq!(state.swap_input, balance_b);
+}
+
+#[tokio::test]
+async fn round_trip_through_one_pool_has_no_profit() {
+    let mut setup = setup(None).await;
+
+    let ixs = [
+        setup.start_swap_ix(SWAP_INPUT),
+        setup.sell_a_ix(&setup.dear_a),
+        setup.buy_a_ix(&setup.dear_a),
+        setup.profit_or_revert_ix(),
+    ];
+    let err = setup.process(&ixs).await.unwrap_err();
+    assert_eq!(err, custom_error(3, ErrorCode::NoProfit));
+}
+
+#[tokio::test]
+async fn hop_without_start_swap_is_rejected() {
+    let mut setup = setup(None).await;
+
+    let ixs = [setup.sell_a_ix(&setup.dear_a)];
+    let err = setup.process(&ixs).await.unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::InvalidState));
+}
+
+#[tokio::test]
+async fn start_swap_without_input_is_rejected() {
+    let mut setup = setup(None).await;
+
+    let ixs = [setup.start_swap_ix(0)];
+    let err = setup.process(&ixs).await.unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::InvalidAmount));
+}
+
+#[tokio::test]
+async fn second_start_swap_is_rejected() {
+    let mut setup = setup(None).await;
+
+    let ixs = [setup.start_swap_ix(SWAP_INPUT), setup.start_swap_ix(SWAP_INPUT)];
+    let err = setup.process(&ixs).await.unwrap_err();
+    assert_eq!(err, custom_error(1, ErrorCode::InvalidState));
+}
+
+#[tokio::test]
+async fn total_volume_overflow_is_rejected() {
+    let mut setup = setup(Some(SwapState {
+        total_volume: u64::MAX - SWAP_INPUT + 1,
+        ..Default::default()
+    }))
+    .await;
+
+    let ixs = [setup.start_swap_ix(SWAP_INPUT)];
+    let err = setup.process(&ixs).await.unwrap_err();
+    assert_eq!(err, custom_error(0, ErrorCode::Overflow));
+}
+
+#[tokio::test]
+async fn route_matches_separate_hops() {
+    let mut routed = setup(None).await;
+    let authority = routed.authority.pubkey();
+    let (user_a, user_b, mint_a) = (routed.user_a, routed.user_b, routed.mint_a);
+
+    let mut hop_accounts = routed.dear_a.route_hop_accounts(&authority, &user_a, &user_b, &mint_a);
+    let second_hop_start = hop_accounts.len() as u8;
+    let mint_b = routed.cheap_a.mint_b;
+    hop_accounts.extend(routed.cheap_a.route_hop_accounts(&authority, &user_b, &user_a, &mint_b));
+    let hops = vec![
+        RouteHop {
+            dex: DexKind::Orca,
+            accounts_start: 0,
+            accounts_end: second_hop_start,
+            min_amount_out: None,
+            splits: vec![],
+        },
+        RouteHop {
+            dex: DexKind::Orca,
+            accounts_start: second_hop_start,
+            accounts_end: hop_accounts.len() as u8,
+            min_amount_out: None,
+            splits: vec![],
+        },
+    ];
+    let ixs = [route_ix(&authority, &user_a, &mint_a, SWAP_INPUT, hops, hop_accounts)];
+    let logs = routed.process_logs(&ixs).await;
+    let (route_balance_a, route_balance_b) = routed.balances().await;
+
+    let hop_events = events::<HopExecuted>(&logs);
+    assert_eq!(hop_events.len(), 2);
+    assert_eq!(hop_events[1].amount_in, hop_events[0].amount_out);
+    assert_eq!(events::<RouteCompleted>(&logs)[0].profit, route_balance_a - USER_BALANCE);
+
+    // same trip with one instruction per hop, on fresh pools
+    let mut separate = setup(None).await;
+    let ixs = [
+        separate.start_swap_ix(SWAP_INPUT),
+        separate.sell_a_ix(&separate.dear_a),
+        separate.buy_a_ix(&separate.cheap_a),
+        separate.profit_or_revert_ix(),
+    ];
+    separate.process(&ixs).await.unwrap();
+
+    assert_eq!(separate.balances().await, (route_balance_a, route_balance_b));
+    let state = routed.swap_state().await;
+    assert!(!state.is_valid);
+    assert_eq!(state.total_profit, route_balance_a - USER_BALANCE);
+}
+
+#[tokio::test]
+async fn route_is_reported_by_events() {
+    let mut setup = setup(None).await;
+
+    let ixs = [
+        setup.start_swap_ix(SWAP_INPUT),
+        setup.sell_a_ix(&setup.dear_a),
+        setup.buy_a_ix(&setup.cheap_a),
+        setup.profit_or_revert_ix(),
+    ];
+    let logs = setup.process_logs(&ixs).await;
+    let (balance_a, balance_b) = setup.balances().await;
+    let authority = setup.authority.pubkey();
+
+    let started = events::<RouteStarted>(&logs);
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0].authority, authority);
+    assert_eq!(started[0].mint, setup.mint_a);
+    assert_eq!(started[0].swap_input, SWAP_INPUT);
+    assert_eq!(started[0].flash_borrowed, 0);
+
+    // output of a hop is the input of the next one
+    let hops = events::<HopExecuted>(&logs);
+    assert_eq!(hops.len(), 2);
+    assert!(hops.iter().all(|hop| hop.dex == DexKind::Orca));
+    assert_eq!(hops[0].amount_in, SWAP_INPUT);
+    assert_eq!(hops[1].amount_in, hops[0].amount_out);
+    assert_eq!(balance_b, 0);
+
+    let completed = events::<RouteCompleted>(&logs);
+    assert_eq!(completed.len(), 1);
+    assert_eq!(completed[0].mint, setup.mint_a);
+    assert_eq!(completed[0].profit, balance_a - USER_BALANCE);
+    assert_eq!(completed[0].profit, hops[1].amount_out - SWAP_INPUT);
+}
+
+#[tokio::test]
+async fn split_hop_sums_the_outputs_of_its_pools() {
+    let mut setup = setup(None).await;
+    let authority = setup.authority.pubkey();
+    let (user_a, user_b, mint_a) = (setup.user_a, setup.user_b, setup.mint_a);
+    let mint_b = setup.cheap_a.mint_b;
+
+    // sell A on both pools (90% / 10%), buy it back on the cheap one
+    let mut hop_accounts = setup.dear_a.route_hop_accounts(&authority, &user_a, &user_b, &mint_a);
+    let split_start = hop_accounts.len() as u8;
+    hop_accounts.extend(setup.cheap_a.route_hop_accounts(&authority, &user_a, &user_b, &mint_a));
+    let second_hop_start = hop_accounts.len() as u8;
+    hop_accounts.extend(setup.cheap_a.route_hop_accounts(&authority, &user_b, &user_a, &mint_b));
+    let hops = vec![
+        RouteHop {
+            dex: DexKind::Orca,
+            accounts_start: 0,
+            accounts_end: split_start,
+            min_amount_out: None,
+            splits: vec![HopSplit {
+                dex: DexKind::Orca,
+                accounts_start: split_start,
+                accounts_end: second_hop_start,
+                share_bps: 1_000,
+            }],
+        },
+        RouteHop {
+            dex: DexKind::Orca,
+            accounts_start: second_hop_start,
+            accounts_end: hop_accounts.len() as u8,
+            min_amount_out: None,
+            splits: vec![],
+        },
+    ];
+    let ixs = [route_ix(&authority, &user_a, &mint_a, SWAP_INPUT, hops, hop_accounts)];
+    let logs = setup.process_logs(&ixs).await;
+    let (balance_a, balance_b) = setup.balances().await;
+
+    // one event per pool of the split hop
+    let swaps = events::<HopExecuted>(&logs);
+    assert_eq!(swaps.len(), 3);
+    assert_eq!(swaps[0].amount_in, SWAP_INPUT * 9 / 10);
+    assert_eq!(swaps[1].amount_in, SWAP_INPUT / 10);
+    assert_eq!(swaps[2].amount_in, swaps[0].amount_out + swaps[1].amount_out);
+    assert_eq!(balance_b, 0);
+    assert_eq!(events::<RouteCompleted>(&logs)[0].profit, balance_a - USER_BALANCE);
+}
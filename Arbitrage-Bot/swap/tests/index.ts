@@ -69,7 +69,7 @@ describe("tmp", () => {
 
     it("sets up the info pda", async () => {
         const [state_pda, sb] = await anchor.web3.PublicKey.findProgramAddress(
-            [Buffer.from("swap_state")],
+            [Buffer.from("swap_state"), wallet.publicKey.toBuffer()],
             program.programId
         );    
         console.log("pda:", state_pda.toString())
@@ -79,9 +79,11 @@ describe("tmp", () => {
             await program.rpc.initProgram({
                 accounts: {
                     swapState: state_pda, 
+                    authority: wallet.publicKey,
                     payer: provider.wallet.publicKey,
                     systemProgram: web3.SystemProgram.programId,
-                }
+                },
+                signers: [wallet],
             });
         } else { 
             console.log("pda already initialized...")